@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use osu_parser::Beatmap;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(parsed) = Beatmap::from_bytes(data) {
+        assert_eq!(parsed.to_string().as_bytes(), data);
+    }
+});
@@ -1,13 +1,18 @@
 use std::{ffi::OsString, path::PathBuf};
 
-use crate::section::{
-    Colour, Difficulty, Editor, Events, General, HitObject, Metadata, TimingPoint,
+use crate::{
+    error::{Error, Result},
+    section::{
+        Colour, Difficulty, Editor, Events, General, HitObject, Metadata, TimingIndex, TimingPoint,
+    },
+    source::{Entry, SectionEntries, Source},
+    token::Section,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Beatmap {
-    file_name: String,
-    directory: OsString,
+    pub(crate) file_name: Option<String>,
+    directory: Option<OsString>,
     version: u8,
     no_spinners: bool,
     pub(crate) general: General,
@@ -16,14 +21,16 @@ pub struct Beatmap {
     pub(crate) editor: Editor,
     pub(crate) events: Events,
     pub(crate) timing_points: Vec<TimingPoint>,
+    pub(crate) timing_index: TimingIndex,
     pub(crate) colours: Vec<Colour>,
     pub(crate) hit_objects: Vec<HitObject>,
+    pub(crate) source: Option<Source>,
+    lossless: bool,
 }
 
 impl Beatmap {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
-        file_name: String,
-        directory: OsString,
         version: u8,
         general: General,
         editor: Editor,
@@ -35,8 +42,8 @@ impl Beatmap {
         hit_objects: Vec<HitObject>,
     ) -> Self {
         Beatmap {
-            file_name,
-            directory,
+            file_name: None,
+            directory: None,
             version,
             no_spinners: false,
             general,
@@ -44,88 +51,185 @@ impl Beatmap {
             metadata,
             editor,
             events,
+            timing_index: TimingIndex::new(&timing_points),
             timing_points,
             colours,
             hit_objects,
+            source: None,
+            lossless: true,
         }
     }
 
-    pub fn save_with_name(&self, name: &str) {
-        std::fs::write(name, self.to_string()).unwrap();
+    pub fn save_with_name(&self, name: &str) -> Result<()> {
+        std::fs::write(name, self.to_string())?;
+
+        Ok(())
     }
 
-    pub fn save_to_directory_with_name(&self, name: &str) -> std::io::Result<()> {
-        std::fs::write(PathBuf::from(&self.directory).join(name), self.to_string())?;
+    pub fn save_to_directory_with_name(&self, name: &str) -> Result<()> {
+        let directory = self.directory.clone().unwrap_or_default();
+        std::fs::write(PathBuf::from(directory).join(name), self.to_string())?;
 
         Ok(())
     }
 
-    pub fn save(&self) {
-        self.save_with_name(self.get_file_name());
+    /// Saves to the file name the beatmap was loaded from or given with
+    /// [`Beatmap::change_file_name`].
+    pub fn save(&self) -> Result<()> {
+        self.save_with_name(self.required_file_name()?)
     }
 
-    pub fn save_to_directory(&self) -> std::io::Result<()> {
-        self.save_to_directory_with_name(self.get_file_name())
+    pub fn save_to_directory(&self) -> Result<()> {
+        self.save_to_directory_with_name(self.required_file_name()?)
+    }
+
+    fn required_file_name(&self) -> Result<&str> {
+        self.get_file_name().ok_or_else(|| {
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Beatmap has no file name",
+            ))
+        })
+    }
+
+    /// The `osu file format` version the beatmap was written in.
+    pub fn get_version(&self) -> u8 {
+        self.version
     }
 
     pub fn get_hit_objects(&self) -> &Vec<HitObject> {
         &self.hit_objects
     }
 
+    pub fn get_hit_objects_mut(&mut self) -> &mut Vec<HitObject> {
+        &mut self.hit_objects
+    }
+
+    pub fn iter_hit_objects(&self) -> std::slice::Iter<'_, HitObject> {
+        self.hit_objects.iter()
+    }
+
+    pub fn iter_hit_objects_mut(&mut self) -> std::slice::IterMut<'_, HitObject> {
+        self.hit_objects.iter_mut()
+    }
+
     pub fn get_timing_points(&self) -> &Vec<TimingPoint> {
         &self.timing_points
     }
 
-    pub fn get_directory(&self) -> &OsString {
-        &self.directory
+    pub fn get_directory(&self) -> Option<&OsString> {
+        self.directory.as_ref()
+    }
+
+    pub fn get_file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    pub fn change_directory<D: Into<OsString>>(&mut self, directory: D) {
+        self.directory = Some(directory.into());
     }
 
-    pub fn get_file_name(&self) -> &str {
-        &self.file_name
+    pub fn change_file_name(&mut self, file_name: &str) {
+        self.file_name = Some(file_name.to_string());
     }
 
     pub fn toggle_spinners(&mut self) {
         self.no_spinners = !self.no_spinners;
     }
+
+    /// Whether a parsed beatmap is written back with its original formatting,
+    /// comments and unknown keys, only rewriting the lines that changed.
+    pub fn is_lossless(&self) -> bool {
+        self.lossless && self.source.is_some()
+    }
+
+    /// Lossless output is on by default. Turn it off to write every section
+    /// in canonical form.
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
+    pub(crate) fn section_entries(&self) -> Vec<SectionEntries> {
+        let section = |section: Section, entries: Vec<Entry>| SectionEntries {
+            header: section.header(),
+            entries,
+        };
+
+        vec![
+            section(Section::General, Entry::keyed(&self.general.to_string())),
+            section(Section::Editor, Entry::keyed(&self.editor.to_string())),
+            section(Section::Metadata, Entry::keyed(&self.metadata.to_string())),
+            section(
+                Section::Difficulty,
+                Entry::keyed(&self.difficulty.to_string()),
+            ),
+            section(Section::Events, self.events.entries()),
+            section(
+                Section::TimingPoints,
+                self.timing_points.iter().map(Entry::item).collect(),
+            ),
+            section(
+                Section::Colours,
+                self.colours.iter().map(Entry::item).collect(),
+            ),
+            section(
+                Section::HitObjects,
+                self.hit_objects
+                    .iter()
+                    .filter(|hit_object| {
+                        !(self.no_spinners && matches!(hit_object, HitObject::Spinner(_)))
+                    })
+                    .map(Entry::item)
+                    .collect(),
+            ),
+        ]
+    }
 }
 
 impl std::fmt::Display for Beatmap {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut display_string = format!(
-            "osu file format v{}\n\n{}\n\n{}\n\n{}\n\n{}\n\n{}",
-            self.version, self.general, self.editor, self.metadata, self.difficulty, self.events,
-        );
-
-        if self.colours.len() > 0 {
-            display_string += "\n\n[Colours]\n";
-            for colour in &self.colours {
-                display_string += &format!("{}", colour);
-            }
+        if let (true, Some(source)) = (self.lossless, &self.source) {
+            return write!(f, "{}", source.render(&self.section_entries()));
         }
 
-        if self.timing_points.len() > 0 {
-            display_string += "\n\n[TimingPoints]\n";
-            for timing_point in &self.timing_points {
-                display_string += &format!("{}", timing_point);
+        let mut sections = vec![
+            self.general.to_string(),
+            self.editor.to_string(),
+            self.metadata.to_string(),
+            self.difficulty.to_string(),
+            self.events.to_string(),
+        ];
+
+        let mut list = |header: Section, items: Vec<String>| {
+            if !items.is_empty() {
+                sections.push(format!("{}\n{}", header.header(), items.concat()));
             }
-        }
-
-        if self.hit_objects.len() > 0 {
-            display_string += "\n\n[HitObjects]\n";
-
-            for hit_object in &self.hit_objects {
-                match hit_object {
-                    HitObject::Spinner(_) => {
-                        if self.no_spinners {
-                            continue;
-                        }
-                    }
-                    _ => {}
-                }
+        };
+        list(
+            Section::TimingPoints,
+            self.timing_points.iter().map(|t| t.to_string()).collect(),
+        );
+        list(
+            Section::Colours,
+            self.colours.iter().map(|c| c.to_string()).collect(),
+        );
+        list(
+            Section::HitObjects,
+            self.hit_objects
+                .iter()
+                .filter(|hit_object| {
+                    !(self.no_spinners && matches!(hit_object, HitObject::Spinner(_)))
+                })
+                .map(|h| h.to_string())
+                .collect(),
+        );
 
-                display_string += &format!("{}", hit_object);
-            }
-        }
+        let mut display_string = format!("osu file format v{}\n\n", self.version);
+        display_string += &sections
+            .into_iter()
+            .filter(|section| !section.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
 
         write!(f, "{}", display_string)
     }
@@ -0,0 +1,406 @@
+//! Constructs beatmaps from scratch instead of parsing them.
+
+use crate::{
+    error::{Error, Result},
+    section::{
+        Colour, DifficultyBuilder, Editor, Events, GameMode, GeneralBuilder, HitObject,
+        HitObjectBuilder, MetadataBuilder, TimingPointBuilder,
+    },
+    utils::Color,
+    Beatmap,
+};
+
+/// The most combo colours osu! will use.
+const MAX_COMBO_COLOURS: usize = 8;
+
+/// Fails with a build error carrying `message` unless `condition` holds.
+pub(crate) fn ensure<S: Into<String>>(condition: bool, message: S) -> Result<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::build(message))
+    }
+}
+
+/// Says which part of the beatmap a build error came from.
+fn within(error: Error, context: &str) -> Error {
+    match error {
+        Error::Build(message) => Error::build(format!("{}: {}", context, message)),
+        error => error,
+    }
+}
+
+/// Builds a [`Beatmap`] section by section. Nothing is checked until
+/// [`BeatmapBuilder::build`], which reports the first invalid value.
+///
+/// ```
+/// use osu_parser::{BeatmapBuilder, CircleBuilder, GeneralBuilder, MetadataBuilder, TimingPointBuilder};
+///
+/// let beatmap = BeatmapBuilder::new()
+///     .general(GeneralBuilder::new().audio_file("audio.mp3"))
+///     .metadata(
+///         MetadataBuilder::new()
+///             .title("Title")
+///             .artist("Artist")
+///             .creator("Creator")
+///             .version("Normal"),
+///     )
+///     .timing_point(TimingPointBuilder::uninherited(0, 500.0))
+///     .hit_object(CircleBuilder::new(256, 192, 1000).new_combo(true))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(beatmap.get_hit_objects().len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct BeatmapBuilder {
+    general: GeneralBuilder,
+    metadata: MetadataBuilder,
+    difficulty: DifficultyBuilder,
+    timing_points: Vec<TimingPointBuilder>,
+    combo_colours: Vec<(i32, i32, i32)>,
+    hit_objects: Vec<HitObjectBuilder>,
+}
+
+impl BeatmapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn general(mut self, general: GeneralBuilder) -> Self {
+        self.general = general;
+        self
+    }
+
+    pub fn metadata(mut self, metadata: MetadataBuilder) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn difficulty(mut self, difficulty: DifficultyBuilder) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// Timing points must be added in time order, starting with an uninherited one.
+    pub fn timing_point(mut self, timing_point: TimingPointBuilder) -> Self {
+        self.timing_points.push(timing_point);
+        self
+    }
+
+    /// Adds the next combo colour, numbered from `Combo1`.
+    pub fn combo_colour(mut self, r: i32, g: i32, b: i32) -> Self {
+        self.combo_colours.push((r, g, b));
+        self
+    }
+
+    /// Hit objects must be added in time order.
+    pub fn hit_object<H: Into<HitObjectBuilder>>(mut self, hit_object: H) -> Self {
+        self.hit_objects.push(hit_object.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Beatmap> {
+        let general = self.general.build().map_err(|e| within(e, "[General]"))?;
+        let metadata = self.metadata.build().map_err(|e| within(e, "[Metadata]"))?;
+        let difficulty = self
+            .difficulty
+            .build()
+            .map_err(|e| within(e, "[Difficulty]"))?;
+
+        if let Some(first) = self.timing_points.first() {
+            ensure(
+                first.is_uninherited(),
+                "timing point 0: the first timing point must be uninherited",
+            )?;
+        }
+        ensure(
+            self.hit_objects.is_empty() || !self.timing_points.is_empty(),
+            "hit objects need at least one timing point",
+        )?;
+
+        let mut timing_points = Vec::new();
+        let mut previous_time = i32::MIN;
+        for (index, timing_point) in self.timing_points.into_iter().enumerate() {
+            let context = format!("timing point {}", index);
+            let time = timing_point.get_time();
+            ensure(time >= previous_time, "timing points must be in time order")
+                .map_err(|e| within(e, &context))?;
+            previous_time = time;
+            timing_points.push(timing_point.build().map_err(|e| within(e, &context))?);
+        }
+
+        ensure(
+            self.combo_colours.len() <= MAX_COMBO_COLOURS,
+            format!("at most {} combo colours are allowed", MAX_COMBO_COLOURS),
+        )?;
+        let mut colours = Vec::new();
+        for (index, (r, g, b)) in self.combo_colours.into_iter().enumerate() {
+            ensure(
+                [r, g, b].iter().all(|c| (0..=255).contains(c)),
+                format!(
+                    "combo colour {}: components must be between 0 and 255",
+                    index
+                ),
+            )?;
+            colours.push(Colour::ComboColor(index as i32 + 1, Color::new(r, g, b)));
+        }
+
+        let is_mania = general.mode == Some(GameMode::Mania);
+        let mut hit_objects = Vec::new();
+        let mut previous_time = i32::MIN;
+        for (index, hit_object) in self.hit_objects.into_iter().enumerate() {
+            let context = format!("hit object {}", index);
+            let time = hit_object.get_time();
+            ensure(time >= previous_time, "hit objects must be in time order")
+                .map_err(|e| within(e, &context))?;
+            previous_time = time;
+
+            let hit_object = hit_object.build().map_err(|e| within(e, &context))?;
+            let allowed = match hit_object {
+                HitObject::Circle(_) => true,
+                HitObject::Slider(_) | HitObject::Spinner(_) => !is_mania,
+                HitObject::Hold(_) => is_mania,
+            };
+            ensure(
+                allowed,
+                "this kind of hit object is not allowed in this mode",
+            )
+            .map_err(|e| within(e, &context))?;
+            hit_objects.push(hit_object);
+        }
+
+        Ok(Beatmap::new(
+            14,
+            general,
+            Editor::default(),
+            metadata,
+            difficulty,
+            Events::default(),
+            timing_points,
+            colours,
+            hit_objects,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CircleBuilder, CurveType, EdgeSet, HitSample, HitSound, HoldBuilder, SampleBank,
+        SliderBuilder, SpinnerBuilder,
+    };
+
+    fn builder() -> BeatmapBuilder {
+        BeatmapBuilder::new()
+            .general(GeneralBuilder::new().audio_file("audio.mp3"))
+            .metadata(
+                MetadataBuilder::new()
+                    .title("Title")
+                    .artist("Artist")
+                    .creator("Creator")
+                    .version("Normal"),
+            )
+            .timing_point(TimingPointBuilder::uninherited(0, 500.0))
+    }
+
+    fn message(result: Result<Beatmap>) -> String {
+        match result {
+            Err(Error::Build(message)) => message,
+            other => panic!("expected a build error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let beatmap = builder()
+            .difficulty(DifficultyBuilder::new().circle_size(4.0).approach_rate(9.0))
+            .timing_point(TimingPointBuilder::inherited(2000, 2.0).kiai(true))
+            .combo_colour(255, 128, 0)
+            .combo_colour(0, 128, 255)
+            .hit_object(CircleBuilder::new(256, 192, 1000).new_combo(true))
+            .hit_object(
+                SliderBuilder::new(100, 100, 2000, CurveType::Bezier, 140.0)
+                    .point(200, 100)
+                    .point(300, 150)
+                    .slides(2)
+                    .edge(
+                        HitSound::WHISTLE,
+                        EdgeSet::new(SampleBank::Normal, SampleBank::Auto),
+                    )
+                    .edge(
+                        HitSound::NONE,
+                        EdgeSet::new(SampleBank::Auto, SampleBank::Auto),
+                    )
+                    .edge(
+                        HitSound::CLAP,
+                        EdgeSet::new(SampleBank::Soft, SampleBank::Drum),
+                    ),
+            )
+            .hit_object(
+                CircleBuilder::new(0, 0, 2500)
+                    .hit_sound(HitSound::FINISH)
+                    .hit_sample(
+                        HitSample::new(SampleBank::Normal, SampleBank::Soft, 0, 70)
+                            .with_filename("clap.wav"),
+                    ),
+            )
+            .hit_object(SpinnerBuilder::new(3000, 4000).combo_skip(2))
+            .build()
+            .unwrap();
+
+        let osu_data = beatmap.to_string();
+        assert!(
+            osu_data.starts_with("osu file format v14\n\n[General]\nAudioFilename: audio.mp3\n")
+        );
+        assert!(osu_data.contains("\nBeatmapSetID:-1\n\n[Difficulty]\n"));
+        assert!(osu_data.contains("[TimingPoints]\n0,500,4,0,0,100,1,0\n2000,-50,4,0,0,100,0,1\n"));
+        assert!(osu_data.contains("[Colours]\nCombo1 :255,128,0\nCombo2 :0,128,255\n"));
+        assert!(osu_data.ends_with(
+            "[HitObjects]\n\
+             256,192,1000,5,0,0:0:0:0:\n\
+             100,100,2000,2,0,B|200:100|300:150,2,140,2|0|8,1:0|0:0|2:3,0:0:0:0:\n\
+             0,0,2500,1,4,1:2:0:70:clap.wav\n\
+             256,192,3000,44,0,4000,0:0:0:0:\n"
+        ));
+        assert!(!osu_data.contains("\n\n\n"));
+
+        let parsed: Beatmap = osu_data.parse().unwrap();
+        assert_eq!(parsed.to_string(), osu_data);
+        assert_eq!(parsed.get_metadata_unicode_title(), "Title");
+        assert_eq!(parsed.get_circle_size(), Some(4.0));
+    }
+
+    #[test]
+    fn test_mania() {
+        let beatmap = builder()
+            .general(
+                GeneralBuilder::new()
+                    .audio_file("audio.mp3")
+                    .mode(GameMode::Mania),
+            )
+            .hit_object(CircleBuilder::new(64, 192, 0))
+            .hit_object(HoldBuilder::new(192, 500, 1000))
+            .build()
+            .unwrap();
+        assert!(beatmap
+            .to_string()
+            .ends_with("64,192,0,1,0,0:0:0:0:\n192,192,500,128,0,1000:0:0:0:0:\n"));
+
+        let error = message(
+            builder()
+                .hit_object(HoldBuilder::new(192, 500, 1000))
+                .build(),
+        );
+        assert_eq!(
+            error,
+            "hit object 0: this kind of hit object is not allowed in this mode"
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            message(BeatmapBuilder::new().build()),
+            "[General]: AudioFilename is required"
+        );
+        assert_eq!(
+            message(
+                builder()
+                    .metadata(MetadataBuilder::new().title("タイトル"))
+                    .build()
+            ),
+            "[Metadata]: Artist is required"
+        );
+        assert_eq!(
+            message(
+                builder()
+                    .difficulty(DifficultyBuilder::new().circle_size(11.0))
+                    .build()
+            ),
+            "[Difficulty]: CircleSize must be between 0 and 10"
+        );
+        assert_eq!(
+            message(
+                BeatmapBuilder::new()
+                    .general(GeneralBuilder::new().audio_file("audio.mp3"))
+                    .metadata(
+                        MetadataBuilder::new()
+                            .title("Title")
+                            .artist("Artist")
+                            .creator("Creator")
+                            .version("Normal"),
+                    )
+                    .hit_object(CircleBuilder::new(0, 0, 0))
+                    .build()
+            ),
+            "hit objects need at least one timing point"
+        );
+        assert_eq!(
+            message(
+                builder()
+                    .timing_point(TimingPointBuilder::inherited(-10, 1.0))
+                    .build()
+            ),
+            "timing point 1: timing points must be in time order"
+        );
+        assert_eq!(
+            message(
+                builder()
+                    .hit_object(CircleBuilder::new(0, 0, 100))
+                    .hit_object(SliderBuilder::new(0, 0, 200, CurveType::Linear, 100.0))
+                    .build()
+            ),
+            "hit object 1: slider needs at least one curve point"
+        );
+        assert_eq!(
+            message(
+                builder()
+                    .hit_object(
+                        SliderBuilder::new(0, 0, 200, CurveType::Linear, 100.0)
+                            .point(100, 0)
+                            .edge(
+                                HitSound::NONE,
+                                EdgeSet::new(SampleBank::Auto, SampleBank::Auto)
+                            )
+                    )
+                    .build()
+            ),
+            "hit object 0: slider with 1 slides needs 2 edges"
+        );
+        assert_eq!(
+            message(
+                builder()
+                    .hit_object(
+                        SliderBuilder::new(0, 0, 200, CurveType::Linear, 100.0)
+                            .point(100, 0)
+                            .slides(i32::MAX)
+                    )
+                    .build()
+            ),
+            "hit object 0: slider can have at most 9000 slides"
+        );
+        assert_eq!(
+            message(
+                builder()
+                    .hit_object(CircleBuilder::new(0, 0, 100).combo_skip(1))
+                    .build()
+            ),
+            "hit object 0: combo colour skip needs a new combo"
+        );
+        assert_eq!(
+            message(builder().hit_object(SpinnerBuilder::new(100, 100)).build()),
+            "hit object 0: spinner must end after it starts"
+        );
+
+        let mut too_many = builder();
+        for _ in 0..9 {
+            too_many = too_many.combo_colour(0, 0, 0);
+        }
+        assert_eq!(
+            message(too_many.build()),
+            "at most 8 combo colours are allowed"
+        );
+    }
+}
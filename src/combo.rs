@@ -0,0 +1,177 @@
+//! Works out the combo each hit object belongs to, the number drawn on it
+//! and the colour it is drawn in, like osu! does.
+
+use crate::{section::Colour, utils::Color, Beatmap};
+
+/// The combo colours of the default skin, used when a beatmap has none.
+const DEFAULT_COMBO_COLOURS: [(i32, i32, i32); 4] =
+    [(255, 192, 0), (0, 202, 0), (18, 124, 255), (242, 24, 57)];
+
+/// A hit object's place in the combos of its beatmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComboInfo {
+    combo_index: usize,
+    combo_number: usize,
+    colour_index: usize,
+    colour: Color,
+}
+
+impl ComboInfo {
+    /// How many combos start before this object's, ignoring colour skips.
+    pub fn get_combo_index(&self) -> usize {
+        self.combo_index
+    }
+
+    /// The number drawn on the object, starting at 1 for each combo.
+    pub fn get_combo_number(&self) -> usize {
+        self.combo_number
+    }
+
+    /// Which of [`Beatmap::get_combo_colours`] the object is drawn in.
+    pub fn get_colour_index(&self) -> usize {
+        self.colour_index
+    }
+
+    pub fn get_colour(&self) -> Color {
+        self.colour
+    }
+}
+
+impl Beatmap {
+    /// The combo colours in the order they are used, falling back to the
+    /// default skin's when the beatmap has none.
+    pub fn get_combo_colours(&self) -> Vec<Color> {
+        let colours: Vec<Color> = self
+            .colours
+            .iter()
+            .filter_map(|colour| match colour {
+                Colour::ComboColor(_, color) => Some(*color),
+                _ => None,
+            })
+            .collect();
+
+        if colours.is_empty() {
+            DEFAULT_COMBO_COLOURS
+                .iter()
+                .map(|&(r, g, b)| Color::new(r, g, b))
+                .collect()
+        } else {
+            colours
+        }
+    }
+
+    /// The combo of each hit object, in file order.
+    ///
+    /// The first object and the first object after a spinner always start
+    /// a new combo. Spinners carry on the combo before them, but pass
+    /// their colour skip on to the next combo.
+    pub fn get_combo_info(&self) -> Vec<ComboInfo> {
+        let colours = self.get_combo_colours();
+        let mut combo_index = 0;
+        let mut combo_number = 0;
+        // The colour of the combo before the first, so skips apply to it
+        let mut colour_offset = colours.len() - 1;
+        let mut after_spinner = false;
+        // Colour skips from spinners, added to the next combo
+        let mut extra_skip = 0;
+        // Format v8 and below always start a new combo after a spinner
+        let force_after_spinner = self.get_version() <= 8;
+        let mut force_new_combo = false;
+
+        self.hit_objects
+            .iter()
+            .enumerate()
+            .map(|(index, hit_object)| {
+                let object_type = hit_object.get_object_type();
+                let is_first = index == 0;
+
+                let (new_combo, skip) = if object_type.is_spinner() {
+                    force_new_combo |= force_after_spinner || object_type.new_combo();
+                    extra_skip += object_type.combo_skip() as usize;
+                    after_spinner = true;
+                    (is_first, 0)
+                } else {
+                    let new_combo =
+                        is_first || after_spinner || force_new_combo || object_type.new_combo();
+                    let skip = object_type.combo_skip() as usize + extra_skip;
+                    after_spinner = false;
+                    force_new_combo = false;
+                    extra_skip = 0;
+                    (new_combo, skip)
+                };
+
+                if new_combo {
+                    if !is_first {
+                        combo_index += 1;
+                    }
+                    combo_number = 1;
+                    colour_offset = (colour_offset + skip + 1) % colours.len();
+                } else {
+                    combo_number += 1;
+                }
+
+                ComboInfo {
+                    combo_index,
+                    combo_number,
+                    colour_index: colour_offset,
+                    colour: colours[colour_offset],
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Beatmap, Color};
+
+    const OBJECTS: &[u8] = b"osu file format v14
+
+[HitObjects]
+64,64,1000,1,0,0:0:0:0:
+128,64,1500,1,0,0:0:0:0:
+192,64,2000,37,0,0:0:0:0:
+256,192,3000,8,0,4000,0:0:0:0:
+64,64,5000,1,0,0:0:0:0:
+128,64,5500,1,0,0:0:0:0:
+";
+
+    #[test]
+    fn test_default_colours() {
+        let beatmap = Beatmap::from_bytes(OBJECTS).unwrap();
+        let combos = beatmap.get_combo_info();
+
+        let numbers: Vec<usize> = combos
+            .iter()
+            .map(|combo| combo.get_combo_number())
+            .collect();
+        assert_eq!(numbers, [1, 2, 1, 2, 1, 2]);
+        let indices: Vec<usize> = combos.iter().map(|combo| combo.get_combo_index()).collect();
+        assert_eq!(indices, [0, 0, 1, 1, 2, 2]);
+        // The third object skips two colours, the one after the spinner none
+        let colours: Vec<usize> = combos
+            .iter()
+            .map(|combo| combo.get_colour_index())
+            .collect();
+        assert_eq!(colours, [0, 0, 3, 3, 0, 0]);
+        assert_eq!(combos[0].get_colour(), Color::new(255, 192, 0));
+    }
+
+    #[test]
+    fn test_custom_colours() {
+        let mut source =
+            b"osu file format v14\n\n[Colours]\nCombo1 : 10,20,30\nCombo2 : 40,50,60\n\n".to_vec();
+        source.extend(OBJECTS.strip_prefix(b"osu file format v14\n\n").unwrap());
+        let beatmap = Beatmap::from_bytes(&source).unwrap();
+
+        assert_eq!(beatmap.get_combo_colours().len(), 2);
+        let colours: Vec<Color> = beatmap
+            .get_combo_info()
+            .iter()
+            .map(|combo| combo.get_colour())
+            .collect();
+        assert_eq!(colours[0], Color::new(10, 20, 30));
+        assert_eq!(colours[2], Color::new(40, 50, 60));
+        assert_eq!(colours[4], Color::new(10, 20, 30));
+    }
+}
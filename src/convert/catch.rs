@@ -0,0 +1,355 @@
+use super::{random::LegacyRandom, PLAYFIELD_WIDTH};
+use crate::{
+    error::Result,
+    section::{GameMode, HitObject, SliderHitObject},
+    Beatmap,
+};
+
+/// Seed osu! uses for every random offset in osu!catch.
+const RANDOM_SEED: i32 = 1337;
+/// Tiny droplets fill any gap between slider events longer than this.
+const TINY_DROPLET_GAP: f64 = 80.0;
+/// Longest gap between tiny droplets.
+const TINY_DROPLET_SPACING: f64 = 100.0;
+
+/// What an osu!catch object is. Only fruits and droplets add to the combo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatchKind {
+    Fruit,
+    Droplet,
+    TinyDroplet,
+    Banana,
+}
+
+/// Something to catch, with every nested object of a slider or spinner
+/// listed on its own, see [`Beatmap::get_catch_objects`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatchObject {
+    pub(crate) time: f64,
+    pub(crate) x: f32,
+    pub(crate) kind: CatchKind,
+}
+
+impl CatchObject {
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Position across the playfield, after any random offset.
+    pub fn get_x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn get_kind(&self) -> CatchKind {
+        self.kind
+    }
+}
+
+/// A point along a slider that nested objects are placed on.
+#[derive(Debug, Clone, Copy)]
+struct SliderEvent {
+    time: f64,
+    progress: f64,
+    kind: Option<CatchKind>,
+}
+
+impl Beatmap {
+    /// The osu!catch objects of the beatmap. Sliders become fruits at their
+    /// ends and droplets on their ticks with tiny droplets between, and
+    /// spinners become banana showers. `hard_rock` moves fruits the way the
+    /// mod does. Fails for osu!taiko and osu!mania beatmaps.
+    pub fn get_catch_objects(&self, hard_rock: bool) -> Result<Vec<CatchObject>> {
+        self.is_converted_to(GameMode::Catch)?;
+        let mut hit_objects: Vec<&HitObject> = self.hit_objects.iter().collect();
+        hit_objects.sort_by_key(|hit_object| hit_object.get_time());
+
+        let mut random = LegacyRandom::new(RANDOM_SEED);
+        let mut last_position = None;
+        let mut last_time = 0.0;
+        let mut objects = Vec::new();
+
+        for hit_object in hit_objects {
+            let time = hit_object.get_time() as f64;
+            match hit_object {
+                HitObject::Slider(slider) => {
+                    // osu!stable used the last control point and the start
+                    // time, not where the slider really ends
+                    last_position = Some(
+                        slider
+                            .get_curve_points()
+                            .last()
+                            .map_or(slider.get_x(), |point| point.get_x())
+                            as f32,
+                    );
+                    last_time = time;
+
+                    for mut object in self.get_juice_stream(slider) {
+                        match object.kind {
+                            CatchKind::TinyDroplet => {
+                                let offset = random.next_in(-20, 20) as f32;
+                                object.x += offset.clamp(-object.x, PLAYFIELD_WIDTH - object.x);
+                            }
+                            CatchKind::Droplet => {
+                                random.next();
+                            }
+                            _ => {}
+                        }
+                        objects.push(object);
+                    }
+                }
+                HitObject::Spinner(spinner) => {
+                    let end_time = spinner.get_end_time() as f64;
+                    let mut spacing = end_time - time;
+                    while spacing > 100.0 {
+                        spacing /= 2.0;
+                    }
+                    if spacing <= 0.0 {
+                        continue;
+                    }
+
+                    let mut banana_time = time;
+                    while banana_time <= end_time {
+                        let x = (random.next_double() * PLAYFIELD_WIDTH as f64) as f32;
+                        // osu!stable drew a rotation and colour for each banana
+                        random.next();
+                        random.next();
+                        random.next();
+                        objects.push(CatchObject {
+                            time: banana_time,
+                            x,
+                            kind: CatchKind::Banana,
+                        });
+                        banana_time += spacing;
+                    }
+                }
+                HitObject::Circle(_) | HitObject::Hold(_) => {
+                    let mut x = hit_object.get_x() as f32;
+                    if hard_rock {
+                        x = hard_rock_offset(
+                            x,
+                            time,
+                            &mut last_position,
+                            &mut last_time,
+                            &mut random,
+                        );
+                    }
+                    objects.push(CatchObject {
+                        time,
+                        x,
+                        kind: CatchKind::Fruit,
+                    });
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    /// Fruits, droplets and tiny droplets along `slider`, before any
+    /// random offsets.
+    fn get_juice_stream(&self, slider: &SliderHitObject) -> Vec<CatchObject> {
+        let timing = self.get_slider_timing(slider);
+        let path = slider.get_path();
+        let start_time = slider.get_time() as f64;
+        let spans = slider.get_span_count();
+        let span_duration = timing.get_span_duration();
+        let ticks = timing.get_ticks();
+
+        let mut events = vec![SliderEvent {
+            time: start_time,
+            progress: 0.0,
+            kind: Some(CatchKind::Fruit),
+        }];
+        for span in 0..spans {
+            events.extend(
+                ticks
+                    .iter()
+                    .filter(|tick| tick.get_span() == span)
+                    .map(|tick| SliderEvent {
+                        time: tick.get_time(),
+                        progress: tick.get_progress(),
+                        kind: Some(CatchKind::Droplet),
+                    }),
+            );
+            if span + 1 < spans {
+                events.push(SliderEvent {
+                    time: start_time + (span + 1) as f64 * span_duration,
+                    progress: ((span + 1) % 2) as f64,
+                    kind: Some(CatchKind::Fruit),
+                });
+            }
+        }
+
+        // The legacy last tick places nothing, but tiny droplets are spaced
+        // up to it
+        let final_span_start = start_time + (spans - 1) as f64 * span_duration;
+        let last_tick_time = timing.get_legacy_last_tick_time();
+        let mut last_tick_progress = (last_tick_time - final_span_start) / span_duration;
+        if spans.is_multiple_of(2) {
+            last_tick_progress = 1.0 - last_tick_progress;
+        }
+        events.push(SliderEvent {
+            time: last_tick_time,
+            progress: last_tick_progress,
+            kind: None,
+        });
+        events.push(SliderEvent {
+            time: timing.get_end_time(),
+            progress: (spans % 2) as f64,
+            kind: Some(CatchKind::Fruit),
+        });
+
+        let x_at = |progress: f64| path.position_at(progress).x.clamp(0.0, PLAYFIELD_WIDTH);
+        let mut objects = Vec::new();
+        let mut last_event: Option<SliderEvent> = None;
+        for event in events {
+            if let Some(last) = last_event {
+                let since_last = (event.time as i32 - last.time as i32) as f64;
+                if since_last > TINY_DROPLET_GAP {
+                    let mut spacing = since_last;
+                    while spacing > TINY_DROPLET_SPACING {
+                        spacing /= 2.0;
+                    }
+
+                    let mut offset = spacing;
+                    while offset < since_last {
+                        let progress =
+                            last.progress + offset / since_last * (event.progress - last.progress);
+                        objects.push(CatchObject {
+                            time: last.time + offset,
+                            x: x_at(progress),
+                            kind: CatchKind::TinyDroplet,
+                        });
+                        offset += spacing;
+                    }
+                }
+            }
+            last_event = Some(event);
+
+            if let Some(kind) = event.kind {
+                objects.push(CatchObject {
+                    time: event.time,
+                    x: x_at(event.progress),
+                    kind,
+                });
+            }
+        }
+        objects
+    }
+}
+
+/// Where Hard Rock moves a fruit at `x`: a little to one side when it's
+/// stacked on the last one, or further along when it's already moving.
+fn hard_rock_offset(
+    x: f32,
+    time: f64,
+    last_position: &mut Option<f32>,
+    last_time: &mut f64,
+    random: &mut LegacyRandom,
+) -> f32 {
+    let Some(last) = *last_position else {
+        *last_position = Some(x);
+        *last_time = time;
+        return x;
+    };
+
+    let position_diff = x - last;
+    // osu!stable worked in whole milliseconds here
+    let time_diff = (time - *last_time) as i32;
+    if time_diff > 1000 {
+        *last_position = Some(x);
+        *last_time = time;
+        return x;
+    }
+
+    let mut offset_x = x;
+    if position_diff == 0.0 {
+        let right = random.next_bool();
+        let max_offset = (time_diff as f64 / 4.0).max(0.0);
+        let amount = (random.next_in_f64(0.0, max_offset) as f32).min(20.0);
+        if right {
+            if offset_x + amount <= PLAYFIELD_WIDTH {
+                offset_x += amount;
+            } else {
+                offset_x -= amount;
+            }
+        } else if offset_x - amount >= 0.0 {
+            offset_x -= amount;
+        } else {
+            offset_x += amount;
+        }
+        return offset_x;
+    }
+
+    if position_diff.abs() < (time_diff / 3) as f32 {
+        if position_diff > 0.0 {
+            if offset_x + position_diff < PLAYFIELD_WIDTH {
+                offset_x += position_diff;
+            }
+        } else if offset_x + position_diff > 0.0 {
+            offset_x += position_diff;
+        }
+    }
+
+    *last_position = Some(offset_x);
+    *last_time = time;
+    offset_x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CatchKind::{self, *};
+    use crate::Beatmap;
+
+    const BEATMAP: &[u8] = b"osu file format v14
+
+[Difficulty]
+SliderMultiplier:1
+SliderTickRate:1
+
+[TimingPoints]
+0,500,4,2,0,100,1,0
+
+[HitObjects]
+100,192,1000,2,0,L|300:192,1,200
+256,192,3000,12,0,3400
+";
+
+    /// Worked out by hand from osu!'s conversion and random generator.
+    const EXPECTED: [(f64, f32, CatchKind); 22] = [
+        (1000.0, 100.0, Fruit),
+        (1062.5, 98.5, TinyDroplet),
+        (1125.0, 115.0, TinyDroplet),
+        (1187.5, 135.5, TinyDroplet),
+        (1250.0, 165.0, TinyDroplet),
+        (1312.5, 179.5, TinyDroplet),
+        (1375.0, 176.0, TinyDroplet),
+        (1437.5, 187.5, TinyDroplet),
+        (1500.0, 200.0, Droplet),
+        (1558.0, 204.6, TinyDroplet),
+        (1616.0, 212.2, TinyDroplet),
+        (1674.0, 231.8, TinyDroplet),
+        (1732.0, 260.4, TinyDroplet),
+        (1790.0, 262.0, TinyDroplet),
+        (1848.0, 261.6, TinyDroplet),
+        (1906.0, 286.2, TinyDroplet),
+        (2000.0, 300.0, Fruit),
+        (3000.0, 17.78237, Banana),
+        (3100.0, 433.8482, Banana),
+        (3200.0, 201.61826, Banana),
+        (3300.0, 244.1033, Banana),
+        (3400.0, 55.05202, Banana),
+    ];
+
+    #[test]
+    fn test_conversion() {
+        let beatmap = Beatmap::from_bytes(BEATMAP).unwrap();
+        let objects = beatmap.get_catch_objects(false).unwrap();
+
+        assert_eq!(objects.len(), EXPECTED.len());
+        for (object, (time, x, kind)) in objects.iter().zip(EXPECTED) {
+            assert!((object.get_time() - time).abs() < 1e-3, "{:?}", object);
+            assert!((object.get_x() - x).abs() < 1e-3, "{:?}", object);
+            assert_eq!(object.get_kind(), kind);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Patterns for spinners: one hold in a random column.
+
+use super::{
+    pattern::{NextColumn, Pattern, PatternGenerator},
+    ManiaObject,
+};
+use crate::section::HitSound;
+
+/// Spinners shorter than this become a note instead of a hold.
+const MIN_HOLD_DURATION: i32 = 100;
+
+pub(super) struct EndTimeObjectPatternGenerator<'a> {
+    generator: PatternGenerator<'a>,
+    end_time: i32,
+    /// Whether the last pattern's columns should be avoided
+    force_not_stack: bool,
+}
+
+impl<'a> EndTimeObjectPatternGenerator<'a> {
+    pub(super) fn new(generator: PatternGenerator<'a>, end_time: i32) -> Self {
+        let force_not_stack = generator.previous.column_with_objects() != generator.total_columns;
+        EndTimeObjectPatternGenerator {
+            generator,
+            end_time,
+            force_not_stack,
+        }
+    }
+
+    pub(super) fn generate(&mut self) -> Pattern {
+        let start_time = self.generator.hit_object.get_time();
+        let hold = self.end_time - start_time >= MIN_HOLD_DURATION;
+
+        let column = if self.generator.total_columns == 8 {
+            // Short spinners with a finish go on the special key
+            if self.generator.has_sound(HitSound::FINISH) && self.end_time - start_time < 1000 {
+                0
+            } else {
+                self.get_random_column(None)
+            }
+        } else {
+            self.get_random_column(Some(0))
+        };
+
+        let hit_object = self.generator.hit_object;
+        let mut pattern = Pattern::default();
+        pattern.add(ManiaObject {
+            time: start_time as f64,
+            end_time: hold.then_some(self.end_time as f64),
+            column: column as usize,
+            sound: hit_object.get_hit_sound(),
+        });
+        pattern
+    }
+
+    fn get_random_column(&mut self, lower: Option<i32>) -> i32 {
+        let initial = self.generator.get_random_column(lower, None);
+        let previous = self.generator.previous;
+        let patterns: &[&Pattern] = if self.force_not_stack {
+            &[previous]
+        } else {
+            &[]
+        };
+        self.generator.find_available_column(
+            initial,
+            (lower, None),
+            NextColumn::Random,
+            None,
+            patterns,
+        )
+    }
+}
@@ -0,0 +1,351 @@
+//! Patterns for circles, chosen from how far and how soon they come after
+//! the last object.
+
+use super::{
+    pattern::{NextColumn, Pattern, PatternGenerator, PatternType},
+    ManiaObject,
+};
+use crate::{section::HitSound, utils::Vec2};
+
+pub(super) struct HitObjectPatternGenerator<'a> {
+    generator: PatternGenerator<'a>,
+    convert_type: PatternType,
+    /// Which way the next stair goes, carried on to the next circle
+    pub(super) stair_type: PatternType,
+}
+
+impl<'a> HitObjectPatternGenerator<'a> {
+    pub(super) fn new(
+        generator: PatternGenerator<'a>,
+        beat_length: f64,
+        kiai: bool,
+        previous_time: f64,
+        previous_position: Vec2,
+        density: f64,
+        last_stair: PatternType,
+    ) -> Self {
+        let hit_object = generator.hit_object;
+        let position = Vec2::new(hit_object.get_x() as f32, hit_object.get_y() as f32);
+        let position_separation = (position - previous_position).length();
+        let time_separation = hit_object.get_time() as f64 - previous_time;
+
+        let mut convert_type = PatternType::NONE;
+        if time_separation <= 80.0 {
+            // More than 187 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE;
+        } else if time_separation <= 95.0 {
+            // More than 157 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE | last_stair;
+        } else if time_separation <= 105.0 {
+            // More than 140 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::LOW_PROBABILITY;
+        } else if time_separation <= 125.0 {
+            // More than 120 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK;
+        } else if time_separation <= 135.0 && position_separation < 20.0 {
+            // More than 111 BPM stream
+            convert_type |= PatternType::CYCLE | PatternType::KEEP_SINGLE;
+        } else if time_separation <= 150.0 && position_separation < 20.0 {
+            // More than 100 BPM stream
+            convert_type |= PatternType::FORCE_STACK | PatternType::LOW_PROBABILITY;
+        } else if position_separation < 20.0 && density >= beat_length / 2.5 {
+            // Low density stream
+            convert_type |= PatternType::REVERSE | PatternType::LOW_PROBABILITY;
+        } else if density < beat_length / 2.5 || kiai {
+            // High density
+        } else {
+            convert_type |= PatternType::LOW_PROBABILITY;
+        }
+
+        if !convert_type.contains(PatternType::KEEP_SINGLE) {
+            if generator.has_sound(HitSound::FINISH) && generator.total_columns != 8 {
+                convert_type |= PatternType::MIRROR;
+            } else if generator.has_sound(HitSound::CLAP) {
+                convert_type |= PatternType::GATHERED;
+            }
+        }
+
+        HitObjectPatternGenerator {
+            generator,
+            convert_type,
+            stair_type: last_stair,
+        }
+    }
+
+    pub(super) fn generate(&mut self) -> Pattern {
+        let pattern = self.generate_core();
+        let total_columns = self.generator.total_columns;
+        for object in pattern.objects.iter() {
+            let column = object.column as i32;
+            if self.convert_type.contains(PatternType::STAIR) && column == total_columns - 1 {
+                self.stair_type = PatternType::REVERSE_STAIR;
+            }
+            if self.convert_type.contains(PatternType::REVERSE_STAIR)
+                && column == self.generator.random_start
+            {
+                self.stair_type = PatternType::STAIR;
+            }
+        }
+        pattern
+    }
+
+    fn generate_core(&mut self) -> Pattern {
+        let mut pattern = Pattern::default();
+        let total_columns = self.generator.total_columns;
+        let random_start = self.generator.random_start;
+        let previous = self.generator.previous;
+        let convert_type = self.convert_type;
+
+        if total_columns == 1 {
+            self.add_to_pattern(&mut pattern, 0);
+            return pattern;
+        }
+
+        let last_column = previous
+            .objects
+            .first()
+            .map_or(0, |object| object.column as i32);
+
+        if convert_type.contains(PatternType::REVERSE) && !previous.objects.is_empty() {
+            // The last pattern in mirrored columns
+            for column in random_start..total_columns {
+                if previous.column_has_object(column) {
+                    self.add_to_pattern(&mut pattern, random_start + total_columns - column - 1);
+                }
+            }
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::CYCLE)
+            && previous.objects.len() == 1
+            // Don't overload the special key of 7K+1
+            && (total_columns != 8 || last_column != 0)
+            // Nor mirror the centre column onto itself
+            && (total_columns % 2 == 0 || last_column != total_columns / 2)
+        {
+            self.add_to_pattern(&mut pattern, random_start + total_columns - last_column - 1);
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::FORCE_STACK) && !previous.objects.is_empty() {
+            for column in random_start..total_columns {
+                if previous.column_has_object(column) {
+                    self.add_to_pattern(&mut pattern, column);
+                }
+            }
+            return pattern;
+        }
+
+        if previous.objects.len() == 1 {
+            if convert_type.contains(PatternType::STAIR) {
+                let mut column = last_column + 1;
+                if column == total_columns {
+                    column = random_start;
+                }
+                self.add_to_pattern(&mut pattern, column);
+                return pattern;
+            }
+
+            if convert_type.contains(PatternType::REVERSE_STAIR) {
+                let mut column = last_column - 1;
+                if column == random_start - 1 {
+                    column = total_columns - 1;
+                }
+                self.add_to_pattern(&mut pattern, column);
+                return pattern;
+            }
+        }
+
+        if convert_type.contains(PatternType::KEEP_SINGLE) {
+            return self.generate_random_notes(1);
+        }
+
+        let difficulty = self.generator.conversion_difficulty;
+        let low_probability = convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if convert_type.contains(PatternType::MIRROR) {
+            return if difficulty > 6.5 {
+                self.generate_random_pattern_with_mirrored(0.12, 0.38, 0.12)
+            } else if difficulty > 4.0 {
+                self.generate_random_pattern_with_mirrored(0.12, 0.17, 0.0)
+            } else {
+                self.generate_random_pattern_with_mirrored(0.12, 0.0, 0.0)
+            };
+        }
+
+        if difficulty > 6.5 {
+            if low_probability {
+                self.generate_random_pattern(0.78, 0.42, 0.0, 0.0)
+            } else {
+                self.generate_random_pattern(1.0, 0.62, 0.0, 0.0)
+            }
+        } else if difficulty > 4.0 {
+            if low_probability {
+                self.generate_random_pattern(0.35, 0.08, 0.0, 0.0)
+            } else {
+                self.generate_random_pattern(0.52, 0.15, 0.0, 0.0)
+            }
+        } else if difficulty > 2.0 {
+            if low_probability {
+                self.generate_random_pattern(0.18, 0.0, 0.0, 0.0)
+            } else {
+                self.generate_random_pattern(0.45, 0.0, 0.0, 0.0)
+            }
+        } else {
+            self.generate_random_pattern(0.0, 0.0, 0.0, 0.0)
+        }
+    }
+
+    /// `note_count` notes starting under the circle, avoiding the last
+    /// pattern when the notes can't stack.
+    fn generate_random_notes(&mut self, mut note_count: i32) -> Pattern {
+        let previous = self.generator.previous;
+        let allow_stacking = !self.convert_type.contains(PatternType::FORCE_NOT_STACK);
+        if !allow_stacking {
+            note_count = note_count.min(
+                self.generator.total_columns
+                    - self.generator.random_start
+                    - previous.column_with_objects(),
+            );
+        }
+
+        let next = if self.convert_type.contains(PatternType::GATHERED) {
+            NextColumn::Gathered
+        } else {
+            NextColumn::Random
+        };
+
+        let mut pattern = Pattern::default();
+        let mut column = self.generator.get_object_column();
+        for _ in 0..note_count {
+            column = if allow_stacking {
+                self.generator
+                    .find_available_column(column, (None, None), next, None, &[&pattern])
+            } else {
+                self.generator.find_available_column(
+                    column,
+                    (None, None),
+                    next,
+                    None,
+                    &[&pattern, previous],
+                )
+            };
+            self.add_to_pattern(&mut pattern, column);
+        }
+        pattern
+    }
+
+    /// Whether the special key of 7K+1 should be pressed too.
+    fn has_special_column(&self) -> bool {
+        self.generator.has_sound(HitSound::CLAP) && self.generator.has_sound(HitSound::FINISH)
+    }
+
+    fn generate_random_pattern(&mut self, p2: f64, p3: f64, p4: f64, p5: f64) -> Pattern {
+        let note_count = self.get_random_note_count(p2, p3, p4, p5);
+        let mut pattern = self.generate_random_notes(note_count);
+        if self.generator.random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+        pattern
+    }
+
+    fn generate_random_pattern_with_mirrored(
+        &mut self,
+        centre_probability: f64,
+        p2: f64,
+        p3: f64,
+    ) -> Pattern {
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK) {
+            return self.generate_random_pattern(0.5 + p2 / 2.0, p2, (p2 + p3) / 2.0, p3);
+        }
+
+        let total_columns = self.generator.total_columns;
+        let random_start = self.generator.random_start;
+        let (note_count, add_to_centre) =
+            self.get_random_note_count_mirrored(centre_probability, p2, p3);
+
+        let mut pattern = Pattern::default();
+        let column_limit = (if total_columns % 2 == 0 {
+            total_columns
+        } else {
+            total_columns - 1
+        }) / 2;
+        let mut column = self.generator.get_random_column(None, Some(column_limit));
+        for _ in 0..note_count {
+            column = self.generator.find_available_column(
+                column,
+                (None, Some(column_limit)),
+                NextColumn::Random,
+                None,
+                &[&pattern],
+            );
+            self.add_to_pattern(&mut pattern, column);
+            self.add_to_pattern(&mut pattern, random_start + total_columns - column - 1);
+        }
+
+        if add_to_centre {
+            self.add_to_pattern(&mut pattern, total_columns / 2);
+        }
+        if random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+        pattern
+    }
+
+    fn get_random_note_count(&mut self, mut p2: f64, mut p3: f64, mut p4: f64, mut p5: f64) -> i32 {
+        match self.generator.total_columns {
+            2 => (p2, p3, p4, p5) = (0.0, 0.0, 0.0, 0.0),
+            3 => (p2, p3, p4, p5) = (p2.min(0.1), 0.0, 0.0, 0.0),
+            4 => (p2, p3, p4, p5) = (p2.min(0.23), p3.min(0.04), 0.0, 0.0),
+            5 => (p3, p4, p5) = (p3.min(0.15), p4.min(0.03), 0.0),
+            _ => {}
+        }
+        if self.generator.has_sound(HitSound::CLAP) {
+            p2 = 1.0;
+        }
+        self.generator.get_random_note_count(p2, p3, p4, p5, 0.0)
+    }
+
+    /// How many mirrored pairs to place, and whether to add a centre note.
+    fn get_random_note_count_mirrored(
+        &mut self,
+        mut centre_probability: f64,
+        mut p2: f64,
+        mut p3: f64,
+    ) -> (i32, bool) {
+        let total_columns = self.generator.total_columns;
+        // osu!stable doubled inverse probabilities, which lazer converts back
+        match total_columns {
+            2 => (centre_probability, p2, p3) = (0.0, 0.0, 0.0),
+            3 => (centre_probability, p2, p3) = (centre_probability.min(0.03), 0.0, 0.0),
+            4 => (centre_probability, p2, p3) = (0.0, 1.0 - ((1.0 - p2) * 2.0).max(0.8), 0.0),
+            5 => (centre_probability, p3) = (centre_probability.min(0.03), 0.0),
+            6 => {
+                (centre_probability, p2, p3) = (
+                    0.0,
+                    1.0 - ((1.0 - p2) * 2.0).max(0.5),
+                    1.0 - ((1.0 - p3) * 2.0).max(0.85),
+                )
+            }
+            _ => {}
+        }
+        let p2 = p2.clamp(0.0, 1.0);
+        let p3 = p3.clamp(0.0, 1.0);
+
+        let centre_value = self.generator.random.next_double();
+        let note_count = self.generator.get_random_note_count(p2, p3, 0.0, 0.0, 0.0);
+        let add_to_centre =
+            total_columns % 2 != 0 && note_count != 3 && centre_value > 1.0 - centre_probability;
+        (note_count, add_to_centre)
+    }
+
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32) {
+        let hit_object = self.generator.hit_object;
+        pattern.add(ManiaObject {
+            time: hit_object.get_time() as f64,
+            end_time: None,
+            column: column as usize,
+            sound: hit_object.get_hit_sound(),
+        });
+    }
+}
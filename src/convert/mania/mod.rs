@@ -0,0 +1,333 @@
+//! osu!mania objects, placed by the legacy pattern generators for converted
+//! beatmaps. Every generator draws from one generator seeded from the
+//! difficulty settings, so conversions match the game note for note.
+
+mod end_time;
+mod hit_object;
+mod path;
+mod pattern;
+
+use end_time::EndTimeObjectPatternGenerator;
+use hit_object::HitObjectPatternGenerator;
+use path::PathObjectPatternGenerator;
+use pattern::{Pattern, PatternGenerator, PatternType};
+
+use super::{random::LegacyRandom, PLAYFIELD_WIDTH};
+use crate::{
+    error::Result,
+    section::{GameMode, HitObject, HitSound},
+    utils::Vec2,
+    Beatmap,
+};
+
+/// How many recent objects the note density is taken over.
+const MAX_NOTES_FOR_DENSITY: usize = 7;
+
+/// A note, or a hold when it has an end time, see
+/// [`Beatmap::get_mania_objects`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManiaObject {
+    pub(crate) time: f64,
+    pub(crate) end_time: Option<f64>,
+    pub(crate) column: usize,
+    pub(crate) sound: HitSound,
+}
+
+impl ManiaObject {
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn get_end_time(&self) -> Option<f64> {
+        self.end_time
+    }
+
+    /// The column from the left, starting at 0.
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+
+    pub fn get_hit_sound(&self) -> HitSound {
+        self.sound
+    }
+}
+
+impl Beatmap {
+    /// The number of columns the beatmap is played with in osu!mania.
+    /// Conversions pick it from the circle size, overall difficulty and how
+    /// many objects aren't circles.
+    pub(crate) fn get_mania_key_count(&self) -> i32 {
+        if self.get_general_mode().unwrap_or_default() == GameMode::Mania {
+            return self.get_key_count();
+        }
+
+        let circle_size = self.get_circle_size().unwrap_or(5.0).round_ties_even();
+        let overall_difficulty = self
+            .get_overall_difficulty()
+            .unwrap_or(5.0)
+            .round_ties_even();
+        let total = self.hit_objects.len();
+        let special = total - self.get_circle_count();
+        let percent_special = special as f64 / total as f64;
+
+        if percent_special < 0.2 {
+            7
+        } else if percent_special < 0.3 || circle_size >= 5.0 {
+            if overall_difficulty > 5.0 {
+                7
+            } else {
+                6
+            }
+        } else if percent_special > 0.6 {
+            if overall_difficulty > 4.0 {
+                5
+            } else {
+                4
+            }
+        } else {
+            (overall_difficulty as i32 + 1).clamp(4, 7)
+        }
+    }
+
+    /// The osu!mania objects of the beatmap in time order. Fails for
+    /// osu!taiko and osu!catch beatmaps.
+    pub fn get_mania_objects(&self) -> Result<Vec<ManiaObject>> {
+        let converted = self.is_converted_to(GameMode::Mania)?;
+        let key_count = self.get_mania_key_count();
+        let mut objects = if !converted {
+            self.hit_objects
+                .iter()
+                .map(|hit_object| {
+                    let divisor = PLAYFIELD_WIDTH / key_count as f32;
+                    let column = (hit_object.get_x() as f32 / divisor).floor() as i32;
+                    let end_time = match hit_object {
+                        HitObject::Circle(_) => None,
+                        _ => Some(self.get_hit_object_end_time(hit_object)),
+                    };
+                    ManiaObject {
+                        time: hit_object.get_time() as f64,
+                        end_time,
+                        column: column.clamp(0, key_count - 1) as usize,
+                        sound: hit_object.get_hit_sound(),
+                    }
+                })
+                .collect()
+        } else {
+            ManiaConverter::new(self, key_count).convert()
+        };
+        objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(objects)
+    }
+
+    /// How hard the converted patterns should be, from the drain rate,
+    /// approach rate and object density.
+    fn get_conversion_difficulty(&self) -> f64 {
+        let first = self.hit_objects.first().map_or(0, |h| h.get_time()) as f64;
+        let last = self.hit_objects.last().map_or(0, |h| h.get_time()) as f64;
+        let mut drain_time = ((last - first - self.events.get_break_time()) / 1000.0) as i32;
+        if drain_time == 0 {
+            drain_time = 10000;
+        }
+
+        let drain_rate = self.get_hp_drain_rate().unwrap_or(5.0) as f64;
+        let approach_rate = self.get_effective_approach_rate().clamp(4.0, 7.0);
+        let difficulty = ((drain_rate + approach_rate) / 1.5
+            + self.hit_objects.len() as f64 / drain_time as f64 * 9.0)
+            / 38.0
+            * 5.0
+            / 1.15;
+        difficulty.min(12.0)
+    }
+
+    /// The seed osu! converts the beatmap with.
+    fn get_conversion_seed(&self) -> i32 {
+        let drain_rate = self.get_hp_drain_rate().unwrap_or(5.0);
+        let circle_size = self.get_circle_size().unwrap_or(5.0);
+        let overall_difficulty = self.get_overall_difficulty().unwrap_or(5.0);
+        let approach_rate = self.get_effective_approach_rate() as f32;
+
+        (drain_rate + circle_size).round_ties_even() as i32 * 20
+            + (overall_difficulty as f64 * 41.2) as i32
+            + approach_rate.round_ties_even() as i32
+    }
+}
+
+/// Converts osu!standard objects one at a time, remembering what came
+/// before for the next pattern.
+struct ManiaConverter<'a> {
+    beatmap: &'a Beatmap,
+    random: LegacyRandom,
+    total_columns: i32,
+    conversion_difficulty: f64,
+    last_pattern: Pattern,
+    last_time: f64,
+    last_position: Vec2,
+    last_stair: PatternType,
+    density: f64,
+    previous_times: Vec<f64>,
+}
+
+impl<'a> ManiaConverter<'a> {
+    fn new(beatmap: &'a Beatmap, total_columns: i32) -> Self {
+        ManiaConverter {
+            beatmap,
+            random: LegacyRandom::new(beatmap.get_conversion_seed()),
+            total_columns,
+            conversion_difficulty: beatmap.get_conversion_difficulty(),
+            last_pattern: Pattern::default(),
+            last_time: 0.0,
+            last_position: Vec2::new(0.0, 0.0),
+            last_stair: PatternType::STAIR,
+            density: i32::MAX as f64,
+            previous_times: Vec::new(),
+        }
+    }
+
+    fn convert(mut self) -> Vec<ManiaObject> {
+        let beatmap = self.beatmap;
+        let mut objects = Vec::new();
+        for hit_object in beatmap.hit_objects.iter() {
+            for pattern in self.convert_hit_object(hit_object) {
+                objects.extend(pattern.objects);
+            }
+        }
+        objects
+    }
+
+    fn convert_hit_object(&mut self, hit_object: &HitObject) -> Vec<Pattern> {
+        let beatmap = self.beatmap;
+        let time = hit_object.get_time() as f64;
+        let position = Vec2::new(hit_object.get_x() as f32, hit_object.get_y() as f32);
+
+        match hit_object {
+            HitObject::Slider(slider) => {
+                let mut path = PathObjectPatternGenerator::new(
+                    self.generator(hit_object),
+                    slider,
+                    beatmap.get_adjusted_beat_length_at(time),
+                    beatmap.get_effective_slider_multiplier(),
+                    beatmap.is_kiai_at(time),
+                );
+                let patterns = path.generate();
+                let (span_count, segment_duration) = (path.span_count, path.segment_duration);
+
+                for span in 0..=span_count {
+                    let span_time = time + (segment_duration * span) as f64;
+                    self.record_note(span_time, position);
+                    self.compute_density(span_time);
+                }
+                if let Some(last) = patterns.last() {
+                    self.last_pattern = last.clone();
+                }
+                patterns
+            }
+            HitObject::Spinner(_) | HitObject::Hold(_) => {
+                let end_time = hit_object.get_end_time().unwrap_or_default();
+                let pattern =
+                    EndTimeObjectPatternGenerator::new(self.generator(hit_object), end_time)
+                        .generate();
+                // Spinners don't set the pattern the next object follows
+                self.record_note(end_time as f64, Vec2::new(256.0, 192.0));
+                self.compute_density(end_time as f64);
+                vec![pattern]
+            }
+            HitObject::Circle(_) => {
+                self.compute_density(time);
+                let (last_time, last_position) = (self.last_time, self.last_position);
+                let (density, last_stair) = (self.density, self.last_stair);
+                let mut circle = HitObjectPatternGenerator::new(
+                    self.generator(hit_object),
+                    beatmap.get_beat_length_at(time),
+                    beatmap.is_kiai_at(time),
+                    last_time,
+                    last_position,
+                    density,
+                    last_stair,
+                );
+                let pattern = circle.generate();
+                self.last_stair = circle.stair_type;
+                self.record_note(time, position);
+                self.last_pattern = pattern.clone();
+                vec![pattern]
+            }
+        }
+    }
+
+    fn generator<'b>(&'b mut self, hit_object: &'b HitObject) -> PatternGenerator<'b> {
+        PatternGenerator::new(
+            &mut self.random,
+            hit_object,
+            &self.last_pattern,
+            self.total_columns,
+            self.conversion_difficulty,
+        )
+    }
+
+    fn record_note(&mut self, time: f64, position: Vec2) {
+        self.last_time = time;
+        self.last_position = position;
+    }
+
+    /// Updates the average time between the last few objects with one at `time`.
+    fn compute_density(&mut self, time: f64) {
+        if self.previous_times.len() == MAX_NOTES_FOR_DENSITY {
+            self.previous_times.remove(0);
+        }
+        self.previous_times.push(time);
+        if let [first, .., last] = self.previous_times[..] {
+            self.density = (last - first) / self.previous_times.len() as f64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Beatmap;
+
+    #[test]
+    fn test_conversion() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+        let key_count = beatmap.get_mania_key_count();
+        let objects = beatmap.get_mania_objects().unwrap();
+
+        assert!((4..=7).contains(&key_count));
+        assert!(objects
+            .iter()
+            .all(|object| object.column < key_count as usize));
+        assert!(objects.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        assert_eq!(objects, beatmap.get_mania_objects().unwrap());
+    }
+
+    #[test]
+    fn test_converted_columns() {
+        // Circles under 80ms apart go to the column under them, one 130ms
+        // after a circle at the same place cycles to the mirrored column and
+        // one 140ms after stacks on it
+        let beatmap = Beatmap::from_bytes(
+            b"osu file format v14
+
+[Difficulty]
+CircleSize:4
+OverallDifficulty:8
+
+[TimingPoints]
+0,500,4,2,0,100,1,0
+
+[HitObjects]
+100,192,10,1,0,0:0:0:0:
+256,192,60,1,0,0:0:0:0:
+400,192,110,1,0,0:0:0:0:
+0,192,160,1,0,0:0:0:0:
+511,192,210,1,0,0:0:0:0:
+511,192,340,1,0,0:0:0:0:
+511,192,480,1,0,0:0:0:0:
+",
+        )
+        .unwrap();
+        let objects = beatmap.get_mania_objects().unwrap();
+
+        assert_eq!(beatmap.get_mania_key_count(), 7);
+        let columns: Vec<usize> = objects.iter().map(|object| object.get_column()).collect();
+        assert_eq!(columns, vec![1, 3, 5, 0, 6, 0, 0]);
+    }
+}
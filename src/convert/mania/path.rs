@@ -0,0 +1,439 @@
+//! Patterns for sliders: holds, stairs or bursts of notes, chosen from how
+//! long each slide takes.
+
+use super::{
+    pattern::{NextColumn, Pattern, PatternGenerator, PatternType},
+    ManiaObject,
+};
+use crate::section::{HitSound, SliderHitObject};
+
+pub(super) struct PathObjectPatternGenerator<'a> {
+    generator: PatternGenerator<'a>,
+    convert_type: PatternType,
+    /// The sound on the head, every repeat and the tail
+    node_sounds: Vec<HitSound>,
+    pub(super) start_time: i32,
+    pub(super) end_time: i32,
+    /// How long one slide takes, rounded down like osu!stable
+    pub(super) segment_duration: i32,
+    pub(super) span_count: i32,
+}
+
+impl<'a> PathObjectPatternGenerator<'a> {
+    /// `beat_length` is the beat length over the slider velocity at the
+    /// slider's start.
+    pub(super) fn new(
+        generator: PatternGenerator<'a>,
+        slider: &SliderHitObject,
+        beat_length: f64,
+        slider_multiplier: f64,
+        kiai: bool,
+    ) -> Self {
+        let convert_type = if kiai {
+            PatternType::NONE
+        } else {
+            PatternType::LOW_PROBABILITY
+        };
+
+        let span_count = slider.get_span_count() as i32;
+        let start_time = slider.get_time();
+        let end_time = (start_time as f64
+            + slider.get_path().get_length() * beat_length * span_count as f64 * 0.01
+                / slider_multiplier)
+            .floor() as i32;
+
+        PathObjectPatternGenerator {
+            generator,
+            convert_type,
+            node_sounds: super::super::get_node_sounds(slider),
+            start_time,
+            end_time,
+            segment_duration: (end_time - start_time) / span_count,
+            span_count,
+        }
+    }
+
+    /// The notes of the slider, split so that the ones ending with it come
+    /// last to be the pattern the next object follows.
+    pub(super) fn generate(&mut self) -> Vec<Pattern> {
+        let original = self.generate_core();
+        if original.objects.len() == 1 {
+            return vec![original];
+        }
+
+        let mut intermediate = Pattern::default();
+        let mut end_time = Pattern::default();
+        for object in original.objects {
+            let object_end = object.end_time.unwrap_or(object.time).round() as i32;
+            if object_end == self.end_time {
+                end_time.add(object);
+            } else {
+                intermediate.add(object);
+            }
+        }
+        vec![intermediate, end_time]
+    }
+
+    fn generate_core(&mut self) -> Pattern {
+        let start_time = self.start_time;
+        let total_columns = self.generator.total_columns;
+        let difficulty = self.generator.conversion_difficulty;
+
+        if total_columns == 1 {
+            let mut pattern = Pattern::default();
+            self.add_to_pattern(&mut pattern, 0, start_time, self.end_time);
+            return pattern;
+        }
+
+        if self.span_count > 1 {
+            if self.segment_duration <= 90 {
+                return self.generate_random_hold_notes(start_time, 1);
+            }
+            if self.segment_duration <= 120 {
+                self.convert_type |= PatternType::FORCE_NOT_STACK;
+                return self.generate_random_notes(start_time, self.span_count + 1);
+            }
+            if self.segment_duration <= 160 {
+                return self.generate_stair(start_time);
+            }
+            if self.segment_duration <= 200 && difficulty > 3.0 {
+                return self.generate_random_multiple_notes(start_time);
+            }
+
+            let duration = self.end_time - start_time;
+            if duration >= 4000 {
+                return self.generate_n_random_notes(start_time, 0.23, 0.0, 0.0);
+            }
+            if self.segment_duration > 400
+                && self.span_count < total_columns - 1 - self.generator.random_start
+            {
+                return self.generate_tiled_hold_notes(start_time);
+            }
+            return self.generate_hold_and_normal_notes(start_time);
+        }
+
+        if self.segment_duration <= 110 {
+            if self.generator.previous.column_with_objects() < total_columns {
+                self.convert_type |= PatternType::FORCE_NOT_STACK;
+            } else {
+                self.convert_type.remove(PatternType::FORCE_NOT_STACK);
+            }
+            let note_count = if self.segment_duration < 80 { 1 } else { 2 };
+            return self.generate_random_notes(start_time, note_count);
+        }
+
+        let low_probability = self.convert_type.contains(PatternType::LOW_PROBABILITY);
+        let (p2, p3, p4) = if difficulty > 6.5 {
+            if low_probability {
+                (0.78, 0.3, 0.0)
+            } else {
+                (0.85, 0.36, 0.03)
+            }
+        } else if difficulty > 4.0 {
+            if low_probability {
+                (0.43, 0.08, 0.0)
+            } else {
+                (0.56, 0.18, 0.0)
+            }
+        } else if difficulty > 2.5 {
+            if low_probability {
+                (0.3, 0.0, 0.0)
+            } else {
+                (0.37, 0.08, 0.0)
+            }
+        } else if low_probability {
+            (0.17, 0.0, 0.0)
+        } else {
+            (0.27, 0.0, 0.0)
+        };
+        self.generate_n_random_notes(start_time, p2, p3, p4)
+    }
+
+    /// `note_count` holds over the whole slider in random columns.
+    fn generate_random_hold_notes(&mut self, start_time: i32, note_count: i32) -> Pattern {
+        let previous = self.generator.previous;
+        let mut pattern = Pattern::default();
+        let usable_columns = self.generator.total_columns
+            - self.generator.random_start
+            - previous.column_with_objects();
+
+        let mut column = self.generator.get_random_column(None, None);
+        for _ in 0..usable_columns.min(note_count) {
+            column = self.generator.find_available_column(
+                column,
+                (None, None),
+                NextColumn::Random,
+                None,
+                &[&pattern, previous],
+            );
+            self.add_to_pattern(&mut pattern, column, start_time, self.end_time);
+        }
+
+        // Holds that don't fit beside the last pattern stack on it
+        for _ in 0..note_count - usable_columns {
+            column = self.generator.find_available_column(
+                column,
+                (None, None),
+                NextColumn::Random,
+                None,
+                &[&pattern],
+            );
+            self.add_to_pattern(&mut pattern, column, start_time, self.end_time);
+        }
+        pattern
+    }
+
+    /// A note on every node, never twice in a row in the same column.
+    fn generate_random_notes(&mut self, mut start_time: i32, note_count: i32) -> Pattern {
+        let previous = self.generator.previous;
+        let mut pattern = Pattern::default();
+
+        let mut column = self.generator.get_object_column();
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && previous.column_with_objects() < self.generator.total_columns
+        {
+            column = self.generator.find_available_column(
+                column,
+                (None, None),
+                NextColumn::Random,
+                None,
+                &[previous],
+            );
+        }
+
+        let mut last_column = column;
+        for _ in 0..note_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+            let not_last = move |candidate: i32| candidate != last_column;
+            column = self.generator.find_available_column(
+                column,
+                (None, None),
+                NextColumn::Random,
+                Some(&not_last),
+                &[],
+            );
+            last_column = column;
+            start_time += self.segment_duration;
+        }
+        pattern
+    }
+
+    /// A note on every node, stepping one column at a time and turning
+    /// around at the edges.
+    fn generate_stair(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let mut column = self.generator.get_object_column();
+        let mut increasing = self.generator.random.next_double() > 0.5;
+
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+            start_time += self.segment_duration;
+
+            if increasing {
+                if column >= self.generator.total_columns - 1 {
+                    increasing = false;
+                    column -= 1;
+                } else {
+                    column += 1;
+                }
+            } else if column <= self.generator.random_start {
+                increasing = true;
+                column += 1;
+            } else {
+                column -= 1;
+            }
+        }
+        pattern
+    }
+
+    /// Two notes a set interval apart on every node.
+    fn generate_random_multiple_notes(&mut self, mut start_time: i32) -> Pattern {
+        let total_columns = self.generator.total_columns;
+        let random_start = self.generator.random_start;
+        let mut pattern = Pattern::default();
+
+        let legacy = (4..=8).contains(&total_columns);
+        let interval = self
+            .generator
+            .random
+            .next_in(1, total_columns - if legacy { 1 } else { 0 });
+
+        let mut column = self.generator.get_object_column();
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+
+            column += interval;
+            if column >= total_columns - random_start {
+                column = column - total_columns - random_start + if legacy { 1 } else { 0 };
+            }
+            column += random_start;
+
+            // Not too many doubles in a row on 2K
+            if total_columns > 2 {
+                self.add_to_pattern(&mut pattern, column, start_time, start_time);
+            }
+
+            column = self.generator.get_random_column(None, None);
+            start_time += self.segment_duration;
+        }
+        pattern
+    }
+
+    /// Random holds over the slider, at least two when it has a clap or
+    /// finish.
+    fn generate_n_random_notes(
+        &mut self,
+        start_time: i32,
+        mut p2: f64,
+        mut p3: f64,
+        mut p4: f64,
+    ) -> Pattern {
+        match self.generator.total_columns {
+            2 => (p2, p3, p4) = (0.0, 0.0, 0.0),
+            3 => (p2, p3, p4) = (p2.min(0.1), 0.0, 0.0),
+            4 => (p2, p3, p4) = (p2.min(0.3), p3.min(0.04), 0.0),
+            5 => (p2, p3, p4) = (p2.min(0.34), p3.min(0.1), p4.min(0.03)),
+            _ => {}
+        }
+
+        let is_double =
+            |sound: HitSound| sound.contains(HitSound::CLAP) || sound.contains(HitSound::FINISH);
+        let can_generate_two_notes = !self.convert_type.contains(PatternType::LOW_PROBABILITY)
+            && (is_double(self.generator.hit_object.get_hit_sound())
+                || is_double(self.sound_at(self.start_time)));
+        if can_generate_two_notes {
+            p2 = 1.0;
+        }
+
+        let note_count = self.generator.get_random_note_count(p2, p3, p4, 0.0, 0.0);
+        self.generate_random_hold_notes(start_time, note_count)
+    }
+
+    /// Holds starting one node after another, all ending together.
+    fn generate_tiled_hold_notes(&mut self, mut start_time: i32) -> Pattern {
+        let previous = self.generator.previous;
+        let mut pattern = Pattern::default();
+        let column_repeat = self.span_count.min(self.generator.total_columns);
+        // Rounding means this can come before the slider's own end
+        let end_time = start_time + self.segment_duration * self.span_count;
+
+        let mut column = self.generator.get_object_column();
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && previous.column_with_objects() < self.generator.total_columns
+        {
+            column = self.generator.find_available_column(
+                column,
+                (None, None),
+                NextColumn::Random,
+                None,
+                &[previous],
+            );
+        }
+
+        for _ in 0..column_repeat {
+            column = self.generator.find_available_column(
+                column,
+                (None, None),
+                NextColumn::Random,
+                None,
+                &[&pattern],
+            );
+            self.add_to_pattern(&mut pattern, column, start_time, end_time);
+            start_time += self.segment_duration;
+        }
+        pattern
+    }
+
+    /// A hold over the slider, with notes beside it on every node.
+    fn generate_hold_and_normal_notes(&mut self, mut start_time: i32) -> Pattern {
+        let previous = self.generator.previous;
+        let total_columns = self.generator.total_columns;
+        let difficulty = self.generator.conversion_difficulty;
+        let mut pattern = Pattern::default();
+
+        let mut hold_column = self.generator.get_object_column();
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && previous.column_with_objects() < total_columns
+        {
+            hold_column = self.generator.find_available_column(
+                hold_column,
+                (None, None),
+                NextColumn::Random,
+                None,
+                &[previous],
+            );
+        }
+        self.add_to_pattern(&mut pattern, hold_column, start_time, self.end_time);
+
+        let mut column = self.generator.get_random_column(None, None);
+        let note_count = if difficulty > 6.5 {
+            self.generator
+                .get_random_note_count(0.63, 0.0, 0.0, 0.0, 0.0)
+        } else if difficulty > 4.0 {
+            let p2 = if total_columns < 6 { 0.12 } else { 0.45 };
+            self.generator.get_random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else if difficulty > 2.5 {
+            let p2 = if total_columns < 6 { 0.0 } else { 0.24 };
+            self.generator.get_random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else {
+            0
+        };
+        let note_count = note_count.min(total_columns - 1);
+
+        let head_sound = self.sound_at(start_time);
+        let ignore_head = !(head_sound.contains(HitSound::WHISTLE)
+            || head_sound.contains(HitSound::FINISH)
+            || head_sound.contains(HitSound::CLAP));
+
+        let not_hold = move |candidate: i32| candidate != hold_column;
+        let mut row = Pattern::default();
+        for _ in 0..=self.span_count {
+            if !(ignore_head && start_time == self.start_time) {
+                for _ in 0..note_count {
+                    column = self.generator.find_available_column(
+                        column,
+                        (None, None),
+                        NextColumn::Random,
+                        Some(&not_hold),
+                        &[&row],
+                    );
+                    self.add_to_pattern(&mut row, column, start_time, start_time);
+                }
+            }
+            pattern.append(&mut row);
+            start_time += self.segment_duration;
+        }
+        pattern
+    }
+
+    /// The sound on the node at `time`, or the slider's own past the end.
+    fn sound_at(&self, time: i32) -> HitSound {
+        let node = if self.segment_duration == 0 {
+            0
+        } else {
+            (time - self.start_time) / self.segment_duration
+        };
+        usize::try_from(node)
+            .ok()
+            .and_then(|node| self.node_sounds.get(node))
+            .copied()
+            .unwrap_or_else(|| self.generator.hit_object.get_hit_sound())
+    }
+
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32, start_time: i32, end_time: i32) {
+        let (end_time, sound) = if start_time == end_time {
+            (None, self.sound_at(start_time))
+        } else {
+            (
+                Some(end_time as f64),
+                self.generator.hit_object.get_hit_sound(),
+            )
+        };
+        pattern.add(ManiaObject {
+            time: start_time as f64,
+            end_time,
+            column: column as usize,
+            sound,
+        });
+    }
+}
@@ -0,0 +1,222 @@
+//! What the legacy pattern generators share: the notes placed for one
+//! object, and the column picking every generator uses.
+
+use std::ops::{BitOr, BitOrAssign};
+
+use super::ManiaObject;
+use crate::{
+    convert::{random::LegacyRandom, PLAYFIELD_WIDTH},
+    section::{HitObject, HitSound},
+};
+
+/// Hints for how a pattern should be laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) struct PatternType(u32);
+
+impl PatternType {
+    pub(super) const NONE: PatternType = PatternType(0);
+    /// Keep using the columns of the last pattern
+    pub(super) const FORCE_STACK: PatternType = PatternType(1 << 0);
+    /// Avoid the columns of the last pattern
+    pub(super) const FORCE_NOT_STACK: PatternType = PatternType(1 << 1);
+    /// Only one note
+    pub(super) const KEEP_SINGLE: PatternType = PatternType(1 << 2);
+    /// Fewer notes than usual
+    pub(super) const LOW_PROBABILITY: PatternType = PatternType(1 << 3);
+    /// Notes in neighbouring columns
+    pub(super) const GATHERED: PatternType = PatternType(1 << 7);
+    /// Notes mirrored around the centre
+    pub(super) const MIRROR: PatternType = PatternType(1 << 8);
+    /// The last pattern mirrored
+    pub(super) const REVERSE: PatternType = PatternType(1 << 9);
+    /// The last single note mirrored
+    pub(super) const CYCLE: PatternType = PatternType(1 << 10);
+    /// One column to the right of the last note
+    pub(super) const STAIR: PatternType = PatternType(1 << 11);
+    /// One column to the left of the last note
+    pub(super) const REVERSE_STAIR: PatternType = PatternType(1 << 12);
+
+    pub(super) fn contains(self, other: PatternType) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub(super) fn remove(&mut self, other: PatternType) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for PatternType {
+    type Output = PatternType;
+
+    fn bitor(self, other: PatternType) -> PatternType {
+        PatternType(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for PatternType {
+    fn bitor_assign(&mut self, other: PatternType) {
+        self.0 |= other.0;
+    }
+}
+
+/// The notes generated for one object.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Pattern {
+    pub(super) objects: Vec<ManiaObject>,
+}
+
+impl Pattern {
+    pub(super) fn add(&mut self, object: ManiaObject) {
+        self.objects.push(object);
+    }
+
+    pub(super) fn append(&mut self, other: &mut Pattern) {
+        self.objects.append(&mut other.objects);
+    }
+
+    pub(super) fn column_has_object(&self, column: i32) -> bool {
+        self.objects
+            .iter()
+            .any(|object| object.column as i32 == column)
+    }
+
+    /// How many different columns the pattern uses.
+    pub(super) fn column_with_objects(&self) -> i32 {
+        let mut columns: Vec<usize> = self.objects.iter().map(|object| object.column).collect();
+        columns.sort_unstable();
+        columns.dedup();
+        columns.len() as i32
+    }
+}
+
+/// How to pick the next column to try when one is taken.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum NextColumn {
+    /// A random column within the bounds
+    Random,
+    /// The column to the right, wrapping around
+    Gathered,
+}
+
+/// The state every generator works from.
+pub(super) struct PatternGenerator<'a> {
+    pub(super) random: &'a mut LegacyRandom,
+    pub(super) hit_object: &'a HitObject,
+    pub(super) previous: &'a Pattern,
+    pub(super) total_columns: i32,
+    /// The first column notes go in, skipping the special key of 7K+1
+    pub(super) random_start: i32,
+    pub(super) conversion_difficulty: f64,
+}
+
+impl<'a> PatternGenerator<'a> {
+    pub(super) fn new(
+        random: &'a mut LegacyRandom,
+        hit_object: &'a HitObject,
+        previous: &'a Pattern,
+        total_columns: i32,
+        conversion_difficulty: f64,
+    ) -> Self {
+        PatternGenerator {
+            random,
+            hit_object,
+            previous,
+            total_columns,
+            random_start: if total_columns == 8 { 1 } else { 0 },
+            conversion_difficulty,
+        }
+    }
+
+    pub(super) fn has_sound(&self, sound: HitSound) -> bool {
+        self.hit_object.get_hit_sound().contains(sound)
+    }
+
+    /// The column under `x`. With `allow_special` 7K+1 keeps its first
+    /// column free.
+    pub(super) fn get_column(&self, x: f32, allow_special: bool) -> i32 {
+        if allow_special && self.total_columns == 8 {
+            let divisor = PLAYFIELD_WIDTH / 7.0;
+            return ((x / divisor).floor() as i32).clamp(0, 6) + 1;
+        }
+        let divisor = PLAYFIELD_WIDTH / self.total_columns as f32;
+        ((x / divisor).floor() as i32).clamp(0, self.total_columns - 1)
+    }
+
+    pub(super) fn get_object_column(&self) -> i32 {
+        self.get_column(self.hit_object.get_x() as f32, true)
+    }
+
+    pub(super) fn get_random_column(&mut self, lower: Option<i32>, upper: Option<i32>) -> i32 {
+        let lower = lower.unwrap_or(self.random_start);
+        let upper = upper.unwrap_or(self.total_columns);
+        self.random.next_in(lower, upper)
+    }
+
+    /// Starting from `initial`, finds a column that passes `validation` and
+    /// is empty in every one of `patterns`. Gives back `initial` when no
+    /// column between the bounds is free.
+    pub(super) fn find_available_column(
+        &mut self,
+        initial: i32,
+        bounds: (Option<i32>, Option<i32>),
+        next: NextColumn,
+        validation: Option<&dyn Fn(i32) -> bool>,
+        patterns: &[&Pattern],
+    ) -> i32 {
+        let lower = bounds.0.unwrap_or(self.random_start);
+        let upper = bounds.1.unwrap_or(self.total_columns);
+        let is_valid = |column: i32| {
+            validation.is_none_or(|validation| validation(column))
+                && !patterns
+                    .iter()
+                    .any(|pattern| pattern.column_has_object(column))
+        };
+
+        if is_valid(initial) || !(lower..upper).any(is_valid) {
+            return initial;
+        }
+
+        let mut column = initial;
+        loop {
+            column = match next {
+                NextColumn::Random => self.get_random_column(Some(lower), Some(upper)),
+                NextColumn::Gathered => {
+                    if column + 1 == self.total_columns {
+                        self.random_start
+                    } else {
+                        column + 1
+                    }
+                }
+            };
+            if is_valid(column) {
+                return column;
+            }
+        }
+    }
+
+    /// A random number of notes from 1 to 6, where `p2` is the chance of at
+    /// least 2 and so on.
+    pub(super) fn get_random_note_count(
+        &mut self,
+        p2: f64,
+        p3: f64,
+        p4: f64,
+        p5: f64,
+        p6: f64,
+    ) -> i32 {
+        let value = self.random.next_double();
+        if value >= 1.0 - p6 {
+            6
+        } else if value >= 1.0 - p5 {
+            5
+        } else if value >= 1.0 - p4 {
+            4
+        } else if value >= 1.0 - p3 {
+            3
+        } else if value >= 1.0 - p2 {
+            2
+        } else {
+            1
+        }
+    }
+}
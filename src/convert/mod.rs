@@ -0,0 +1,181 @@
+//! Turns beatmaps into the objects each ruleset plays, converting
+//! osu!standard beatmaps the way the game does.
+
+mod catch;
+mod mania;
+mod random;
+mod taiko;
+
+pub use catch::{CatchKind, CatchObject};
+pub use mania::ManiaObject;
+pub use taiko::{TaikoKind, TaikoObject};
+
+use crate::{
+    builder::ensure,
+    error::{Error, Result},
+    section::{
+        CircleBuilder, GameMode, HitObject, HitObjectBuilder, HitSound, HoldBuilder,
+        SliderHitObject, SpinnerBuilder,
+    },
+    Beatmap,
+};
+
+/// Slider distance travelled per beat at a slider multiplier and velocity of 1.
+const BASE_SCORING_DISTANCE: f64 = 100.0;
+/// Width of the osu! playfield.
+const PLAYFIELD_WIDTH: f32 = 512.0;
+
+impl Beatmap {
+    /// Whether playing the beatmap in `mode` needs a conversion. Only
+    /// osu!standard beatmaps can be converted.
+    pub(crate) fn is_converted_to(&self, mode: GameMode) -> Result<bool> {
+        let source = self.get_general_mode().unwrap_or_default();
+        ensure(
+            source == mode || source == GameMode::Osu,
+            format!("a {:?} beatmap can't be played in {:?}", source, mode),
+        )?;
+        Ok(source != mode)
+    }
+
+    /// A copy of the beatmap converted to `mode`, ready to be saved. osu!taiko
+    /// and osu!mania get their converted objects, while osu!catch plays
+    /// osu!standard objects as they are. Only osu!standard beatmaps can be
+    /// converted.
+    pub fn convert_to(&self, mode: GameMode) -> Result<Beatmap> {
+        ensure(
+            !matches!(mode, GameMode::Unknown(_)),
+            format!("can't convert to {:?}", mode),
+        )?;
+        self.is_converted_to(mode)?;
+
+        let mut beatmap = self.clone();
+        beatmap.general.change_mode(mode);
+        match mode {
+            GameMode::Taiko => {
+                beatmap.hit_objects = self
+                    .get_taiko_objects()?
+                    .iter()
+                    .map(|object| self.taiko_hit_object(object))
+                    .collect::<Result<_>>()?;
+            }
+            GameMode::Mania => {
+                let key_count = self.get_mania_key_count();
+                beatmap.difficulty.change_circle_size(key_count as f32);
+                let column_width = PLAYFIELD_WIDTH / key_count as f32;
+                beatmap.hit_objects = self
+                    .get_mania_objects()?
+                    .iter()
+                    .map(|object| {
+                        let x = ((object.column as f32 + 0.5) * column_width) as i32;
+                        let time = object.time.round() as i32;
+                        let builder: HitObjectBuilder = match object.end_time {
+                            Some(end_time) => HoldBuilder::new(x, time, end_time.round() as i32)
+                                .hit_sound(object.sound)
+                                .into(),
+                            None => CircleBuilder::new(x, 192, time)
+                                .hit_sound(object.sound)
+                                .into(),
+                        };
+                        builder.build()
+                    })
+                    .collect::<Result<_>>()?;
+            }
+            _ => {}
+        }
+        Ok(beatmap)
+    }
+
+    /// The hit object an osu!taiko object is written as. Drum rolls keep
+    /// the slider they came from.
+    fn taiko_hit_object(&self, object: &TaikoObject) -> Result<HitObject> {
+        let time = object.time.round() as i32;
+        let mut sound = object.sound;
+        sound.set(HitSound::FINISH, object.strong);
+
+        let builder: HitObjectBuilder = match object.kind {
+            TaikoKind::Hit { .. } => CircleBuilder::new(256, 192, time).hit_sound(sound).into(),
+            TaikoKind::DrumRoll { .. } => {
+                return self
+                    .hit_objects
+                    .iter()
+                    .find(|hit_object| {
+                        matches!(hit_object, HitObject::Slider(_)) && hit_object.get_time() == time
+                    })
+                    .cloned()
+                    .ok_or_else(|| Error::build(format!("no slider at {} for a drum roll", time)));
+            }
+            TaikoKind::Swell { end_time, .. } => SpinnerBuilder::new(time, end_time as i32)
+                .hit_sound(sound)
+                .into(),
+        };
+        builder.build()
+    }
+
+    /// Milliseconds per beat divided by the slider velocity at `time`, which
+    /// is how converters find how long a slider lasts.
+    fn get_adjusted_beat_length_at(&self, time: f64) -> f64 {
+        self.get_beat_length_at(time) / self.get_slider_velocity_at(time)
+    }
+}
+
+/// The sounds on the head, every repeat and the tail of `slider`. Nodes
+/// without an edge sound use the slider's own.
+fn get_node_sounds(slider: &SliderHitObject) -> Vec<HitSound> {
+    let edge_sounds = slider.get_edge_sounds();
+    (0..=slider.get_span_count())
+        .map(|node| {
+            edge_sounds
+                .get(node)
+                .copied()
+                .unwrap_or_else(|| slider.get_hit_sound())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Beatmap, GameMode};
+
+    #[test]
+    fn test_convert_to() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+
+        let mania = beatmap.convert_to(GameMode::Mania).unwrap();
+        let written = Beatmap::from_bytes(mania.to_string().as_bytes()).unwrap();
+        assert_eq!(written.get_general_mode(), Some(GameMode::Mania));
+        assert_eq!(written.get_key_count(), beatmap.get_mania_key_count());
+        assert_eq!(
+            written.get_mania_objects().unwrap(),
+            beatmap.get_mania_objects().unwrap()
+        );
+
+        let taiko = beatmap.convert_to(GameMode::Taiko).unwrap();
+        let written = Beatmap::from_bytes(taiko.to_string().as_bytes()).unwrap();
+        let objects = beatmap.get_taiko_objects().unwrap();
+        let written_objects = written.get_taiko_objects().unwrap();
+        assert_eq!(written_objects.len(), objects.len());
+        assert!(written_objects
+            .iter()
+            .zip(objects.iter())
+            .all(|(a, b)| a.kind == b.kind || a.is_hit() && b.is_hit()));
+
+        let catch = beatmap.convert_to(GameMode::Catch).unwrap();
+        assert_eq!(
+            catch.get_hit_objects().len(),
+            beatmap.get_hit_objects().len()
+        );
+    }
+
+    #[test]
+    fn test_convert_native() {
+        let taiko = Beatmap::from_bytes(b"osu file format v14\n\n[General]\nMode: 1\n").unwrap();
+
+        assert!(taiko.convert_to(GameMode::Taiko).is_ok());
+        assert!(taiko.convert_to(GameMode::Mania).is_err());
+        assert!(taiko.get_catch_objects(false).is_err());
+        assert!(Beatmap::parse_file("beatmap.osu")
+            .unwrap()
+            .convert_to(GameMode::Unknown(4))
+            .is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! The xorshift generator osu!stable used for conversions, so seeded
+//! patterns come out the same as in the game.
+
+const INT_TO_REAL: f64 = 1.0 / (i32::MAX as f64 + 1.0);
+const INT_MASK: u32 = 0x7FFF_FFFF;
+const Y: u32 = 842502087;
+const Z: u32 = 3579807591;
+const W: u32 = 273326509;
+
+#[derive(Debug, Clone)]
+pub(crate) struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+    bit_buffer: u32,
+    bit_index: u32,
+}
+
+impl LegacyRandom {
+    pub(crate) fn new(seed: i32) -> Self {
+        LegacyRandom {
+            x: seed as u32,
+            y: Y,
+            z: Z,
+            w: W,
+            bit_buffer: 0,
+            bit_index: 32,
+        }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+        self.w
+    }
+
+    /// A random non-negative `i32`.
+    pub(crate) fn next(&mut self) -> i32 {
+        (INT_MASK & self.next_u32()) as i32
+    }
+
+    /// A random value in `lower..upper`.
+    pub(crate) fn next_in(&mut self, lower: i32, upper: i32) -> i32 {
+        self.next_in_f64(lower as f64, upper as f64)
+    }
+
+    pub(crate) fn next_in_f64(&mut self, lower: f64, upper: f64) -> i32 {
+        (lower + self.next_double() * (upper - lower)) as i32
+    }
+
+    /// A random value in `0.0..1.0`.
+    pub(crate) fn next_double(&mut self) -> f64 {
+        INT_TO_REAL * self.next() as f64
+    }
+
+    /// A random bit, taking 32 at a time from the generator.
+    pub(crate) fn next_bool(&mut self) -> bool {
+        if self.bit_index == 32 {
+            self.bit_buffer = self.next_u32();
+            self.bit_index = 1;
+            return self.bit_buffer & 1 == 1;
+        }
+        self.bit_index += 1;
+        self.bit_buffer >>= 1;
+        self.bit_buffer & 1 == 1
+    }
+}
@@ -0,0 +1,195 @@
+use super::{get_node_sounds, BASE_SCORING_DISTANCE};
+use crate::{
+    error::Result,
+    section::{difficulty_range, GameMode, HitObject, HitSound, SliderHitObject},
+    Beatmap,
+};
+
+/// How much faster osu!taiko scrolls than osu!standard sliders move.
+const VELOCITY_MULTIPLIER: f64 = 1.4;
+/// Hits a swell needs per second, on top of the overall difficulty.
+const SWELL_HIT_MULTIPLIER: f64 = 1.65;
+
+/// What an osu!taiko object is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaikoKind {
+    /// A don, or a kat when `rim` is set
+    Hit {
+        rim: bool,
+    },
+    DrumRoll {
+        end_time: f64,
+    },
+    Swell {
+        end_time: f64,
+        required_hits: usize,
+    },
+}
+
+/// An object of an osu!taiko beatmap, converted or not, see
+/// [`Beatmap::get_taiko_objects`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaikoObject {
+    pub(crate) time: f64,
+    pub(crate) kind: TaikoKind,
+    /// Hit with both hands for a bigger score
+    pub(crate) strong: bool,
+    pub(crate) sound: HitSound,
+}
+
+impl TaikoObject {
+    fn hit(time: f64, sound: HitSound) -> Self {
+        TaikoObject {
+            time,
+            kind: TaikoKind::Hit {
+                rim: sound.contains(HitSound::WHISTLE) || sound.contains(HitSound::CLAP),
+            },
+            strong: sound.contains(HitSound::FINISH),
+            sound,
+        }
+    }
+
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn get_kind(&self) -> TaikoKind {
+        self.kind
+    }
+
+    pub fn is_strong(&self) -> bool {
+        self.strong
+    }
+
+    pub fn get_hit_sound(&self) -> HitSound {
+        self.sound
+    }
+
+    pub fn is_hit(&self) -> bool {
+        matches!(self.kind, TaikoKind::Hit { .. })
+    }
+}
+
+impl Beatmap {
+    /// The osu!taiko objects of the beatmap in time order. Converted sliders
+    /// that are short and fast become hits instead of drum rolls, and
+    /// objects at the same time merge into one strong hit. Fails for
+    /// osu!catch and osu!mania beatmaps.
+    pub fn get_taiko_objects(&self) -> Result<Vec<TaikoObject>> {
+        let converted = self.is_converted_to(GameMode::Taiko)?;
+
+        let mut objects = Vec::new();
+        for hit_object in self.hit_objects.iter() {
+            let time = hit_object.get_time() as f64;
+            match hit_object {
+                HitObject::Slider(slider) => self.convert_slider(slider, converted, &mut objects),
+                HitObject::Spinner(spinner) => {
+                    let end_time = spinner.get_end_time() as f64;
+                    let overall_difficulty = self.get_overall_difficulty().map_or(5.0, f64::from);
+                    let hits_per_second =
+                        difficulty_range(overall_difficulty, 3.0, 5.0, 7.5) * SWELL_HIT_MULTIPLIER;
+                    let required_hits = ((end_time - time) / 1000.0 * hits_per_second) as usize;
+                    objects.push(TaikoObject {
+                        time,
+                        kind: TaikoKind::Swell {
+                            end_time,
+                            required_hits: required_hits.max(1),
+                        },
+                        strong: false,
+                        sound: spinner.get_hit_sound(),
+                    });
+                }
+                HitObject::Circle(_) | HitObject::Hold(_) => {
+                    objects.push(TaikoObject::hit(time, hit_object.get_hit_sound()));
+                }
+            }
+        }
+        objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        if converted {
+            // Objects at the same time keep the first, made strong
+            objects.dedup_by(|next, first| {
+                let same_time = next.time == first.time;
+                if same_time && !matches!(first.kind, TaikoKind::Swell { .. }) {
+                    first.strong = true;
+                }
+                same_time
+            });
+        }
+        Ok(objects)
+    }
+
+    /// Pushes `slider` as a drum roll, or for conversions as hits spaced
+    /// along it when it's too fast to roll, following the game's rounding.
+    fn convert_slider(
+        &self,
+        slider: &SliderHitObject,
+        converted: bool,
+        objects: &mut Vec<TaikoObject>,
+    ) {
+        let time = slider.get_time() as f64;
+        let spans = slider.get_span_count();
+        let distance = slider.get_path().get_length() * spans as f64 * VELOCITY_MULTIPLIER;
+
+        let mut beat_length = self.get_adjusted_beat_length_at(time);
+        let slider_multiplier = self.get_effective_slider_multiplier() * VELOCITY_MULTIPLIER;
+        let tick_rate = self.get_effective_slider_tick_rate();
+        let scoring_point_distance = BASE_SCORING_DISTANCE * slider_multiplier / tick_rate;
+        let taiko_velocity = scoring_point_distance * tick_rate;
+        let taiko_duration = (distance / taiko_velocity * beat_length) as i32 as f64;
+
+        if converted {
+            let osu_velocity = taiko_velocity * (1000.0 / beat_length);
+            // From v8 the hits are spaced by the red line alone
+            if self.get_version() >= 8 {
+                beat_length = self.get_beat_length_at(time);
+            }
+            let tick_spacing = (beat_length / tick_rate).min(taiko_duration / spans as f64);
+
+            if tick_spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * beat_length {
+                let sounds = get_node_sounds(slider);
+                let mut hit_time = time;
+                for sound in sounds.iter().cycle() {
+                    if hit_time > time + taiko_duration + tick_spacing / 8.0 {
+                        break;
+                    }
+                    objects.push(TaikoObject::hit(hit_time, *sound));
+                    // Spacing too small to move the time on would never end
+                    if hit_time + tick_spacing == hit_time {
+                        break;
+                    }
+                    hit_time += tick_spacing;
+                }
+                return;
+            }
+        }
+
+        let sound = slider.get_hit_sound();
+        objects.push(TaikoObject {
+            time,
+            kind: TaikoKind::DrumRoll {
+                end_time: time + taiko_duration,
+            },
+            strong: sound.contains(HitSound::FINISH),
+            sound,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TaikoKind;
+    use crate::{Beatmap, GameMode};
+
+    #[test]
+    fn test_conversion() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+        let objects = beatmap.get_taiko_objects().unwrap();
+
+        assert!(beatmap.is_converted_to(GameMode::Taiko).unwrap());
+        assert!(objects.windows(2).all(|pair| pair[0].time < pair[1].time));
+        assert!(objects
+            .iter()
+            .any(|object| matches!(object.kind, TaikoKind::Swell { .. })));
+    }
+}
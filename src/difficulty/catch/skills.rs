@@ -0,0 +1,115 @@
+//! Movement strain: how far and how suddenly the catcher has to move, with
+//! a bonus for dashes that only just avoid being hyperdashes.
+
+use super::CatchDifficultyObject;
+use crate::difficulty::skill::{strain_decay, weighted_sum, StrainPeaks};
+
+const SECTION_LENGTH: f64 = 750.0;
+const DECAY_WEIGHT: f64 = 0.94;
+
+const SKILL_MULTIPLIER: f64 = 900.0;
+const STRAIN_DECAY_BASE: f64 = 0.2;
+
+const ABSOLUTE_PLAYER_POSITIONING_ERROR: f32 = 16.0;
+pub(super) const NORMALIZED_HITOBJECT_RADIUS: f32 = 41.0;
+const DIRECTION_CHANGE_BONUS: f64 = 21.0;
+/// Dashes closer than this to a hyperdash count as edge dashes.
+const EDGE_DASH_DISTANCE: f32 = 20.0;
+
+pub(super) struct Movement {
+    /// How much faster the catcher moves with rate changes
+    catcher_speed_multiplier: f64,
+    last_player_position: Option<f32>,
+    last_distance_moved: f32,
+    last_strain_time: f64,
+    current_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Movement {
+    pub(super) fn new(clock_rate: f64) -> Self {
+        Movement {
+            catcher_speed_multiplier: clock_rate,
+            last_player_position: None,
+            last_distance_moved: 0.0,
+            last_strain_time: 0.0,
+            current_strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(super) fn process(&mut self, objects: &[CatchDifficultyObject], index: usize) {
+        let current = &objects[index];
+        let strain = self.current_strain;
+        let last_start = index
+            .checked_sub(1)
+            .map_or(current.start_time, |previous| objects[previous].start_time);
+        self.peaks.advance(current.start_time, |time| {
+            strain * strain_decay(STRAIN_DECAY_BASE, time - last_start)
+        });
+
+        self.current_strain *= strain_decay(STRAIN_DECAY_BASE, current.delta_time);
+        self.current_strain += self.strain_value_of(current) * SKILL_MULTIPLIER;
+        self.peaks.push(self.current_strain);
+    }
+
+    fn strain_value_of(&mut self, current: &CatchDifficultyObject) -> f64 {
+        let last_player_position = *self
+            .last_player_position
+            .get_or_insert(current.last_normalized_position);
+        // The player only moves as far as they need to catch the object
+        let reach = NORMALIZED_HITOBJECT_RADIUS - ABSOLUTE_PLAYER_POSITIONING_ERROR;
+        let mut player_position = last_player_position.clamp(
+            current.normalized_position - reach,
+            current.normalized_position + reach,
+        );
+        let distance_moved = player_position - last_player_position;
+        let distance = distance_moved.abs() as f64;
+
+        let weighted_strain_time = current.strain_time + 13.0 + 3.0 / self.catcher_speed_multiplier;
+        let mut distance_addition = distance.powf(1.3) / 510.0;
+        let sqrt_strain = weighted_strain_time.sqrt();
+
+        if distance > 0.1 {
+            let last_distance = self.last_distance_moved.abs() as f64;
+            if last_distance > 0.1 && distance_moved.signum() != self.last_distance_moved.signum() {
+                let bonus_factor = distance.min(50.0) / 50.0;
+                let antiflow_factor = (last_distance.min(70.0) / 70.0).max(0.38);
+                distance_addition += DIRECTION_CHANGE_BONUS / (self.last_strain_time + 16.0).sqrt()
+                    * bonus_factor
+                    * antiflow_factor
+                    * (1.0 - (weighted_strain_time / 1000.0).powi(3)).max(0.0);
+            }
+
+            // Every movement counts for something, giving streams some weight
+            let radius = NORMALIZED_HITOBJECT_RADIUS as f64;
+            distance_addition += 12.5 * distance.min(radius * 2.0) / (radius * 6.0) / sqrt_strain;
+        }
+
+        if current.last_distance_to_hyper_dash <= EDGE_DASH_DISTANCE {
+            let edge_dash_bonus = if current.last_hyper_dash {
+                // A hyperdash always lands the catcher on the object
+                player_position = current.normalized_position;
+                0.0
+            } else {
+                5.7
+            };
+            // Edge dashes are easier when there's less time for them
+            let edge_factor = (EDGE_DASH_DISTANCE - current.last_distance_to_hyper_dash) as f64
+                / EDGE_DASH_DISTANCE as f64;
+            let time_factor = ((current.strain_time * self.catcher_speed_multiplier).min(265.0)
+                / 265.0)
+                .powf(1.5);
+            distance_addition *= 1.0 + edge_dash_bonus * edge_factor * time_factor;
+        }
+
+        self.last_player_position = Some(player_position);
+        self.last_distance_moved = distance_moved;
+        self.last_strain_time = current.strain_time;
+        distance_addition / weighted_strain_time
+    }
+
+    pub(super) fn difficulty_value(&self) -> f64 {
+        weighted_sum(self.peaks.peaks(), DECAY_WEIGHT)
+    }
+}
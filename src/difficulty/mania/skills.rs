@@ -0,0 +1,102 @@
+//! Strain from pressing keys, per column and across the keyboard, with a
+//! bonus for notes played while holding another key.
+
+use super::ManiaDifficultyObject;
+use crate::difficulty::skill::{strain_decay, weighted_sum, StrainPeaks};
+
+const SECTION_LENGTH: f64 = 400.0;
+const DECAY_WEIGHT: f64 = 0.9;
+
+const INDIVIDUAL_DECAY_BASE: f64 = 0.125;
+const OVERALL_DECAY_BASE: f64 = 0.3;
+/// Releases this close to another end are easy.
+const RELEASE_THRESHOLD: f64 = 30.0;
+
+pub(super) struct Strain {
+    start_times: Vec<f64>,
+    end_times: Vec<f64>,
+    individual_strains: Vec<f64>,
+    individual_strain: f64,
+    overall_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Strain {
+    pub(super) fn new(columns: usize) -> Self {
+        Strain {
+            start_times: vec![0.0; columns],
+            end_times: vec![0.0; columns],
+            individual_strains: vec![0.0; columns],
+            individual_strain: 0.0,
+            overall_strain: 1.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(super) fn process(&mut self, objects: &[ManiaDifficultyObject], index: usize) {
+        let current = &objects[index];
+        let (individual, overall) = (self.individual_strain, self.overall_strain);
+        let last_start = index
+            .checked_sub(1)
+            .map_or(current.start_time, |previous| objects[previous].start_time);
+        self.peaks.advance(current.start_time, |time| {
+            individual * strain_decay(INDIVIDUAL_DECAY_BASE, time - last_start)
+                + overall * strain_decay(OVERALL_DECAY_BASE, time - last_start)
+        });
+
+        let strain = self.strain_value_of(current);
+        self.peaks.push(strain);
+    }
+
+    fn strain_value_of(&mut self, current: &ManiaDifficultyObject) -> f64 {
+        let (start_time, end_time, column) = (current.start_time, current.end_time, current.column);
+        let mut is_overlapping = false;
+        let mut closest_end_time = (end_time - start_time).abs();
+        // Everything is harder while another key is held
+        let mut hold_factor = 1.0;
+        let mut hold_addition = 0.0;
+
+        for &other_end in self.end_times.iter() {
+            is_overlapping |=
+                definitely_bigger(other_end, start_time) && definitely_bigger(end_time, other_end);
+            if definitely_bigger(other_end, end_time) {
+                hold_factor = 1.25;
+            }
+            closest_end_time = closest_end_time.min((end_time - other_end).abs());
+        }
+
+        // Releasing awkwardly is only hard without another end close by
+        if is_overlapping {
+            hold_addition = 1.0 / (1.0 + (0.27 * (RELEASE_THRESHOLD - closest_end_time)).exp());
+        }
+
+        let column_strain = &mut self.individual_strains[column];
+        *column_strain *=
+            strain_decay(INDIVIDUAL_DECAY_BASE, start_time - self.start_times[column]);
+        *column_strain += 2.0 * hold_factor;
+
+        // A chord is as hard as its hardest column
+        self.individual_strain = if current.delta_time <= 1.0 {
+            self.individual_strain.max(*column_strain)
+        } else {
+            *column_strain
+        };
+
+        self.overall_strain *= strain_decay(OVERALL_DECAY_BASE, current.delta_time);
+        self.overall_strain += (1.0 + hold_addition) * hold_factor;
+
+        self.start_times[column] = start_time;
+        self.end_times[column] = end_time;
+
+        self.individual_strain + self.overall_strain
+    }
+
+    pub(super) fn difficulty_value(&self) -> f64 {
+        weighted_sum(self.peaks.peaks(), DECAY_WEIGHT)
+    }
+}
+
+/// Whether `a` is bigger than `b` by more than a millisecond.
+fn definitely_bigger(a: f64, b: f64) -> bool {
+    a - 1.0 > b
+}
@@ -0,0 +1,108 @@
+//! Star rating calculators, following the current osu! algorithms.
+
+mod catch;
+mod mania;
+mod osu;
+mod skill;
+mod taiko;
+
+pub use catch::CatchDifficultyAttributes;
+pub use mania::ManiaDifficultyAttributes;
+pub use osu::OsuDifficultyAttributes;
+pub(crate) use osu::{base_performance, PERFORMANCE_BASE_MULTIPLIER};
+pub use taiko::TaikoDifficultyAttributes;
+
+use crate::{
+    error::{Error, Result},
+    mods::Mod,
+    performance::{PerformanceAttributes, Score},
+    section::GameMode,
+    Beatmap,
+};
+
+/// The difficulty of a beatmap in any mode, see [`Beatmap::get_difficulty`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifficultyAttributes {
+    Osu(OsuDifficultyAttributes),
+    Taiko(TaikoDifficultyAttributes),
+    Catch(CatchDifficultyAttributes),
+    Mania(ManiaDifficultyAttributes),
+}
+
+impl DifficultyAttributes {
+    pub fn get_star_rating(&self) -> f64 {
+        match self {
+            DifficultyAttributes::Osu(attributes) => attributes.star_rating,
+            DifficultyAttributes::Taiko(attributes) => attributes.star_rating,
+            DifficultyAttributes::Catch(attributes) => attributes.star_rating,
+            DifficultyAttributes::Mania(attributes) => attributes.star_rating,
+        }
+    }
+
+    pub fn get_max_combo(&self) -> usize {
+        match self {
+            DifficultyAttributes::Osu(attributes) => attributes.max_combo,
+            DifficultyAttributes::Taiko(attributes) => attributes.max_combo,
+            DifficultyAttributes::Catch(attributes) => attributes.max_combo,
+            DifficultyAttributes::Mania(attributes) => attributes.max_combo,
+        }
+    }
+
+    /// Calculates the pp of `score` in the mode these attributes are for.
+    pub fn get_performance(&self, score: &Score) -> Result<PerformanceAttributes> {
+        match self {
+            DifficultyAttributes::Osu(attributes) => attributes.get_performance(score),
+            DifficultyAttributes::Taiko(attributes) => attributes.get_performance(score),
+            DifficultyAttributes::Catch(attributes) => attributes.get_performance(score),
+            DifficultyAttributes::Mania(attributes) => attributes.get_performance(score),
+        }
+    }
+}
+
+impl Beatmap {
+    /// Calculates the star rating of the beatmap played in `mode` with
+    /// `mods`. osu!standard beatmaps can be played in every mode, other
+    /// beatmaps only in their own.
+    pub fn get_difficulty(&self, mode: GameMode, mods: &[Mod]) -> Result<DifficultyAttributes> {
+        self.is_converted_to(mode)?;
+        match mode {
+            GameMode::Osu => self.get_osu_difficulty(mods).map(DifficultyAttributes::Osu),
+            GameMode::Taiko => self
+                .get_taiko_difficulty(mods)
+                .map(DifficultyAttributes::Taiko),
+            GameMode::Catch => self
+                .get_catch_difficulty(mods)
+                .map(DifficultyAttributes::Catch),
+            GameMode::Mania => self
+                .get_mania_difficulty(mods)
+                .map(DifficultyAttributes::Mania),
+            GameMode::Unknown(value) => Err(Error::build(format!("unknown mode {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DifficultyAttributes;
+    use crate::{Beatmap, GameMode, Mod};
+
+    #[test]
+    fn test_every_mode() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+        let osu = beatmap.get_difficulty(GameMode::Osu, &[]).unwrap();
+        assert_eq!(
+            osu,
+            DifficultyAttributes::Osu(beatmap.get_osu_difficulty(&[]).unwrap())
+        );
+        assert_eq!(osu.get_max_combo(), beatmap.get_max_combo());
+        assert!(beatmap.get_difficulty(GameMode::Unknown(4), &[]).is_err());
+
+        for mode in [GameMode::Taiko, GameMode::Catch, GameMode::Mania] {
+            let nomod = beatmap.get_difficulty(mode, &[]).unwrap();
+            let double_time = beatmap.get_difficulty(mode, &[Mod::DoubleTime]).unwrap();
+            assert!(nomod.get_star_rating() > 0.0);
+            assert!(double_time.get_star_rating() > nomod.get_star_rating());
+            assert!(nomod.get_performance(&crate::Score::new()).unwrap().pp > 0.0);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! How hard a single object is to aim, tap, read in rhythm and read under
+//! Flashlight, judged from the objects before it.
+
+use std::f64::consts::PI;
+
+use super::object::{OsuDifficultyObject, OsuKind};
+
+const WIDE_ANGLE_MULTIPLIER: f64 = 1.5;
+const ACUTE_ANGLE_MULTIPLIER: f64 = 1.95;
+const SLIDER_MULTIPLIER: f64 = 1.35;
+const VELOCITY_CHANGE_MULTIPLIER: f64 = 0.75;
+
+/// Spacing above which a stream stops getting harder to tap.
+const SINGLE_SPACING_THRESHOLD: f64 = 125.0;
+/// Objects closer together than this get a speed bonus, about 200 BPM 1/4th.
+const MIN_SPEED_BONUS: f64 = 75.0;
+const SPEED_BALANCING_FACTOR: f64 = 40.0;
+
+const RHYTHM_HISTORY_TIME_MAX: f64 = 5000.0;
+const RHYTHM_HISTORY_OBJECTS_MAX: usize = 32;
+const RHYTHM_MULTIPLIER: f64 = 0.75;
+
+const FLASHLIGHT_MAX_OPACITY_BONUS: f64 = 0.4;
+const FLASHLIGHT_HIDDEN_BONUS: f64 = 0.2;
+const FLASHLIGHT_MIN_VELOCITY: f64 = 0.5;
+const FLASHLIGHT_SLIDER_MULTIPLIER: f64 = 1.3;
+const FLASHLIGHT_MIN_ANGLE_MULTIPLIER: f64 = 0.2;
+
+/// What the evaluators need besides the objects themselves.
+pub(crate) struct EvaluatorContext {
+    pub(crate) radius: f64,
+    pub(crate) preempt: f64,
+    pub(crate) fade_in: f64,
+    pub(crate) hidden: bool,
+}
+
+/// The aim difficulty of `objects[index]`: its velocity, with bonuses for
+/// sharp and wide angles, changes in velocity and, optionally, sliders.
+pub(crate) fn evaluate_aim(
+    objects: &[OsuDifficultyObject],
+    index: usize,
+    with_slider_travel_distance: bool,
+) -> f64 {
+    let current = &objects[index];
+    if index <= 1
+        || current.base.kind == OsuKind::Spinner
+        || objects[index - 1].base.kind == OsuKind::Spinner
+    {
+        return 0.0;
+    }
+
+    let last = &objects[index - 1];
+    let last_last = &objects[index - 2];
+
+    // Jumps from the end of a slider can be shortened by leaving it early
+    let mut current_velocity = current.lazy_jump_distance / current.strain_time;
+    if last.base.kind == OsuKind::Slider && with_slider_travel_distance {
+        let travel_velocity = last.travel_distance / last.travel_time;
+        let movement_velocity = current.minimum_jump_distance / current.minimum_jump_time;
+        current_velocity = current_velocity.max(movement_velocity + travel_velocity);
+    }
+
+    let mut previous_velocity = last.lazy_jump_distance / last.strain_time;
+    if last_last.base.kind == OsuKind::Slider && with_slider_travel_distance {
+        let travel_velocity = last_last.travel_distance / last_last.travel_time;
+        let movement_velocity = last.minimum_jump_distance / last.minimum_jump_time;
+        previous_velocity = previous_velocity.max(movement_velocity + travel_velocity);
+    }
+
+    let mut wide_angle_bonus = 0.0;
+    let mut acute_angle_bonus = 0.0;
+    let mut velocity_change_bonus = 0.0;
+    let mut aim_strain = current_velocity;
+
+    // Angles only matter when the rhythm stays about the same
+    if current.strain_time.max(last.strain_time) < 1.25 * current.strain_time.min(last.strain_time)
+    {
+        if let (Some(current_angle), Some(last_angle), Some(last_last_angle)) =
+            (current.angle, last.angle, last_last.angle)
+        {
+            let angle_bonus = current_velocity.min(previous_velocity);
+
+            wide_angle_bonus = wide_angle_bonus_of(current_angle);
+            acute_angle_bonus = acute_angle_bonus_of(current_angle);
+
+            // Only rewards acute angles that are at least 300 BPM 1/2 and
+            // spaced out more than a circle apart
+            if current.strain_time > 100.0 {
+                acute_angle_bonus = 0.0;
+            } else {
+                acute_angle_bonus *= acute_angle_bonus_of(last_angle)
+                    * angle_bonus.min(125.0 / current.strain_time)
+                    * (PI / 2.0 * ((100.0 - current.strain_time) / 25.0).min(1.0))
+                        .sin()
+                        .powi(2)
+                    * (PI / 2.0 * (current.lazy_jump_distance.clamp(50.0, 100.0) - 50.0) / 50.0)
+                        .sin()
+                        .powi(2);
+            }
+
+            // Repeating the same angle is easier than the first time
+            wide_angle_bonus *=
+                angle_bonus * (1.0 - wide_angle_bonus.min(wide_angle_bonus_of(last_angle).powi(3)));
+            acute_angle_bonus *= 0.5
+                + 0.5
+                    * (1.0 - acute_angle_bonus.min(acute_angle_bonus_of(last_last_angle).powi(3)));
+        }
+    }
+
+    if previous_velocity.max(current_velocity) != 0.0 {
+        // Slider travel counts here even when sliders are left out elsewhere
+        previous_velocity =
+            (last.lazy_jump_distance + last_last.travel_distance) / last.strain_time;
+        current_velocity =
+            (current.lazy_jump_distance + last.travel_distance) / current.strain_time;
+
+        let distance_ratio = (PI / 2.0 * (previous_velocity - current_velocity).abs()
+            / previous_velocity.max(current_velocity))
+        .sin()
+        .powi(2);
+        let overlap_velocity_buff = (125.0 / current.strain_time.min(last.strain_time))
+            .min((previous_velocity - current_velocity).abs());
+
+        velocity_change_bonus = overlap_velocity_buff
+            * distance_ratio
+            * (current.strain_time.min(last.strain_time)
+                / current.strain_time.max(last.strain_time))
+            .powi(2);
+    }
+
+    aim_strain += (acute_angle_bonus * ACUTE_ANGLE_MULTIPLIER).max(
+        wide_angle_bonus * WIDE_ANGLE_MULTIPLIER
+            + velocity_change_bonus * VELOCITY_CHANGE_MULTIPLIER,
+    );
+
+    if with_slider_travel_distance && last.base.kind == OsuKind::Slider {
+        aim_strain += last.travel_distance / last.travel_time * SLIDER_MULTIPLIER;
+    }
+
+    aim_strain
+}
+
+fn wide_angle_bonus_of(angle: f64) -> f64 {
+    (0.75 * (angle.clamp(PI / 6.0, 5.0 / 6.0 * PI) - PI / 6.0))
+        .sin()
+        .powi(2)
+}
+
+fn acute_angle_bonus_of(angle: f64) -> f64 {
+    1.0 - wide_angle_bonus_of(angle)
+}
+
+/// The tapping difficulty of `objects[index]` from how soon it follows the
+/// last object and how far apart they are.
+pub(crate) fn evaluate_speed(objects: &[OsuDifficultyObject], index: usize) -> f64 {
+    let current = &objects[index];
+    if current.base.kind == OsuKind::Spinner {
+        return 0.0;
+    }
+
+    let doubletapness = 1.0 - current.doubletapness(objects.get(index + 1));
+
+    // Cap the speed at what the 300 window allows, so it can't be
+    // outweighed by just tapping faster than needed
+    let mut strain_time = current.strain_time;
+    strain_time /= (strain_time / current.hit_window_great / 0.93).clamp(0.92, 1.0);
+
+    let mut speed_bonus = 1.0;
+    if strain_time < MIN_SPEED_BONUS {
+        speed_bonus += 0.75 * ((MIN_SPEED_BONUS - strain_time) / SPEED_BALANCING_FACTOR).powi(2);
+    }
+
+    let travel_distance = index
+        .checked_sub(1)
+        .map_or(0.0, |last| objects[last].travel_distance);
+    let distance = SINGLE_SPACING_THRESHOLD.min(travel_distance + current.minimum_jump_distance);
+
+    (speed_bonus + speed_bonus * (distance / SINGLE_SPACING_THRESHOLD).powf(3.5)) * doubletapness
+        / strain_time
+}
+
+/// How complex the rhythm leading up to `objects[index]` is, at least 1.
+/// Rewards changes in spacing between groups of evenly spaced objects.
+pub(crate) fn evaluate_rhythm(objects: &[OsuDifficultyObject], index: usize) -> f64 {
+    let current = &objects[index];
+    if current.base.kind == OsuKind::Spinner {
+        return 0.0;
+    }
+
+    let previous = |back: usize| &objects[index - back - 1];
+
+    let mut previous_island_size = 0;
+    let mut rhythm_complexity_sum = 0.0;
+    let mut island_size = 1;
+    // Ratio of the rhythm change that started the current island
+    let mut start_ratio = 0.0;
+    let mut first_delta_switch = false;
+
+    let historical_note_count = index.min(RHYTHM_HISTORY_OBJECTS_MAX);
+    let mut rhythm_start = 0;
+    while rhythm_start + 2 < historical_note_count
+        && current.start_time - previous(rhythm_start).start_time < RHYTHM_HISTORY_TIME_MAX
+    {
+        rhythm_start += 1;
+    }
+
+    for i in (1..=rhythm_start).rev() {
+        let current_object = previous(i - 1);
+        let previous_object = previous(i);
+        let last_object = previous(i + 1);
+
+        // Older rhythm changes count for less
+        let historical_decay = ((RHYTHM_HISTORY_TIME_MAX
+            - (current.start_time - current_object.start_time))
+            / RHYTHM_HISTORY_TIME_MAX)
+            .min((historical_note_count - i) as f64 / historical_note_count as f64);
+
+        let current_delta = current_object.strain_time;
+        let previous_delta = previous_object.strain_time;
+        let last_delta = last_object.strain_time;
+        let current_ratio = 1.0
+            + 6.0
+                * (PI / (previous_delta.min(current_delta) / previous_delta.max(current_delta)))
+                    .sin()
+                    .powi(2)
+                    .min(0.5);
+
+        // Changes smaller than the hit window can be played as if there were none
+        let window = current_object.hit_window_great * 0.3;
+        let window_penalty =
+            (((previous_delta - current_delta).abs() - window).max(0.0) / window).min(1.0);
+        let mut effective_ratio = window_penalty * current_ratio;
+
+        if first_delta_switch {
+            if !(previous_delta > 1.25 * current_delta || previous_delta * 1.25 < current_delta) {
+                // The island keeps going
+                if island_size < 7 {
+                    island_size += 1;
+                }
+            } else {
+                if current_object.base.kind == OsuKind::Slider {
+                    effective_ratio *= 0.125;
+                }
+                if previous_object.base.kind == OsuKind::Slider {
+                    effective_ratio *= 0.25;
+                }
+                // Repeating the same island size, or one of the same parity, is easier
+                if previous_island_size == island_size {
+                    effective_ratio *= 0.25;
+                }
+                if previous_island_size % 2 == island_size % 2 {
+                    effective_ratio *= 0.5;
+                }
+                // Slowing down over two changes is easy
+                if last_delta > previous_delta + 10.0 && previous_delta > current_delta + 10.0 {
+                    effective_ratio *= 0.125;
+                }
+
+                rhythm_complexity_sum += (effective_ratio * start_ratio).sqrt()
+                    * historical_decay
+                    * (4.0 + island_size as f64).sqrt()
+                    / 2.0
+                    * (4.0 + previous_island_size as f64).sqrt()
+                    / 2.0;
+
+                start_ratio = effective_ratio;
+                previous_island_size = island_size;
+
+                // A slow down ends the switch, only speed ups start one
+                if previous_delta * 1.25 < current_delta {
+                    first_delta_switch = false;
+                }
+                island_size = 1;
+            }
+        } else if previous_delta > 1.25 * current_delta {
+            // The rhythm sped up, starting the first island
+            first_delta_switch = true;
+            start_ratio = effective_ratio;
+            island_size = 1;
+        }
+    }
+
+    (4.0 + rhythm_complexity_sum * RHYTHM_MULTIPLIER).sqrt() / 2.0
+}
+
+/// The reading difficulty of `objects[index]` under Flashlight, from how
+/// far it is from the objects shortly before it and how visible they were.
+pub(crate) fn evaluate_flashlight(
+    objects: &[OsuDifficultyObject],
+    index: usize,
+    context: &EvaluatorContext,
+) -> f64 {
+    let current = &objects[index];
+    if current.base.kind == OsuKind::Spinner {
+        return 0.0;
+    }
+
+    let scaling_factor = 52.0 / context.radius;
+    let mut small_distance_nerf = 1.0;
+    let mut cumulative_strain_time = 0.0;
+    let mut result = 0.0;
+    let mut last = current;
+    let mut angle_repeat_count = 0.0;
+
+    for i in 0..index.min(10) {
+        let previous = &objects[index - i - 1];
+        cumulative_strain_time += last.strain_time;
+
+        if previous.base.kind != OsuKind::Spinner {
+            let jump_distance =
+                (current.base.position - previous.base.end_position).length() as f64;
+
+            // Objects almost on top of the last one are easy to find
+            if i == 0 {
+                small_distance_nerf = (jump_distance / 75.0).min(1.0);
+            }
+
+            // Stacks are no harder to read than a single object
+            let stack_nerf = (previous.lazy_jump_distance / scaling_factor / 25.0).min(1.0);
+
+            // Objects that had faded out are harder to remember
+            let opacity_bonus = 1.0
+                + FLASHLIGHT_MAX_OPACITY_BONUS
+                    * (1.0
+                        - current.base.opacity_at(
+                            previous.base.start_time,
+                            context.preempt,
+                            context.fade_in,
+                            context.hidden,
+                        ));
+
+            result += stack_nerf * opacity_bonus * scaling_factor * jump_distance
+                / cumulative_strain_time;
+
+            if let (Some(previous_angle), Some(current_angle)) = (previous.angle, current.angle) {
+                if (previous_angle - current_angle).abs() < 0.02 {
+                    angle_repeat_count += (1.0 - 0.1 * i as f64).max(0.0);
+                }
+            }
+        }
+
+        last = previous;
+    }
+
+    result = (small_distance_nerf * result).powi(2);
+
+    if context.hidden {
+        result *= 1.0 + FLASHLIGHT_HIDDEN_BONUS;
+    }
+
+    // Patterns that keep the same angle are easier to read
+    result *= FLASHLIGHT_MIN_ANGLE_MULTIPLIER
+        + (1.0 - FLASHLIGHT_MIN_ANGLE_MULTIPLIER) / (angle_repeat_count + 1.0);
+
+    if current.base.kind == OsuKind::Slider {
+        let pixel_travel_distance = current.base.lazy_travel_distance / scaling_factor;
+        let mut slider_bonus = (pixel_travel_distance / current.travel_time
+            - FLASHLIGHT_MIN_VELOCITY)
+            .max(0.0)
+            .sqrt()
+            * pixel_travel_distance;
+        if current.base.repeat_count > 0 {
+            slider_bonus /= (current.base.repeat_count + 1) as f64;
+        }
+        result += slider_bonus * FLASHLIGHT_SLIDER_MULTIPLIER;
+    }
+
+    result
+}
@@ -0,0 +1,174 @@
+mod evaluators;
+mod object;
+mod skills;
+
+use evaluators::EvaluatorContext;
+use object::{OsuDifficultyObject, OsuKind, OsuObject};
+use skills::{Aim, Flashlight, Speed};
+
+use crate::{error::Result, mods::Mod, Beatmap};
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
+/// Multiplier osu! applies to the total performance of a play.
+pub(crate) const PERFORMANCE_BASE_MULTIPLIER: f64 = 1.14;
+
+/// The difficulty of an osu!standard beatmap with a set of mods, see
+/// [`Beatmap::get_osu_difficulty`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OsuDifficultyAttributes {
+    pub star_rating: f64,
+    pub aim_difficulty: f64,
+    pub speed_difficulty: f64,
+    /// 0 unless Flashlight is enabled
+    pub flashlight_difficulty: f64,
+    /// Aim difficulty without sliders over aim difficulty with them
+    pub slider_factor: f64,
+    /// Roughly how many objects are hard to tap
+    pub speed_note_count: f64,
+    /// Approach rate after mods, including rate changes
+    pub approach_rate: f64,
+    /// Overall difficulty after mods, including rate changes
+    pub overall_difficulty: f64,
+    pub drain_rate: f64,
+    pub max_combo: usize,
+    pub circle_count: usize,
+    pub slider_count: usize,
+    pub spinner_count: usize,
+}
+
+impl Beatmap {
+    /// Calculates the osu!standard star rating of the beatmap played with `mods`.
+    pub fn get_osu_difficulty(&self, mods: &[Mod]) -> Result<OsuDifficultyAttributes> {
+        let modded = self.with_mods(mods)?;
+        let beatmap = modded.get_beatmap();
+        let clock_rate = modded.get_clock_rate();
+        let radius = beatmap.get_circle_radius();
+        let great_window = beatmap
+            .get_hit_windows()
+            .map_or(0.0, |windows| windows.great);
+
+        let objects = OsuObject::from_beatmap(&modded);
+        let difficulty_objects =
+            OsuDifficultyObject::create(&objects, radius, great_window, clock_rate);
+
+        let flashlight = modded.has_mod(Mod::Flashlight);
+        let context = EvaluatorContext {
+            radius,
+            preempt: beatmap.get_preempt(),
+            fade_in: beatmap.get_fade_in(),
+            hidden: modded.has_mod(Mod::Hidden),
+        };
+
+        let mut aim = Aim::new(true);
+        let mut aim_no_sliders = Aim::new(false);
+        let mut speed = Speed::new();
+        let mut flashlight_skill = Flashlight::new();
+        for index in 0..difficulty_objects.len() {
+            aim.process(&difficulty_objects, index);
+            aim_no_sliders.process(&difficulty_objects, index);
+            speed.process(&difficulty_objects, index);
+            if flashlight {
+                flashlight_skill.process(&difficulty_objects, index, &context);
+            }
+        }
+
+        let aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+        let aim_rating_no_sliders =
+            aim_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+        let speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+        let flashlight_rating = if flashlight {
+            flashlight_skill.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER
+        } else {
+            0.0
+        };
+
+        let slider_factor = if aim_rating > 0.0 {
+            aim_rating_no_sliders / aim_rating
+        } else {
+            1.0
+        };
+
+        let base_aim_performance = base_performance(aim_rating);
+        let base_speed_performance = base_performance(speed_rating);
+        let base_flashlight_performance = flashlight_rating.powi(2) * 25.0;
+        let total_base_performance = (base_aim_performance.powf(1.1)
+            + base_speed_performance.powf(1.1)
+            + base_flashlight_performance.powf(1.1))
+        .powf(1.0 / 1.1);
+
+        // An empty beatmap has no difficulty, even though every rating's
+        // performance has a floor
+        let star_rating = if !objects.is_empty() && total_base_performance > 0.00001 {
+            PERFORMANCE_BASE_MULTIPLIER.cbrt()
+                * 0.027
+                * ((100000.0 / 2f64.powf(1.0 / 1.1) * total_base_performance).cbrt() + 4.0)
+        } else {
+            0.0
+        };
+
+        let count = |kind: OsuKind| objects.iter().filter(|object| object.kind == kind).count();
+
+        Ok(OsuDifficultyAttributes {
+            star_rating,
+            aim_difficulty: aim_rating,
+            speed_difficulty: speed_rating,
+            flashlight_difficulty: flashlight_rating,
+            slider_factor,
+            speed_note_count: speed.relevant_note_count(),
+            approach_rate: modded.get_approach_rate(),
+            overall_difficulty: modded.get_overall_difficulty(),
+            drain_rate: beatmap.get_hp_drain_rate().map_or(5.0, f64::from),
+            max_combo: objects.iter().map(|object| 1 + object.nested_count).sum(),
+            circle_count: count(OsuKind::Circle),
+            slider_count: count(OsuKind::Slider),
+            spinner_count: count(OsuKind::Spinner),
+        })
+    }
+}
+
+/// The performance a skill's rating would be worth on its own.
+pub(crate) fn base_performance(rating: f64) -> f64 {
+    (5.0 * (rating / DIFFICULTY_MULTIPLIER).max(1.0) - 4.0).powi(3) / 100000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Beatmap, Mod};
+
+    #[test]
+    fn test_star_rating() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+        let nomod = beatmap.get_osu_difficulty(&[]).unwrap();
+
+        // Snapshots of this crate's own output, kept to catch regressions.
+        // They are not osu! reference values yet: replace them with osu-tools
+        // `difficulty` output for beatmap 2321526 and record its lazer version.
+        assert!((nomod.star_rating - 3.08594).abs() < 1e-3);
+        assert!(nomod.aim_difficulty > 0.0 && nomod.speed_difficulty > 0.0);
+        assert_eq!(nomod.flashlight_difficulty, 0.0);
+        assert!(nomod.slider_factor > 0.0 && nomod.slider_factor <= 1.0);
+        assert_eq!(nomod.approach_rate, 8.0);
+        assert!((nomod.overall_difficulty - 6.0).abs() < 1e-9);
+        assert!(nomod.max_combo >= nomod.circle_count + nomod.slider_count * 2);
+
+        let double_time = beatmap.get_osu_difficulty(&[Mod::DoubleTime]).unwrap();
+        assert!((double_time.star_rating - 4.26725).abs() < 1e-3);
+        assert_eq!(double_time.max_combo, nomod.max_combo);
+
+        let hard_rock = beatmap.get_osu_difficulty(&[Mod::HardRock]).unwrap();
+        assert!((hard_rock.star_rating - 3.22011).abs() < 1e-3);
+
+        let flashlight = beatmap.get_osu_difficulty(&[Mod::Flashlight]).unwrap();
+        assert!((flashlight.flashlight_difficulty - 0.71892).abs() < 1e-3);
+        assert!((flashlight.star_rating - 3.51288).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_empty_beatmap() {
+        let beatmap = Beatmap::from_bytes(b"osu file format v14\n\n[HitObjects]\n").unwrap();
+        let attributes = beatmap.get_osu_difficulty(&[]).unwrap();
+
+        assert_eq!(attributes.star_rating, 0.0);
+        assert_eq!(attributes.max_combo, 0);
+    }
+}
@@ -0,0 +1,356 @@
+//! Hit objects as the osu!standard skills see them: stacked positions, the
+//! lazy cursor path through sliders, and the movement between objects.
+
+use crate::{
+    mods::ModdedBeatmap, section::HitObject, slider_timing::LEGACY_LAST_TICK_OFFSET, utils::Vec2,
+};
+
+/// Distances are scaled to a circle of this radius so every circle size
+/// compares the same.
+pub(crate) const NORMALISED_RADIUS: f64 = 50.0;
+/// Shortest time between objects, so simultaneous ones don't divide by zero.
+pub(crate) const MIN_DELTA_TIME: f64 = 25.0;
+const MAXIMUM_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 2.4;
+const ASSUMED_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 1.8;
+/// Share of the preempt Hidden fades objects out over.
+const HIDDEN_FADE_OUT_DURATION: f64 = 0.3;
+/// Share of the preempt Hidden fades objects in over.
+const HIDDEN_FADE_IN_DURATION: f64 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum OsuKind {
+    Circle,
+    Slider,
+    Spinner,
+}
+
+/// A hit object with stacking applied. Times are as written in the file.
+#[derive(Debug, Clone)]
+pub(crate) struct OsuObject {
+    pub(crate) kind: OsuKind,
+    pub(crate) start_time: f64,
+    pub(crate) end_time: f64,
+    pub(crate) position: Vec2,
+    pub(crate) end_position: Vec2,
+    pub(crate) repeat_count: usize,
+    /// Where a player following the slider lazily lets go of it
+    pub(crate) lazy_end_position: Vec2,
+    pub(crate) lazy_travel_distance: f64,
+    pub(crate) lazy_travel_time: f64,
+    /// Ticks, repeats and the tail, which each add to the combo
+    pub(crate) nested_count: usize,
+}
+
+/// Part of a slider the cursor has to pass over.
+struct Nested {
+    time: f64,
+    position: Vec2,
+    is_repeat: bool,
+}
+
+impl OsuObject {
+    pub(crate) fn from_beatmap(modded: &ModdedBeatmap) -> Vec<OsuObject> {
+        let beatmap = modded.get_beatmap();
+        let radius = beatmap.get_circle_radius();
+        let heights = beatmap.get_stack_heights();
+
+        beatmap
+            .iter_hit_objects()
+            .zip(heights)
+            .map(|(hit_object, height)| {
+                let offset = beatmap.get_stack_offset(height);
+                let position =
+                    Vec2::new(hit_object.get_x() as f32, hit_object.get_y() as f32) + offset;
+                let start_time = hit_object.get_time() as f64;
+
+                match hit_object {
+                    HitObject::Slider(slider) => {
+                        let timing = beatmap.get_slider_timing(slider);
+                        let path = slider.get_path();
+                        let slides = slider.get_span_count();
+                        let span_end = |span: usize| {
+                            let progress = if span.is_multiple_of(2) { 1.0 } else { 0.0 };
+                            path.position_at(progress) + offset
+                        };
+
+                        let mut ticks = timing.get_ticks().into_iter().peekable();
+                        let mut nested = Vec::new();
+                        for span in 0..slides {
+                            while let Some(tick) = ticks.next_if(|tick| tick.get_span() == span) {
+                                nested.push(Nested {
+                                    time: tick.get_time(),
+                                    position: path.position_at(tick.get_progress()) + offset,
+                                    is_repeat: false,
+                                });
+                            }
+                            if span + 1 < slides {
+                                nested.push(Nested {
+                                    time: start_time
+                                        + (span + 1) as f64 * timing.get_span_duration(),
+                                    position: span_end(span),
+                                    is_repeat: true,
+                                });
+                            }
+                        }
+                        let end_position = span_end(slides - 1);
+
+                        let mut object = OsuObject {
+                            kind: OsuKind::Slider,
+                            start_time,
+                            end_time: timing.get_end_time(),
+                            position,
+                            end_position,
+                            repeat_count: slides - 1,
+                            lazy_end_position: end_position,
+                            lazy_travel_distance: 0.0,
+                            lazy_travel_time: 0.0,
+                            nested_count: nested.len() + 1,
+                        };
+                        object.compute_lazy_path(
+                            &nested,
+                            radius,
+                            timing.get_span_duration(),
+                            |progress| path.position_at(progress) + offset,
+                        );
+                        object
+                    }
+                    _ => {
+                        let is_spinner = matches!(hit_object, HitObject::Spinner(_));
+                        // Spinners always sit in the middle of the playfield
+                        let position = if is_spinner {
+                            Vec2::new(256.0, 192.0)
+                        } else {
+                            position
+                        };
+                        OsuObject {
+                            kind: if is_spinner {
+                                OsuKind::Spinner
+                            } else {
+                                OsuKind::Circle
+                            },
+                            start_time,
+                            end_time: beatmap.get_hit_object_end_time(hit_object),
+                            position,
+                            end_position: position,
+                            repeat_count: 0,
+                            lazy_end_position: position,
+                            lazy_travel_distance: 0.0,
+                            lazy_travel_time: 0.0,
+                            nested_count: 0,
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Follows the slider with the least movement that still keeps the
+    /// cursor within a generous radius of every tick, repeat and the tail.
+    fn compute_lazy_path(
+        &mut self,
+        nested: &[Nested],
+        radius: f64,
+        span_duration: f64,
+        position_at: impl Fn(f64) -> Vec2,
+    ) {
+        // The player only has to hold on until a little before the real end,
+        // unless a tick or repeat is even later
+        let last_nested = nested.last().map_or(self.start_time, |nested| nested.time);
+        let tracking_end_time = (self.end_time - LEGACY_LAST_TICK_OFFSET).max(last_nested);
+        self.lazy_travel_time = tracking_end_time - self.start_time;
+
+        let mut end_progress = self.lazy_travel_time / span_duration;
+        end_progress = if end_progress % 2.0 >= 1.0 {
+            1.0 - end_progress % 1.0
+        } else {
+            end_progress % 1.0
+        };
+        self.lazy_end_position = position_at(end_progress);
+
+        let scaling_factor = NORMALISED_RADIUS / radius;
+        let mut cursor = self.position;
+        let tail = Nested {
+            time: self.end_time,
+            position: self.end_position,
+            is_repeat: false,
+        };
+
+        for (index, point) in nested.iter().chain(std::iter::once(&tail)).enumerate() {
+            let is_tail = index == nested.len();
+            let mut movement = point.position - cursor;
+            let mut required_movement = ASSUMED_SLIDER_RADIUS;
+
+            if is_tail {
+                // The lazy end can be closer than the real one on curved sliders
+                let lazy_movement = self.lazy_end_position - cursor;
+                if lazy_movement.length() < movement.length() {
+                    movement = lazy_movement;
+                }
+            } else if point.is_repeat {
+                required_movement = NORMALISED_RADIUS;
+            }
+
+            let mut movement_length = scaling_factor * movement.length() as f64;
+            if movement_length > required_movement {
+                let kept = (movement_length - required_movement) / movement_length;
+                cursor = cursor + movement * kept as f32;
+                movement_length *= kept;
+                self.lazy_travel_distance += movement_length;
+            }
+
+            if is_tail {
+                self.lazy_end_position = cursor;
+            }
+        }
+    }
+
+    /// How visible the object is at `time` (in file time), from 0 to 1.
+    pub(crate) fn opacity_at(&self, time: f64, preempt: f64, fade_in: f64, hidden: bool) -> f64 {
+        if time > self.start_time {
+            return 0.0;
+        }
+
+        let fade_in_start = self.start_time - preempt;
+        if hidden {
+            let fade_in = preempt * HIDDEN_FADE_IN_DURATION;
+            let fade_out_start = fade_in_start + fade_in;
+            let fade_out_duration = preempt * HIDDEN_FADE_OUT_DURATION;
+            ((time - fade_in_start) / fade_in)
+                .clamp(0.0, 1.0)
+                .min(1.0 - ((time - fade_out_start) / fade_out_duration).clamp(0.0, 1.0))
+        } else {
+            ((time - fade_in_start) / fade_in).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// An object together with the movement from the ones before it. Times are
+/// divided by the clock rate.
+#[derive(Debug, Clone)]
+pub(crate) struct OsuDifficultyObject<'a> {
+    pub(crate) base: &'a OsuObject,
+    pub(crate) start_time: f64,
+    pub(crate) delta_time: f64,
+    pub(crate) strain_time: f64,
+    pub(crate) lazy_jump_distance: f64,
+    pub(crate) minimum_jump_distance: f64,
+    pub(crate) minimum_jump_time: f64,
+    pub(crate) travel_distance: f64,
+    pub(crate) travel_time: f64,
+    pub(crate) angle: Option<f64>,
+    /// The full width of the 300 window
+    pub(crate) hit_window_great: f64,
+}
+
+impl<'a> OsuDifficultyObject<'a> {
+    /// One object for every hit object after the first.
+    pub(crate) fn create(
+        objects: &'a [OsuObject],
+        radius: f64,
+        great_window: f64,
+        clock_rate: f64,
+    ) -> Vec<OsuDifficultyObject<'a>> {
+        (1..objects.len())
+            .map(|i| {
+                let last_last = i.checked_sub(2).map(|index| &objects[index]);
+                OsuDifficultyObject::new(
+                    &objects[i],
+                    &objects[i - 1],
+                    last_last,
+                    radius,
+                    great_window,
+                    clock_rate,
+                )
+            })
+            .collect()
+    }
+
+    fn new(
+        base: &'a OsuObject,
+        last: &OsuObject,
+        last_last: Option<&OsuObject>,
+        radius: f64,
+        great_window: f64,
+        clock_rate: f64,
+    ) -> Self {
+        let delta_time = (base.start_time - last.start_time) / clock_rate;
+        let strain_time = delta_time.max(MIN_DELTA_TIME);
+
+        let mut object = OsuDifficultyObject {
+            base,
+            start_time: base.start_time / clock_rate,
+            delta_time,
+            strain_time,
+            lazy_jump_distance: 0.0,
+            minimum_jump_distance: 0.0,
+            minimum_jump_time: 0.0,
+            travel_distance: 0.0,
+            travel_time: 0.0,
+            angle: None,
+            hit_window_great: 2.0 * great_window / clock_rate,
+        };
+
+        if base.kind == OsuKind::Slider {
+            // Bonus for repeat sliders until a better per nested object strain system can be achieved
+            object.travel_distance =
+                base.lazy_travel_distance * (1.0 + base.repeat_count as f64 / 2.5).powf(1.0 / 2.5);
+            object.travel_time = (base.lazy_travel_time / clock_rate).max(MIN_DELTA_TIME);
+        }
+
+        if base.kind == OsuKind::Spinner || last.kind == OsuKind::Spinner {
+            return object;
+        }
+
+        let mut scaling_factor = NORMALISED_RADIUS / radius;
+        if radius < 30.0 {
+            let small_circle_bonus = (30.0 - radius).min(5.0) / 50.0;
+            scaling_factor *= 1.0 + small_circle_bonus;
+        }
+
+        let last_cursor = last.lazy_end_position;
+        object.lazy_jump_distance = (base.position - last_cursor).length() as f64 * scaling_factor;
+        object.minimum_jump_time = strain_time;
+        object.minimum_jump_distance = object.lazy_jump_distance;
+
+        if last.kind == OsuKind::Slider {
+            let last_travel_time = (last.lazy_travel_time / clock_rate).max(MIN_DELTA_TIME);
+            object.minimum_jump_time = (strain_time - last_travel_time).max(MIN_DELTA_TIME);
+
+            // The cursor either jumps from the lazy end, or follows the slider
+            // to its tail first, whichever is shorter
+            let tail_jump_distance =
+                (last.end_position - base.position).length() as f64 * scaling_factor;
+            object.minimum_jump_distance = (object.lazy_jump_distance
+                - (MAXIMUM_SLIDER_RADIUS - ASSUMED_SLIDER_RADIUS))
+                .min(tail_jump_distance - MAXIMUM_SLIDER_RADIUS)
+                .max(0.0);
+        }
+
+        if let Some(last_last) = last_last.filter(|object| object.kind != OsuKind::Spinner) {
+            let v1 = last_last.lazy_end_position - last.position;
+            let v2 = base.position - last_cursor;
+            let dot = v1.dot(v2) as f64;
+            let det = (v1.x * v2.y - v1.y * v2.x) as f64;
+            object.angle = Some(det.atan2(dot).abs());
+        }
+
+        object
+    }
+
+    /// How alike the rhythm to the next object is, 1 when it can be
+    /// double tapped as easily as it can be alternated.
+    pub(crate) fn doubletapness(&self, next: Option<&OsuDifficultyObject>) -> f64 {
+        let Some(next) = next else {
+            return 0.0;
+        };
+
+        let current_delta_time = self.delta_time.max(1.0);
+        let next_delta_time = next.delta_time.max(1.0);
+        let delta_difference = (next_delta_time - current_delta_time).abs();
+        let speed_ratio = current_delta_time / current_delta_time.max(delta_difference);
+        let window_ratio = (current_delta_time / self.hit_window_great)
+            .min(1.0)
+            .powi(2);
+        1.0 - speed_ratio.powf(1.0 - window_ratio)
+    }
+}
@@ -0,0 +1,185 @@
+//! Aim, speed and Flashlight strain, which build up over dense sections and
+//! decay between them.
+
+use super::{
+    evaluators::{
+        evaluate_aim, evaluate_flashlight, evaluate_rhythm, evaluate_speed, EvaluatorContext,
+    },
+    object::OsuDifficultyObject,
+};
+use crate::difficulty::skill::{lerp, strain_decay, weighted_sum, StrainPeaks};
+
+const SECTION_LENGTH: f64 = 400.0;
+const DECAY_WEIGHT: f64 = 0.9;
+const REDUCED_STRAIN_BASELINE: f64 = 0.75;
+const DEFAULT_DIFFICULTY_MULTIPLIER: f64 = 1.06;
+
+const AIM_SKILL_MULTIPLIER: f64 = 23.55;
+const AIM_STRAIN_DECAY_BASE: f64 = 0.15;
+
+const SPEED_SKILL_MULTIPLIER: f64 = 1375.0;
+const SPEED_STRAIN_DECAY_BASE: f64 = 0.3;
+const SPEED_REDUCED_SECTION_COUNT: usize = 5;
+const SPEED_DIFFICULTY_MULTIPLIER: f64 = 1.04;
+
+const FLASHLIGHT_SKILL_MULTIPLIER: f64 = 0.052;
+const FLASHLIGHT_STRAIN_DECAY_BASE: f64 = 0.15;
+
+/// Strain from moving the cursor between objects.
+pub(crate) struct Aim {
+    with_sliders: bool,
+    current_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Aim {
+    pub(crate) fn new(with_sliders: bool) -> Self {
+        Aim {
+            with_sliders,
+            current_strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(crate) fn process(&mut self, objects: &[OsuDifficultyObject], index: usize) {
+        let current = &objects[index];
+        let strain = self.current_strain;
+        let last_start = previous_start_time(objects, index);
+        self.peaks.advance(current.start_time, |time| {
+            strain * strain_decay(AIM_STRAIN_DECAY_BASE, time - last_start)
+        });
+
+        self.current_strain *= strain_decay(AIM_STRAIN_DECAY_BASE, current.delta_time);
+        self.current_strain +=
+            evaluate_aim(objects, index, self.with_sliders) * AIM_SKILL_MULTIPLIER;
+        self.peaks.push(self.current_strain);
+    }
+
+    pub(crate) fn difficulty_value(&self) -> f64 {
+        reduced_difficulty_value(self.peaks.peaks(), 10, DEFAULT_DIFFICULTY_MULTIPLIER)
+    }
+}
+
+/// Strain from tapping, scaled by how complex the rhythm is.
+pub(crate) struct Speed {
+    current_strain: f64,
+    current_rhythm: f64,
+    object_strains: Vec<f64>,
+    peaks: StrainPeaks,
+}
+
+impl Speed {
+    pub(crate) fn new() -> Self {
+        Speed {
+            current_strain: 0.0,
+            current_rhythm: 0.0,
+            object_strains: Vec::new(),
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(crate) fn process(&mut self, objects: &[OsuDifficultyObject], index: usize) {
+        let current = &objects[index];
+        let strain = self.current_strain * self.current_rhythm;
+        let last_start = previous_start_time(objects, index);
+        self.peaks.advance(current.start_time, |time| {
+            strain * strain_decay(SPEED_STRAIN_DECAY_BASE, time - last_start)
+        });
+
+        self.current_strain *= strain_decay(SPEED_STRAIN_DECAY_BASE, current.strain_time);
+        self.current_strain += evaluate_speed(objects, index) * SPEED_SKILL_MULTIPLIER;
+        self.current_rhythm = evaluate_rhythm(objects, index);
+
+        let total_strain = self.current_strain * self.current_rhythm;
+        self.object_strains.push(total_strain);
+        self.peaks.push(total_strain);
+    }
+
+    pub(crate) fn difficulty_value(&self) -> f64 {
+        reduced_difficulty_value(
+            self.peaks.peaks(),
+            SPEED_REDUCED_SECTION_COUNT,
+            SPEED_DIFFICULTY_MULTIPLIER,
+        )
+    }
+
+    /// Roughly how many objects are hard to tap, counting each by how close
+    /// its strain comes to the hardest one.
+    pub(crate) fn relevant_note_count(&self) -> f64 {
+        let max_strain = self.object_strains.iter().copied().fold(0.0, f64::max);
+        if max_strain == 0.0 {
+            return 0.0;
+        }
+
+        self.object_strains
+            .iter()
+            .map(|strain| 1.0 / (1.0 + (-(strain / max_strain * 12.0 - 6.0)).exp()))
+            .sum()
+    }
+}
+
+/// Strain from remembering where objects are when only the area around the
+/// cursor can be seen.
+pub(crate) struct Flashlight {
+    current_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Flashlight {
+    pub(crate) fn new() -> Self {
+        Flashlight {
+            current_strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(crate) fn process(
+        &mut self,
+        objects: &[OsuDifficultyObject],
+        index: usize,
+        context: &EvaluatorContext,
+    ) {
+        let current = &objects[index];
+        let strain = self.current_strain;
+        let last_start = previous_start_time(objects, index);
+        self.peaks.advance(current.start_time, |time| {
+            strain * strain_decay(FLASHLIGHT_STRAIN_DECAY_BASE, time - last_start)
+        });
+
+        self.current_strain *= strain_decay(FLASHLIGHT_STRAIN_DECAY_BASE, current.delta_time);
+        self.current_strain +=
+            evaluate_flashlight(objects, index, context) * FLASHLIGHT_SKILL_MULTIPLIER;
+        self.peaks.push(self.current_strain);
+    }
+
+    /// Unlike the other skills every section counts in full, since
+    /// Flashlight is about endurance.
+    pub(crate) fn difficulty_value(&self) -> f64 {
+        self.peaks.peaks().iter().sum::<f64>() * DEFAULT_DIFFICULTY_MULTIPLIER
+    }
+}
+
+fn previous_start_time(objects: &[OsuDifficultyObject], index: usize) -> f64 {
+    index
+        .checked_sub(1)
+        .map_or(0.0, |last| objects[last].start_time)
+}
+
+/// Weighs the peaks from hardest to easiest, first toning down the
+/// `reduced_section_count` hardest so a few spikes don't dominate.
+fn reduced_difficulty_value(
+    peaks: Vec<f64>,
+    reduced_section_count: usize,
+    difficulty_multiplier: f64,
+) -> f64 {
+    let mut strains: Vec<f64> = peaks.into_iter().filter(|peak| *peak > 0.0).collect();
+    strains.sort_by(|a, b| b.total_cmp(a));
+
+    for (i, strain) in strains.iter_mut().take(reduced_section_count).enumerate() {
+        let progress = (i as f64 / reduced_section_count as f64).clamp(0.0, 1.0);
+        let scale = lerp(1.0, 10.0, progress).log10();
+        *strain *= lerp(REDUCED_STRAIN_BASELINE, 1.0, scale);
+    }
+
+    weighted_sum(strains, DECAY_WEIGHT) * difficulty_multiplier
+}
@@ -0,0 +1,76 @@
+//! The strain bookkeeping shared by every skill: the hardest moment in each
+//! section of the map, and how those peaks add up to a difficulty.
+
+/// Tracks the highest strain in each `section_length` millisecond section.
+#[derive(Debug)]
+pub(crate) struct StrainPeaks {
+    section_length: f64,
+    current_peak: f64,
+    current_end: Option<f64>,
+    peaks: Vec<f64>,
+}
+
+impl StrainPeaks {
+    pub(crate) fn new(section_length: f64) -> Self {
+        StrainPeaks {
+            section_length,
+            current_peak: 0.0,
+            current_end: None,
+            peaks: Vec::new(),
+        }
+    }
+
+    /// Closes every section that ends before `time`. Each new section starts
+    /// at the strain `initial` gives for its start time, so a peak can come
+    /// from strain carried over rather than from an object in it.
+    pub(crate) fn advance(&mut self, time: f64, initial: impl Fn(f64) -> f64) {
+        let section_length = self.section_length;
+        let mut end = *self
+            .current_end
+            .get_or_insert_with(|| (time / section_length).ceil() * section_length);
+
+        while time > end {
+            self.peaks.push(self.current_peak);
+            self.current_peak = initial(end);
+            end += section_length;
+        }
+        self.current_end = Some(end);
+    }
+
+    pub(crate) fn push(&mut self, strain: f64) {
+        self.current_peak = self.current_peak.max(strain);
+    }
+
+    /// The peak of every section, including the one still open.
+    pub(crate) fn peaks(&self) -> Vec<f64> {
+        let mut peaks = self.peaks.clone();
+        if self.current_end.is_some() {
+            peaks.push(self.current_peak);
+        }
+        peaks
+    }
+}
+
+/// How much strain decays over `ms` milliseconds, keeping `base` of it per second.
+pub(crate) fn strain_decay(base: f64, ms: f64) -> f64 {
+    base.powf(ms / 1000.0)
+}
+
+/// Adds up peaks from highest to lowest, each weighted `decay_weight` times
+/// less than the one before.
+pub(crate) fn weighted_sum(mut peaks: Vec<f64>, decay_weight: f64) -> f64 {
+    peaks.sort_by(|a, b| b.total_cmp(a));
+
+    let mut weight = 1.0;
+    let mut difficulty = 0.0;
+    for peak in peaks {
+        difficulty += peak * weight;
+        weight *= decay_weight;
+    }
+    difficulty
+}
+
+/// Linear interpolation from `start` to `end`.
+pub(crate) fn lerp(start: f64, end: f64, amount: f64) -> f64 {
+    start + (end - start) * amount
+}
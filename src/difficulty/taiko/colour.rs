@@ -0,0 +1,153 @@
+//! Colour patterns: runs of one colour, runs of those with the same length,
+//! and how recently the same pattern was played. Only the first object of
+//! each pattern gets its difficulty.
+
+use super::object::TaikoDifficultyObject;
+
+/// Patterns repeated further back than this count as new.
+const MAX_REPETITION_INTERVAL: usize = 16;
+
+/// Objects in a row of the same colour.
+struct MonoStreak {
+    objects: Vec<usize>,
+}
+
+impl MonoStreak {
+    fn first(&self) -> usize {
+        self.objects[0]
+    }
+}
+
+/// Mono streaks in a row of the same length, alternating colour.
+struct AlternatingMonoPattern {
+    streaks: Vec<usize>,
+}
+
+/// Alternating patterns grouped with the ones that repeat them.
+struct RepeatingHitPatterns {
+    patterns: Vec<usize>,
+    repetition_interval: usize,
+}
+
+/// Encodes the colour patterns of `objects` and sets the colour difficulty
+/// of the objects that start one.
+pub(super) fn assign_colour_difficulty(objects: &mut [TaikoDifficultyObject]) {
+    let streaks = encode_mono_streaks(objects);
+    let patterns = encode_alternating_patterns(&streaks);
+    let repeating = encode_repeating_patterns(&streaks, &patterns, objects);
+
+    for repeating_pattern in repeating.iter() {
+        let repeating_difficulty =
+            2.0 * (1.0 - sigmoid(repeating_pattern.repetition_interval as f64));
+        let first_pattern = &patterns[repeating_pattern.patterns[0]];
+        objects[streaks[first_pattern.streaks[0]].first()].colour_difficulty +=
+            repeating_difficulty;
+
+        for (pattern_index, &pattern) in repeating_pattern.patterns.iter().enumerate() {
+            let pattern = &patterns[pattern];
+            let pattern_difficulty = sigmoid(pattern_index as f64) * repeating_difficulty;
+            objects[streaks[pattern.streaks[0]].first()].colour_difficulty += pattern_difficulty;
+
+            for (streak_index, &streak) in pattern.streaks.iter().enumerate() {
+                let streak_difficulty = sigmoid(streak_index as f64) * pattern_difficulty * 0.5;
+                objects[streaks[streak].first()].colour_difficulty += streak_difficulty;
+            }
+        }
+    }
+}
+
+/// Falls from 1 to 0 around 2.
+fn sigmoid(value: f64) -> f64 {
+    (std::f64::consts::E * -(value - 2.0) / 2.0).tanh() * 0.5 + 0.5
+}
+
+/// Starts a streak whenever the colour changes. Drum rolls and swells
+/// start one too, like in osu!.
+fn encode_mono_streaks(objects: &[TaikoDifficultyObject]) -> Vec<MonoStreak> {
+    let mut streaks: Vec<MonoStreak> = Vec::new();
+    for object in objects.iter() {
+        let previous = object.previous_note.filter(|_| object.rim.is_some());
+        let continues = match (previous, streaks.last()) {
+            (Some(previous), Some(_)) => objects[previous].rim == object.rim,
+            _ => false,
+        };
+
+        match streaks.last_mut() {
+            Some(streak) if continues => streak.objects.push(object.index),
+            _ => streaks.push(MonoStreak {
+                objects: vec![object.index],
+            }),
+        }
+    }
+    streaks
+}
+
+fn encode_alternating_patterns(streaks: &[MonoStreak]) -> Vec<AlternatingMonoPattern> {
+    let mut patterns = Vec::new();
+    let mut current = Vec::new();
+    for (index, streak) in streaks.iter().enumerate() {
+        current.push(index);
+        let ends = streaks
+            .get(index + 1)
+            .is_none_or(|next| next.objects.len() != streak.objects.len());
+        if ends {
+            patterns.push(AlternatingMonoPattern {
+                streaks: std::mem::take(&mut current),
+            });
+        }
+    }
+    patterns
+}
+
+/// Groups patterns with the ones two after them while those repeat them.
+fn encode_repeating_patterns(
+    streaks: &[MonoStreak],
+    patterns: &[AlternatingMonoPattern],
+    objects: &[TaikoDifficultyObject],
+) -> Vec<RepeatingHitPatterns> {
+    let first_streak = |pattern: usize| &streaks[patterns[pattern].streaks[0]];
+    let is_repetition = |a: usize, b: usize| {
+        first_streak(a).objects.len() == first_streak(b).objects.len()
+            && patterns[a].streaks.len() == patterns[b].streaks.len()
+            && objects[first_streak(a).first()].rim == objects[first_streak(b).first()].rim
+    };
+    let is_coupled = |index: usize| index + 2 < patterns.len() && is_repetition(index, index + 2);
+
+    let mut repeating: Vec<RepeatingHitPatterns> = Vec::new();
+    let mut index = 0;
+    while index < patterns.len() {
+        let mut group = Vec::new();
+        if !is_coupled(index) {
+            group.push(index);
+            index += 1;
+        } else {
+            while is_coupled(index) {
+                group.push(index);
+                index += 1;
+            }
+            group.extend([index, index + 1]);
+            index += 2;
+        }
+        repeating.push(RepeatingHitPatterns {
+            patterns: group,
+            repetition_interval: MAX_REPETITION_INTERVAL + 1,
+        });
+    }
+
+    // How many groups back the same group was last played
+    let groups_match = |a: &RepeatingHitPatterns, b: &RepeatingHitPatterns| {
+        a.patterns.len() == b.patterns.len()
+            && a.patterns
+                .iter()
+                .zip(b.patterns.iter())
+                .all(|(&a, &b)| is_repetition(a, b))
+    };
+    for current in 1..repeating.len() {
+        let interval = (1..MAX_REPETITION_INTERVAL.min(current + 1))
+            .find(|&interval| groups_match(&repeating[current], &repeating[current - interval]));
+        if let Some(interval) = interval {
+            repeating[current].repetition_interval = interval;
+        }
+    }
+    repeating
+}
@@ -0,0 +1,93 @@
+use crate::convert::{TaikoKind, TaikoObject};
+
+/// Ratios between consecutive gaps and how hard switching to them is.
+const COMMON_RHYTHMS: [(f64, f64); 9] = [
+    (1.0, 0.0),
+    (2.0, 0.3),
+    (1.0 / 2.0, 0.5),
+    (3.0, 0.3),
+    (1.0 / 3.0, 0.35),
+    (3.0 / 2.0, 0.6),
+    (2.0 / 3.0, 0.4),
+    (5.0 / 4.0, 0.5),
+    (4.0 / 5.0, 0.7),
+];
+
+/// An osu!taiko object with what the skills need to know about it. Times
+/// are in real milliseconds.
+#[derive(Debug, Clone)]
+pub(super) struct TaikoDifficultyObject {
+    pub(super) index: usize,
+    pub(super) start_time: f64,
+    pub(super) delta_time: f64,
+    /// Index into the common rhythms of the gap before over the one before that
+    pub(super) rhythm: usize,
+    /// Whether the object is a kat, `None` for drum rolls and swells
+    pub(super) rim: Option<bool>,
+    /// The hit before this one, for hits
+    pub(super) previous_note: Option<usize>,
+    /// When the last hit two before this one in the same colour was, the
+    /// one hit by the same finger
+    pub(super) same_key_previous_time: Option<f64>,
+    /// Set by the colour encoding for objects that start a pattern
+    pub(super) colour_difficulty: f64,
+}
+
+impl TaikoDifficultyObject {
+    pub(super) fn rhythm_difficulty(&self) -> f64 {
+        COMMON_RHYTHMS[self.rhythm].1
+    }
+
+    /// Builds an object for every one after the first two, which have no
+    /// rhythm to compare against.
+    pub(super) fn create(objects: &[TaikoObject], clock_rate: f64) -> Vec<Self> {
+        let mut difficulty_objects = Vec::new();
+        let mut notes = Vec::new();
+        let mut centres = Vec::new();
+        let mut rims = Vec::new();
+
+        for (i, object) in objects.iter().enumerate().skip(2) {
+            let last = &objects[i - 1];
+            let last_last = &objects[i - 2];
+            let delta_time = (object.time - last.time) / clock_rate;
+            let previous_length = (last.time - last_last.time) / clock_rate;
+
+            // The first of equally close ratios wins, and gaps with nothing
+            // to compare against count as unchanged
+            let ratio = delta_time / previous_length;
+            let mut rhythm = 0;
+            for (index, (common, _)) in COMMON_RHYTHMS.iter().enumerate() {
+                if (common - ratio).abs() < (COMMON_RHYTHMS[rhythm].0 - ratio).abs() {
+                    rhythm = index;
+                }
+            }
+
+            let index = difficulty_objects.len();
+            let start_time = object.time / clock_rate;
+            let mut difficulty_object = TaikoDifficultyObject {
+                index,
+                start_time,
+                delta_time,
+                rhythm,
+                rim: None,
+                previous_note: None,
+                same_key_previous_time: None,
+                colour_difficulty: 0.0,
+            };
+
+            if let TaikoKind::Hit { rim } = object.kind {
+                let same_colour = if rim { &mut rims } else { &mut centres };
+                difficulty_object.rim = Some(rim);
+                difficulty_object.previous_note = notes.last().copied();
+                difficulty_object.same_key_previous_time = same_colour
+                    .len()
+                    .checked_sub(2)
+                    .map(|previous: usize| same_colour[previous]);
+                same_colour.push(start_time);
+                notes.push(index);
+            }
+            difficulty_objects.push(difficulty_object);
+        }
+        difficulty_objects
+    }
+}
@@ -0,0 +1,204 @@
+//! Colour, rhythm and stamina strain, which build up over dense sections.
+
+use super::object::TaikoDifficultyObject;
+use crate::difficulty::skill::{strain_decay, StrainPeaks};
+
+const SECTION_LENGTH: f64 = 400.0;
+
+const COLOUR_SKILL_MULTIPLIER: f64 = 0.12;
+const COLOUR_STRAIN_DECAY_BASE: f64 = 0.8;
+
+const RHYTHM_SKILL_MULTIPLIER: f64 = 10.0;
+const RHYTHM_STRAIN_DECAY_BASE: f64 = 0.0;
+const RHYTHM_STRAIN_DECAY: f64 = 0.96;
+/// How many recent rhythm changes repetitions are looked for in.
+const RHYTHM_HISTORY_LENGTH: usize = 8;
+
+const STAMINA_SKILL_MULTIPLIER: f64 = 1.1;
+const STAMINA_STRAIN_DECAY_BASE: f64 = 0.4;
+
+/// The start time of the object before `index`, for decaying strain into
+/// a new section.
+fn previous_start_time(objects: &[TaikoDifficultyObject], index: usize) -> f64 {
+    index
+        .checked_sub(1)
+        .map_or(objects[index].start_time, |previous| {
+            objects[previous].start_time
+        })
+}
+
+/// Strain from changing colour, from the patterns the colour encoding found.
+pub(super) struct Colour {
+    current_strain: f64,
+    pub(super) peaks: StrainPeaks,
+}
+
+impl Colour {
+    pub(super) fn new() -> Self {
+        Colour {
+            current_strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(super) fn process(&mut self, objects: &[TaikoDifficultyObject], index: usize) {
+        let current = &objects[index];
+        let strain = self.current_strain;
+        let last_start = previous_start_time(objects, index);
+        self.peaks.advance(current.start_time, |time| {
+            strain * strain_decay(COLOUR_STRAIN_DECAY_BASE, time - last_start)
+        });
+
+        self.current_strain *= strain_decay(COLOUR_STRAIN_DECAY_BASE, current.delta_time);
+        self.current_strain += current.colour_difficulty * COLOUR_SKILL_MULTIPLIER;
+        self.peaks.push(self.current_strain);
+    }
+}
+
+/// Strain from changes in the gaps between hits, less for rhythms that
+/// were just played or are too slow to matter.
+pub(super) struct Rhythm {
+    skill_strain: f64,
+    current_strain: f64,
+    notes_since_rhythm_change: usize,
+    /// Index and rhythm of recent objects
+    history: Vec<(usize, usize)>,
+    pub(super) peaks: StrainPeaks,
+}
+
+impl Rhythm {
+    pub(super) fn new() -> Self {
+        Rhythm {
+            skill_strain: 0.0,
+            current_strain: 0.0,
+            notes_since_rhythm_change: 0,
+            history: Vec::with_capacity(RHYTHM_HISTORY_LENGTH),
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(super) fn process(&mut self, objects: &[TaikoDifficultyObject], index: usize) {
+        let current = &objects[index];
+        let strain = self.skill_strain;
+        let last_start = previous_start_time(objects, index);
+        self.peaks.advance(current.start_time, |time| {
+            strain * strain_decay(RHYTHM_STRAIN_DECAY_BASE, time - last_start)
+        });
+
+        // Nothing carries over to the next object unless it's at the same time
+        self.skill_strain *= strain_decay(RHYTHM_STRAIN_DECAY_BASE, current.delta_time);
+        self.skill_strain += self.strain_value_of(current) * RHYTHM_SKILL_MULTIPLIER;
+        self.peaks.push(self.skill_strain);
+    }
+
+    fn strain_value_of(&mut self, current: &TaikoDifficultyObject) -> f64 {
+        if current.rim.is_none() {
+            self.reset();
+            return 0.0;
+        }
+
+        self.current_strain *= RHYTHM_STRAIN_DECAY;
+        self.notes_since_rhythm_change += 1;
+        if current.rhythm_difficulty() == 0.0 {
+            return 0.0;
+        }
+
+        let mut strain = current.rhythm_difficulty();
+        strain *= self.repetition_penalties(current);
+        strain *= pattern_length_penalty(self.notes_since_rhythm_change);
+        strain *= self.speed_penalty(current.delta_time);
+        self.notes_since_rhythm_change = 0;
+
+        self.current_strain += strain;
+        self.current_strain
+    }
+
+    /// Penalises every recent run of 2 to 4 rhythms that the latest ones
+    /// repeat, more the closer it is.
+    fn repetition_penalties(&mut self, current: &TaikoDifficultyObject) -> f64 {
+        if self.history.len() == RHYTHM_HISTORY_LENGTH {
+            self.history.remove(0);
+        }
+        self.history.push((current.index, current.rhythm));
+
+        let mut penalty = 1.0;
+        let len = self.history.len();
+        for compared in 2..=RHYTHM_HISTORY_LENGTH / 2 {
+            let Some(last_start) = len.checked_sub(compared + 1) else {
+                continue;
+            };
+            let recent = &self.history[len - compared..];
+            let repeated = (0..=last_start).rev().find(|&start| {
+                self.history[start..start + compared]
+                    .iter()
+                    .zip(recent)
+                    .all(|(a, b)| a.1 == b.1)
+            });
+            if let Some(start) = repeated {
+                let notes_since = current.index - self.history[start].0;
+                penalty *= (0.032 * notes_since as f64).min(1.0);
+            }
+        }
+        penalty
+    }
+
+    fn speed_penalty(&mut self, delta_time: f64) -> f64 {
+        if delta_time < 80.0 {
+            1.0
+        } else if delta_time < 210.0 {
+            (1.4 - 0.005 * delta_time).max(0.0)
+        } else {
+            self.reset();
+            0.0
+        }
+    }
+
+    fn reset(&mut self) {
+        self.current_strain = 0.0;
+        self.notes_since_rhythm_change = 0;
+    }
+}
+
+/// Short patterns and very long ones are easier to read.
+fn pattern_length_penalty(length: usize) -> f64 {
+    let length = length as f64;
+    let short_pattern_penalty = (0.15 * length).min(1.0);
+    let long_pattern_penalty = (2.5 - 0.15 * length).clamp(0.0, 1.0);
+    short_pattern_penalty.min(long_pattern_penalty)
+}
+
+/// Strain from hitting fast with the same finger, taken as every other hit
+/// of the same colour.
+pub(super) struct Stamina {
+    current_strain: f64,
+    pub(super) peaks: StrainPeaks,
+}
+
+impl Stamina {
+    pub(super) fn new() -> Self {
+        Stamina {
+            current_strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub(super) fn process(&mut self, objects: &[TaikoDifficultyObject], index: usize) {
+        let current = &objects[index];
+        let strain = self.current_strain;
+        let last_start = previous_start_time(objects, index);
+        self.peaks.advance(current.start_time, |time| {
+            strain * strain_decay(STAMINA_STRAIN_DECAY_BASE, time - last_start)
+        });
+
+        self.current_strain *= strain_decay(STAMINA_STRAIN_DECAY_BASE, current.delta_time);
+        self.current_strain += evaluate_stamina(current) * STAMINA_SKILL_MULTIPLIER;
+        self.peaks.push(self.current_strain);
+    }
+}
+
+fn evaluate_stamina(current: &TaikoDifficultyObject) -> f64 {
+    match current.same_key_previous_time {
+        Some(previous) => 0.5 + 30.0 / (current.start_time - previous).max(50.0),
+        None => 0.0,
+    }
+}
@@ -0,0 +1,363 @@
+/// Errors returned while reading or parsing beatmaps and storyboards.
+///
+/// Every parse variant carries the 1-based `line` number in the original
+/// file, the 0-based `field` index of the offending value on that line and
+/// the `raw` line as it appeared in the input.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying file or reader could not be read
+    Io(std::io::Error),
+    /// The `osu file format v` header is malformed
+    FileFormat {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[Section]` header is not recognised
+    Section {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[General]` entry could not be parsed
+    General {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// An `[Editor]` entry could not be parsed
+    Editor {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[Metadata]` entry could not be parsed
+    Metadata {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[Difficulty]` entry could not be parsed
+    Difficulty {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// An `[Events]` entry could not be parsed
+    Event {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[TimingPoints]` entry could not be parsed
+    TimingPoint {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[Colours]` entry could not be parsed
+    Colour {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[HitObjects]` entry could not be parsed
+    HitObject {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A storyboard object declaration (`Sprite`, `Animation`, `Sample`) could not be parsed
+    Storyboard {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A storyboard command could not be parsed
+    Command {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A `[Variables]` entry could not be parsed
+    Variable {
+        line: usize,
+        field: usize,
+        raw: String,
+    },
+    /// A [`BeatmapBuilder`](crate::BeatmapBuilder) was given values that do not
+    /// make a valid beatmap
+    Build(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub(crate) fn file_format(field: usize) -> Self {
+        Error::FileFormat {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn section(field: usize) -> Self {
+        Error::Section {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn general(field: usize) -> Self {
+        Error::General {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn editor(field: usize) -> Self {
+        Error::Editor {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn metadata(field: usize) -> Self {
+        Error::Metadata {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn difficulty(field: usize) -> Self {
+        Error::Difficulty {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn event(field: usize) -> Self {
+        Error::Event {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn timing_point(field: usize) -> Self {
+        Error::TimingPoint {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn colour(field: usize) -> Self {
+        Error::Colour {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn hit_object(field: usize) -> Self {
+        Error::HitObject {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn storyboard(field: usize) -> Self {
+        Error::Storyboard {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn command(field: usize) -> Self {
+        Error::Command {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn variable(field: usize) -> Self {
+        Error::Variable {
+            line: 0,
+            field,
+            raw: String::new(),
+        }
+    }
+
+    pub(crate) fn build<S: Into<String>>(message: S) -> Self {
+        Error::Build(message.into())
+    }
+
+    /// Attaches the position of the offending line to a parse error.
+    pub(crate) fn at(mut self, line_number: usize, raw_line: &str) -> Self {
+        match &mut self {
+            Error::Io(_) | Error::Build(_) => {}
+            Error::FileFormat { line, raw, .. }
+            | Error::Section { line, raw, .. }
+            | Error::General { line, raw, .. }
+            | Error::Editor { line, raw, .. }
+            | Error::Metadata { line, raw, .. }
+            | Error::Difficulty { line, raw, .. }
+            | Error::Event { line, raw, .. }
+            | Error::TimingPoint { line, raw, .. }
+            | Error::Colour { line, raw, .. }
+            | Error::HitObject { line, raw, .. }
+            | Error::Storyboard { line, raw, .. }
+            | Error::Command { line, raw, .. }
+            | Error::Variable { line, raw, .. } => {
+                *line = line_number;
+                *raw = raw_line.to_string();
+            }
+        }
+        self
+    }
+
+    /// Moves the field index of a parse error, used when a sub-value such as a
+    /// hit sample is parsed on its own and then embedded in a larger line.
+    pub(crate) fn offset_field(mut self, offset: usize) -> Self {
+        match &mut self {
+            Error::Io(_) | Error::Build(_) => {}
+            Error::FileFormat { field, .. }
+            | Error::Section { field, .. }
+            | Error::General { field, .. }
+            | Error::Editor { field, .. }
+            | Error::Metadata { field, .. }
+            | Error::Difficulty { field, .. }
+            | Error::Event { field, .. }
+            | Error::TimingPoint { field, .. }
+            | Error::Colour { field, .. }
+            | Error::HitObject { field, .. }
+            | Error::Storyboard { field, .. }
+            | Error::Command { field, .. }
+            | Error::Variable { field, .. } => {
+                *field += offset;
+            }
+        }
+        self
+    }
+
+    /// 1-based line number of the offending line, `None` for I/O and build errors.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Error::Io(_) | Error::Build(_) => None,
+            Error::FileFormat { line, .. }
+            | Error::Section { line, .. }
+            | Error::General { line, .. }
+            | Error::Editor { line, .. }
+            | Error::Metadata { line, .. }
+            | Error::Difficulty { line, .. }
+            | Error::Event { line, .. }
+            | Error::TimingPoint { line, .. }
+            | Error::Colour { line, .. }
+            | Error::HitObject { line, .. }
+            | Error::Storyboard { line, .. }
+            | Error::Command { line, .. }
+            | Error::Variable { line, .. } => Some(*line),
+        }
+    }
+
+    /// 0-based index of the offending field on the line, `None` for I/O and build errors.
+    pub fn field(&self) -> Option<usize> {
+        match self {
+            Error::Io(_) | Error::Build(_) => None,
+            Error::FileFormat { field, .. }
+            | Error::Section { field, .. }
+            | Error::General { field, .. }
+            | Error::Editor { field, .. }
+            | Error::Metadata { field, .. }
+            | Error::Difficulty { field, .. }
+            | Error::Event { field, .. }
+            | Error::TimingPoint { field, .. }
+            | Error::Colour { field, .. }
+            | Error::HitObject { field, .. }
+            | Error::Storyboard { field, .. }
+            | Error::Command { field, .. }
+            | Error::Variable { field, .. } => Some(*field),
+        }
+    }
+
+    /// The offending line exactly as it appeared in the input, `None` for I/O and build errors.
+    pub fn raw(&self) -> Option<&str> {
+        match self {
+            Error::Io(_) | Error::Build(_) => None,
+            Error::FileFormat { raw, .. }
+            | Error::Section { raw, .. }
+            | Error::General { raw, .. }
+            | Error::Editor { raw, .. }
+            | Error::Metadata { raw, .. }
+            | Error::Difficulty { raw, .. }
+            | Error::Event { raw, .. }
+            | Error::TimingPoint { raw, .. }
+            | Error::Colour { raw, .. }
+            | Error::HitObject { raw, .. }
+            | Error::Storyboard { raw, .. }
+            | Error::Command { raw, .. }
+            | Error::Variable { raw, .. } => Some(raw),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "I/O error",
+            Error::FileFormat { .. } => "Invalid file format header",
+            Error::Section { .. } => "Invalid section",
+            Error::General { .. } => "Invalid General entry",
+            Error::Editor { .. } => "Invalid Editor entry",
+            Error::Metadata { .. } => "Invalid Metadata entry",
+            Error::Difficulty { .. } => "Invalid Difficulty entry",
+            Error::Event { .. } => "Invalid Event",
+            Error::TimingPoint { .. } => "Invalid TimingPoint",
+            Error::Colour { .. } => "Invalid Colour",
+            Error::HitObject { .. } => "Invalid HitObject",
+            Error::Storyboard { .. } => "Invalid Storyboard object",
+            Error::Command { .. } => "Invalid Command",
+            Error::Variable { .. } => "Invalid Variable",
+            Error::Build(_) => "Invalid beatmap",
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{}: {}", self.description(), error),
+            Error::Build(message) => write!(f, "{}: {}", self.description(), message),
+            _ => write!(
+                f,
+                "{} at line {}, field {}: {}",
+                self.description(),
+                self.line().unwrap_or_default(),
+                self.field().unwrap_or_default(),
+                self.raw().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
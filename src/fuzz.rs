@@ -0,0 +1,159 @@
+//! Deterministic mutation fuzzing of the parsers. Every mutated input must
+//! either parse or return an error, never panic, and whatever parses must be
+//! written back unchanged.
+
+use crate::{Beatmap, HitObject, Storyboard};
+
+/// Set `OSU_PARSER_FUZZ_ITERATIONS` to run a longer session locally.
+fn iterations() -> usize {
+    std::env::var("OSU_PARSER_FUZZ_ITERATIONS")
+        .ok()
+        .and_then(|iterations| iterations.parse().ok())
+        .unwrap_or(300)
+}
+
+const STORYBOARD: &str = "[Variables]
+$fg=Foreground,Centre
+$half=0.5
+[Events]
+Sprite,Background,Centre,\"sb/bg.jpg\",320,240
+ F,0,0,1000,0,1
+ M,17,1000,2000,320,240,260,350
+ MX,0,1000,,320
+ MY,0,1000,2000,240,200
+ S,0,0,,$half
+ V,0,0,1000,1,1,2,2
+ R,0,0,1000,0,3.14
+ C,0,0,,255,74,74
+ P,0,0,1000,A
+ L,1000,4
+  F,0,0,500,0,1
+  M,0,0,500,320,240,330,250
+ T,HitSoundClap,0,5000
+__S,0,0,100,1,1.2
+Sprite,$fg,\"sb/fg.png\",320,240
+ F,0,0,1000,$half,1
+Animation,Foreground,TopLeft,\"sb/anim.png\",0,0,4,100,LoopOnce
+ F,0,0,,1
+Sample,1000,0,\"sb/hit.wav\",80
+";
+
+/// Small xorshift generator so failures are reproducible from the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+fn mutate(input: &str, rng: &mut Rng) -> String {
+    const TOKENS: [&str; 12] = [
+        ",",
+        ":",
+        "|",
+        "\"",
+        "-",
+        "",
+        " ",
+        "_",
+        "9999999999",
+        "NaN",
+        "[Events]",
+        "\u{feff}",
+    ];
+
+    let mut lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+
+    for _ in 0..1 + rng.below(4) {
+        let index = rng.below(lines.len());
+        let line = &mut lines[index];
+        let chars: Vec<char> = line.chars().collect();
+        let at = rng.below(chars.len() + 1);
+
+        match rng.below(6) {
+            0 => {
+                line.clear();
+                line.extend(&chars[..at]);
+            }
+            1 => {
+                let removed: String = chars
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != at)
+                    .map(|(_, c)| c)
+                    .collect();
+                *line = removed;
+            }
+            2 => {
+                let token = TOKENS[rng.below(TOKENS.len())];
+                let mut inserted: String = chars[..at].iter().collect();
+                inserted.push_str(token);
+                inserted.extend(&chars[at..]);
+                *line = inserted;
+            }
+            3 => {
+                let fields: Vec<&str> = line.split(',').collect();
+                let kept = rng.below(fields.len() + 1);
+                *line = fields[..kept].join(",");
+            }
+            4 => {
+                let copy = lines[rng.below(lines.len())].clone();
+                lines.insert(index, copy);
+            }
+            _ => {
+                lines.remove(index);
+                if lines.is_empty() {
+                    lines.push(String::new());
+                }
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[test]
+fn test_fuzz_beatmap() {
+    let osu_data = std::fs::read_to_string("beatmap.osu").unwrap();
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..iterations() {
+        let mutated = mutate(&osu_data, &mut rng);
+        if let Ok(beatmap) = mutated.parse::<Beatmap>() {
+            assert_eq!(beatmap.to_string(), mutated);
+
+            for hit_object in beatmap.iter_hit_objects() {
+                if let HitObject::Slider(slider) = hit_object {
+                    slider.get_path().end_position();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fuzz_storyboard() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..iterations() * 10 {
+        let mutated = mutate(STORYBOARD, &mut rng);
+        if let Ok(mut storyboard) = mutated.parse::<Storyboard>() {
+            assert_eq!(storyboard.to_string(), mutated);
+
+            storyboard.set_extract_variables(true);
+            let extracted: Storyboard = storyboard.to_string().parse().unwrap();
+            assert_eq!(
+                extracted.to_expanded_string(),
+                storyboard.to_expanded_string()
+            );
+        }
+    }
+}
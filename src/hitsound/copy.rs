@@ -0,0 +1,331 @@
+//! Copies hit sounds from one difficulty to another by time, so a mapping
+//! team can keep them in a single hitsound difficulty.
+
+use super::SoundPoint;
+use crate::{
+    section::{EdgeSet, HitObject, HitSample, HitSound, SampleBank, TimingIndex},
+    Beatmap,
+};
+
+/// The volume unmatched objects get when muted. A volume of 0 means the
+/// timing point's, so this is the quietest osu! plays.
+const MUTED_VOLUME: i32 = 5;
+
+/// How [`Beatmap::copy_hitsounds_from`] copies hit sounds.
+///
+/// Matches objects within 5ms, copies slider bodies and leaves unmatched
+/// objects alone by default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitSoundCopyOptions {
+    tolerance: f64,
+    copy_slider_bodies: bool,
+    mute_unmatched: bool,
+}
+
+impl Default for HitSoundCopyOptions {
+    fn default() -> Self {
+        HitSoundCopyOptions {
+            tolerance: 5.0,
+            copy_slider_bodies: true,
+            mute_unmatched: false,
+        }
+    }
+}
+
+impl HitSoundCopyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How far apart in milliseconds two sounds can be and still match.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Whether sliders starting together also get the source slider's
+    /// slide and whistle sounds.
+    pub fn copy_slider_bodies(mut self, copy_slider_bodies: bool) -> Self {
+        self.copy_slider_bodies = copy_slider_bodies;
+        self
+    }
+
+    /// Whether objects with no sound in the source are turned all the way
+    /// down.
+    pub fn mute_unmatched(mut self, mute_unmatched: bool) -> Self {
+        self.mute_unmatched = mute_unmatched;
+        self
+    }
+}
+
+/// What a sound point of the target had before copying, to keep for the
+/// edges nothing matched.
+struct TargetPoint {
+    time: f64,
+    hit_sound: HitSound,
+    normal_set: SampleBank,
+    addition_set: SampleBank,
+}
+
+impl Beatmap {
+    /// Copies the hit sounds and samples of `source` onto the objects of
+    /// this beatmap that play a sound at the same time, within the
+    /// tolerance of `options`. Every slider edge and spinner end is matched
+    /// on its own. The sample sets, indices and volumes of the timing points
+    /// are copied too, adding green lines where `source` changes them.
+    ///
+    /// Returns how many objects matched a sound of `source`.
+    pub fn copy_hitsounds_from(&mut self, source: &Beatmap, options: HitSoundCopyOptions) -> usize {
+        let mut source_points: Vec<SoundPoint> = source
+            .hit_objects
+            .iter()
+            .flat_map(|hit_object| source.get_sound_points(hit_object))
+            .collect();
+        source_points.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        let target_points: Vec<Vec<TargetPoint>> = self
+            .hit_objects
+            .iter()
+            .map(|hit_object| {
+                self.get_sound_points(hit_object)
+                    .into_iter()
+                    .map(|point| TargetPoint {
+                        time: point.time,
+                        hit_sound: point.hit_sound,
+                        normal_set: point.normal_set,
+                        addition_set: point.addition_set,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut matched = 0;
+        for (hit_object, points) in self.hit_objects.iter_mut().zip(target_points) {
+            let matches: Vec<Option<&SoundPoint>> = points
+                .iter()
+                .map(|point| nearest_point(&source_points, point.time, options.tolerance))
+                .collect();
+
+            if matches.iter().all(Option::is_none) {
+                if options.mute_unmatched {
+                    let mut hit_sample = hit_object.get_hit_sample().cloned().unwrap_or_default();
+                    hit_sample.change_volume(MUTED_VOLUME);
+                    hit_object.change_hit_sample(hit_sample);
+                }
+                continue;
+            }
+            matched += 1;
+
+            let HitObject::Slider(slider) = hit_object else {
+                if let Some(point) = matches[0] {
+                    hit_object.change_hit_sound(point.hit_sound);
+                    hit_object.change_hit_sample(point_sample(point));
+                }
+                continue;
+            };
+
+            let body = source
+                .hit_objects
+                .iter()
+                .filter(|_| options.copy_slider_bodies)
+                .find_map(|source_object| match source_object {
+                    HitObject::Slider(source_slider)
+                        if (source_slider.get_time() - slider.get_time()).abs() as f64
+                            <= options.tolerance =>
+                    {
+                        Some(source_slider)
+                    }
+                    _ => None,
+                });
+
+            let mut edge_sounds = Vec::new();
+            let mut edge_sets = Vec::new();
+            for (point, source_point) in points.iter().zip(&matches) {
+                let (hit_sound, normal_set, addition_set) = match source_point {
+                    Some(source_point) => (
+                        source_point.hit_sound,
+                        source_point.normal_set,
+                        source_point.addition_set,
+                    ),
+                    None => (point.hit_sound, point.normal_set, point.addition_set),
+                };
+                edge_sounds.push(hit_sound);
+                edge_sets.push(EdgeSet::new(normal_set, addition_set));
+            }
+            *slider.get_edge_sounds_mut() = edge_sounds;
+            *slider.get_edge_sets_mut() = edge_sets;
+
+            // Index, volume and file apply to the whole slider, so take them
+            // from its head
+            let mut hit_sample = slider.get_hit_sample().cloned().unwrap_or_default();
+            if let Some(head) = matches[0] {
+                let head_sample = point_sample(head);
+                hit_sample.change_index(head_sample.get_index());
+                hit_sample.change_volume(head_sample.get_volume());
+                match head_sample.get_filename() {
+                    Some(filename) => hit_sample.change_filename(&filename.to_string_lossy()),
+                    None => hit_sample.remove_filename(),
+                }
+            }
+            if let Some(body) = body {
+                let body_sample = body.get_hit_sample().cloned().unwrap_or_default();
+                hit_sample.change_normal_set(body_sample.get_normal_set());
+                hit_sample.change_addition_set(body_sample.get_addition_set());
+                slider.change_hit_sound(body.get_hit_sound());
+            }
+            slider.change_hit_sample(hit_sample);
+        }
+
+        self.copy_sample_points_from(source);
+        matched
+    }
+
+    /// Gives every timing point the samples `source` has at its time, and
+    /// adds green lines where `source` changes them and this beatmap
+    /// doesn't.
+    fn copy_sample_points_from(&mut self, source: &Beatmap) {
+        let samples_at = |beatmap: &Beatmap, time: f64| {
+            (
+                beatmap.get_sample_set_at(time),
+                beatmap.get_sample_index_at(time),
+                beatmap.get_volume_at(time),
+            )
+        };
+
+        for index in 0..self.timing_points.len() {
+            let time = self.timing_points[index].get_time() as f64;
+            let (sample_set, sample_index, volume) = samples_at(source, time);
+            self.timing_points[index].change_samples(sample_set, sample_index, volume);
+        }
+
+        let mut added = Vec::new();
+        for source_point in &source.timing_points {
+            let time = source_point.get_time();
+            let samples = samples_at(source, time as f64);
+            if samples_at(self, time as f64) == samples {
+                continue;
+            }
+            if let Some(point) = self.sample_point_at(time as f64) {
+                let mut point = point.inherited_at(time);
+                point.change_samples(samples.0, samples.1, samples.2);
+                added.push(point);
+            }
+        }
+
+        // Green lines go after red lines at the same time
+        self.timing_points.extend(added);
+        self.timing_points.sort_by_key(|point| point.get_time());
+        self.timing_index = TimingIndex::new(&self.timing_points);
+    }
+}
+
+/// The source point closest to `time`, if one is within `tolerance`.
+fn nearest_point<'a, 'b>(
+    points: &'b [SoundPoint<'a>],
+    time: f64,
+    tolerance: f64,
+) -> Option<&'b SoundPoint<'a>> {
+    let after = points.partition_point(|point| point.time < time);
+    let before = after.checked_sub(1).map(|index| &points[index]);
+    [before, points.get(after)]
+        .into_iter()
+        .flatten()
+        .filter(|point| (point.time - time).abs() <= tolerance)
+        .min_by(|a, b| (a.time - time).abs().total_cmp(&(b.time - time).abs()))
+}
+
+/// The sample of a source point, with its edge's sets.
+fn point_sample(point: &SoundPoint) -> HitSample {
+    let (index, volume) = point
+        .sample
+        .map_or((0, 0), |sample| (sample.get_index(), sample.get_volume()));
+    let sample = HitSample::new(point.normal_set, point.addition_set, index, volume);
+    match point.sample.and_then(HitSample::get_filename) {
+        Some(filename) => sample.with_filename(&filename.to_string_lossy()),
+        None => sample,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Beatmap, HitObject, HitSound, HitSoundCopyOptions, SampleBank};
+
+    const SOURCE: &[u8] = b"osu file format v14
+
+[Difficulty]
+SliderMultiplier:1
+
+[TimingPoints]
+0,500,4,2,0,60,1,0
+1000,-100,4,3,1,80,0,0
+
+[HitObjects]
+256,192,0,1,8,0:0:0:0:
+256,192,500,1,2,1:2:0:0:
+256,192,1000,1,4,0:0:0:0:
+256,192,1500,1,8,0:0:0:0:
+";
+
+    const TARGET: &[u8] = b"osu file format v14
+
+[Difficulty]
+SliderMultiplier:1
+
+[TimingPoints]
+0,500,4,1,0,100,1,0
+
+[HitObjects]
+64,64,2,1,0,0:0:0:0:
+64,64,500,2,0,L|164:64,2,50,0|0|0,0:0|0:0|0:0,0:0:0:0:
+64,64,1000,1,0,0:0:0:0:
+64,64,3000,1,0,0:0:0:0:
+";
+
+    #[test]
+    fn test_copy_hitsounds() {
+        let source = Beatmap::from_bytes(SOURCE).unwrap();
+        let mut target = Beatmap::from_bytes(TARGET).unwrap();
+        let options = HitSoundCopyOptions::new().mute_unmatched(true);
+        assert_eq!(target.copy_hitsounds_from(&source, options), 3);
+
+        let hit_objects = target.get_hit_objects();
+        assert_eq!(hit_objects[0].get_hit_sound(), HitSound::CLAP);
+        match &hit_objects[1] {
+            HitObject::Slider(slider) => {
+                // Each edge gets the sound played at its time
+                assert_eq!(
+                    slider.get_edge_sounds(),
+                    &vec![HitSound::WHISTLE, HitSound::NONE, HitSound::FINISH]
+                );
+                assert_eq!(
+                    slider.get_edge_sets()[0].get_normal_set(),
+                    SampleBank::Normal
+                );
+                assert_eq!(
+                    slider.get_edge_sets()[0].get_addition_set(),
+                    SampleBank::Soft
+                );
+            }
+            _ => panic!("expected a slider"),
+        }
+        assert_eq!(hit_objects[2].get_hit_sound(), HitSound::FINISH);
+        assert_eq!(hit_objects[3].get_hit_sample().unwrap().get_volume(), 5);
+    }
+
+    #[test]
+    fn test_copy_sample_points() {
+        let source = Beatmap::from_bytes(SOURCE).unwrap();
+        let mut target = Beatmap::from_bytes(TARGET).unwrap();
+        target.copy_hitsounds_from(&source, HitSoundCopyOptions::new());
+
+        assert_eq!(target.get_timing_points().len(), 2);
+        assert_eq!(target.get_sample_set_at(0.0), SampleBank::Soft);
+        assert_eq!(target.get_volume_at(0.0), 60);
+        assert_eq!(target.get_sample_set_at(1000.0), SampleBank::Drum);
+        assert_eq!(target.get_sample_index_at(1000.0), 1);
+        assert_eq!(target.get_volume_at(1000.0), 80);
+        // The added green line keeps the target's timing
+        assert_eq!(target.get_beat_length_at(1000.0), 500.0);
+        assert_eq!(target.get_slider_velocity_at(1000.0), 1.0);
+    }
+}
@@ -0,0 +1,319 @@
+//! Works out which sample files a beatmap plays and when, combining the hit
+//! sounds and samples of its objects with the timing points, like osu! does.
+
+mod copy;
+
+pub use copy::HitSoundCopyOptions;
+
+use crate::{
+    section::{EdgeSet, HitObject, HitSample, HitSound, SampleBank, SampleSet},
+    Beatmap,
+};
+
+/// Samples use the timing point this many milliseconds after them, so ones
+/// placed a little early still pick up a change.
+const CONTROL_POINT_LENIENCY: f64 = 5.0;
+
+/// A sample file played at a time, see [`Beatmap::get_hitsound_events`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitSoundEvent {
+    time: f64,
+    filename: String,
+    volume: i32,
+}
+
+impl HitSoundEvent {
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    /// The file name osu! looks for, first in the beatmap's folder and then
+    /// in the skin.
+    pub fn get_filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Volume from 0 to 100.
+    pub fn get_volume(&self) -> i32 {
+        self.volume
+    }
+}
+
+/// A point of a hit object that plays hit sounds: its head, a slider edge
+/// or a spinner's end.
+pub(crate) struct SoundPoint<'a> {
+    pub(crate) time: f64,
+    pub(crate) hit_sound: HitSound,
+    /// The edge's own set, or otherwise the object's
+    pub(crate) normal_set: SampleBank,
+    pub(crate) addition_set: SampleBank,
+    pub(crate) sample: Option<&'a HitSample>,
+}
+
+impl Beatmap {
+    /// Every sample file the beatmap plays in time order: hit sounds of
+    /// circles, slider edges, spinner ends and hold heads, and the slide and
+    /// tick sounds of sliders.
+    pub fn get_hitsound_events(&self) -> Vec<HitSoundEvent> {
+        let mut events = Vec::new();
+
+        for hit_object in &self.hit_objects {
+            for point in self.get_sound_points(hit_object) {
+                self.push_hit_sounds(&mut events, &point);
+            }
+
+            let HitObject::Slider(slider) = hit_object else {
+                continue;
+            };
+            let sample = slider.get_hit_sample();
+            // Custom files replace the slider's own sounds too
+            if custom_filename(sample).is_some() {
+                continue;
+            }
+
+            let start_time = slider.get_time() as f64;
+            let hit_sound = slider.get_hit_sound();
+            let body = SoundPoint {
+                time: start_time,
+                hit_sound,
+                normal_set: sample.map(HitSample::get_normal_set).unwrap_or_default(),
+                addition_set: sample.map(HitSample::get_addition_set).unwrap_or_default(),
+                sample,
+            };
+
+            let (normal_set, addition_set) = self.resolve_banks(&body);
+            let mut body_sounds = vec![("sliderslide", normal_set)];
+            if hit_sound.contains(HitSound::WHISTLE) {
+                body_sounds.push(("sliderwhistle", addition_set));
+            }
+            for (sound, bank) in body_sounds {
+                events.push(self.sample_event(&body, start_time, bank, sound));
+            }
+            for tick in self.get_slider_timing(slider).get_ticks() {
+                events.push(self.sample_event(
+                    &body,
+                    tick.get_time(),
+                    body.normal_set,
+                    "slidertick",
+                ));
+            }
+        }
+
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        events
+    }
+
+    /// The points of `hit_object` that play hit sounds, with every edge of
+    /// a slider in order.
+    pub(crate) fn get_sound_points<'a>(&self, hit_object: &'a HitObject) -> Vec<SoundPoint<'a>> {
+        let sample = hit_object.get_hit_sample();
+        let point = |time: f64, hit_sound: HitSound, edge_set: Option<&EdgeSet>| SoundPoint {
+            time,
+            hit_sound,
+            normal_set: edge_set
+                .map(EdgeSet::get_normal_set)
+                .filter(|&bank| bank != SampleBank::Auto)
+                .or(sample.map(HitSample::get_normal_set))
+                .unwrap_or_default(),
+            addition_set: edge_set
+                .map(EdgeSet::get_addition_set)
+                .filter(|&bank| bank != SampleBank::Auto)
+                .or(sample.map(HitSample::get_addition_set))
+                .unwrap_or_default(),
+            sample,
+        };
+        let hit_sound = hit_object.get_hit_sound();
+
+        match hit_object {
+            HitObject::Slider(slider) => {
+                let span_duration = self.get_slider_timing(slider).get_span_duration();
+                let start_time = slider.get_time() as f64;
+                (0..=slider.get_span_count())
+                    .map(|edge| {
+                        let edge_sound = slider
+                            .get_edge_sounds()
+                            .get(edge)
+                            .copied()
+                            .unwrap_or(hit_sound);
+                        let time = start_time + edge as f64 * span_duration;
+                        point(time, edge_sound, slider.get_edge_sets().get(edge))
+                    })
+                    .collect()
+            }
+            HitObject::Spinner(spinner) => {
+                vec![point(spinner.get_end_time() as f64, hit_sound, None)]
+            }
+            HitObject::Circle(_) | HitObject::Hold(_) => {
+                vec![point(hit_object.get_time() as f64, hit_sound, None)]
+            }
+        }
+    }
+
+    /// Adds the hit normal and every addition of `point`, or only its custom
+    /// file when it has one.
+    fn push_hit_sounds(&self, events: &mut Vec<HitSoundEvent>, point: &SoundPoint) {
+        if let Some(filename) = custom_filename(point.sample) {
+            events.push(HitSoundEvent {
+                time: point.time,
+                filename,
+                volume: self.get_sample_volume(point, point.time),
+            });
+            return;
+        }
+
+        let (normal_set, addition_set) = self.resolve_banks(point);
+        events.push(self.sample_event(point, point.time, normal_set, "hitnormal"));
+
+        let additions = [
+            (HitSound::WHISTLE, "hitwhistle"),
+            (HitSound::FINISH, "hitfinish"),
+            (HitSound::CLAP, "hitclap"),
+        ];
+        for (sound, name) in additions {
+            if point.hit_sound.contains(sound) {
+                events.push(self.sample_event(point, point.time, addition_set, name));
+            }
+        }
+    }
+
+    /// The event for `sound` of `bank`, with the index and volume of the
+    /// sample or otherwise the timing point at `time`.
+    fn sample_event(
+        &self,
+        point: &SoundPoint,
+        time: f64,
+        bank: SampleBank,
+        sound: &str,
+    ) -> HitSoundEvent {
+        let bank = match self.resolve_bank(bank, time) {
+            SampleBank::Soft => "soft",
+            SampleBank::Drum => "drum",
+            _ => "normal",
+        };
+        let index = point
+            .sample
+            .map(HitSample::get_index)
+            .filter(|&index| index != 0)
+            .unwrap_or_else(|| self.get_sample_index_at(time + CONTROL_POINT_LENIENCY));
+        // Index 1 is the beatmap's own file without a number
+        let suffix = if index >= 2 {
+            index.to_string()
+        } else {
+            String::new()
+        };
+
+        HitSoundEvent {
+            time,
+            filename: format!("{}-{}{}.wav", bank, sound, suffix),
+            volume: self.get_sample_volume(point, time),
+        }
+    }
+
+    /// The sample's volume, or the timing point's at `time` when it has none.
+    fn get_sample_volume(&self, point: &SoundPoint, time: f64) -> i32 {
+        point
+            .sample
+            .map(HitSample::get_volume)
+            .filter(|&volume| volume != 0)
+            .unwrap_or_else(|| self.get_volume_at(time + CONTROL_POINT_LENIENCY))
+    }
+
+    /// The normal and addition banks of `point`. Additions use the normal
+    /// bank unless they have their own.
+    fn resolve_banks(&self, point: &SoundPoint) -> (SampleBank, SampleBank) {
+        let normal_set = self.resolve_bank(point.normal_set, point.time);
+        let addition_set = match point.addition_set {
+            SampleBank::Auto => normal_set,
+            bank => bank,
+        };
+        (normal_set, addition_set)
+    }
+
+    /// Fills in [`SampleBank::Auto`] from the timing point at `time`, and
+    /// then from the beatmap's default sample set.
+    fn resolve_bank(&self, bank: SampleBank, time: f64) -> SampleBank {
+        if bank != SampleBank::Auto {
+            return bank;
+        }
+
+        match self.get_sample_set_at(time + CONTROL_POINT_LENIENCY) {
+            SampleBank::Auto => match self.get_general_sample_set().unwrap_or_default() {
+                SampleSet::Normal => SampleBank::Normal,
+                SampleSet::Soft => SampleBank::Soft,
+                SampleSet::Drum => SampleBank::Drum,
+            },
+            bank => bank,
+        }
+    }
+}
+
+/// The file a sample plays instead of its sample set's sounds, if any.
+fn custom_filename(sample: Option<&HitSample>) -> Option<String> {
+    sample
+        .and_then(HitSample::get_filename)
+        .map(|filename| filename.to_string_lossy().into_owned())
+        .filter(|filename| !filename.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Beatmap;
+
+    const HITSOUNDS: &[u8] = b"osu file format v14
+
+[General]
+SampleSet: Soft
+
+[Difficulty]
+SliderMultiplier:1
+SliderTickRate:1
+
+[TimingPoints]
+0,500,4,0,0,60,1,0
+2000,-100,4,3,2,80,0,0
+3000,-100,4,2,0,60,0,0
+
+[HitObjects]
+64,64,0,1,8,0:0:0:0:
+64,64,500,1,2,1:2:0:0:
+64,64,1000,2,2,L|264:64,2,100,2|0|8,0:0|0:0|0:2,0:0:0:0:
+64,64,2000,1,4,0:0:3:0:
+64,64,2500,1,0,0:0:0:40:kick.wav
+64,64,3000,2,2,L|164:64,1,100,0|0,0:0|0:0,1:0:0:0:
+";
+
+    #[test]
+    fn test_hitsound_events() {
+        let beatmap = Beatmap::from_bytes(HITSOUNDS).unwrap();
+        let events = beatmap.get_hitsound_events();
+        let events: Vec<(f64, &str, i32)> = events
+            .iter()
+            .map(|event| (event.get_time(), event.get_filename(), event.get_volume()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (0.0, "soft-hitnormal.wav", 60),
+                (0.0, "soft-hitclap.wav", 60),
+                (500.0, "normal-hitnormal.wav", 60),
+                (500.0, "soft-hitwhistle.wav", 60),
+                (1000.0, "soft-hitnormal.wav", 60),
+                (1000.0, "soft-hitwhistle.wav", 60),
+                (1000.0, "soft-sliderslide.wav", 60),
+                (1000.0, "soft-sliderwhistle.wav", 60),
+                (1500.0, "soft-hitnormal.wav", 60),
+                // The timing point at the slider's end changes its bank
+                (2000.0, "drum-hitnormal2.wav", 80),
+                (2000.0, "soft-hitclap2.wav", 80),
+                (2000.0, "drum-hitnormal3.wav", 80),
+                (2000.0, "drum-hitfinish3.wav", 80),
+                (2500.0, "kick.wav", 40),
+                // The whistle follows the slider's own bank, not the section's
+                (3000.0, "normal-hitnormal.wav", 60),
+                (3000.0, "normal-sliderslide.wav", 60),
+                (3000.0, "normal-sliderwhistle.wav", 60),
+                (3500.0, "normal-hitnormal.wav", 60),
+            ]
+        );
+    }
+}
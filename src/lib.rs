@@ -1,11 +1,49 @@
 mod beatmap;
+mod builder;
+mod combo;
+mod convert;
+mod difficulty;
 mod error;
+#[cfg(test)]
+mod fuzz;
+mod hitsound;
+mod mods;
 mod parse;
+mod performance;
+mod rate;
 mod sanitize;
 mod section;
+mod slider_path;
+mod slider_timing;
+mod source;
+mod stacking;
 mod storyboard;
 mod token;
 mod utils;
+mod validate;
 
 pub use beatmap::Beatmap;
+pub use builder::BeatmapBuilder;
+pub use combo::ComboInfo;
+pub use convert::{CatchKind, CatchObject, ManiaObject, TaikoKind, TaikoObject};
+pub use difficulty::{
+    CatchDifficultyAttributes, DifficultyAttributes, ManiaDifficultyAttributes,
+    OsuDifficultyAttributes, TaikoDifficultyAttributes,
+};
+pub use error::{Error, Result};
+pub use hitsound::{HitSoundCopyOptions, HitSoundEvent};
+pub use mods::{Mod, ModdedBeatmap};
+pub use performance::{PerformanceAttributes, Score};
+pub use section::{
+    CircleBuilder, CircleHitObject, Countdown, CurvePoint, CurveType, DifficultyBuilder, Easing,
+    EdgeSet, GameMode, GeneralBuilder, HitObject, HitObjectBuilder, HitSample, HitSound,
+    HitWindows, HoldBuilder, HoldHitObject, MetadataBuilder, ObjectType, OverlayPosition,
+    SampleBank, SampleSet, SliderBuilder, SliderHitObject, SpinnerBuilder, SpinnerHitObject,
+    TimingPoint, TimingPointBuilder,
+};
+pub use slider_path::SliderPath;
+pub use slider_timing::{SliderTick, SliderTiming, LEGACY_LAST_TICK_OFFSET};
+pub use stacking::StackedObject;
 pub use storyboard::Storyboard;
+pub use utils::{Color, Vec2};
+pub use validate::{Diagnostic, Rule, Severity};
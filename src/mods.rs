@@ -0,0 +1,272 @@
+//! Mods that change a beatmap's difficulty settings, playfield or speed.
+
+use crate::{
+    builder::ensure,
+    error::Result,
+    section::{GameMode, HitObject, HitWindows},
+    Beatmap,
+};
+
+/// Height of the osu! playfield, which Hard Rock flips objects across.
+const PLAYFIELD_HEIGHT: i32 = 384;
+
+/// A mod that changes the beatmap being played.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mod {
+    Easy,
+    NoFail,
+    HardRock,
+    DoubleTime,
+    Nightcore,
+    HalfTime,
+    Daycore,
+    Hidden,
+    Flashlight,
+    SpunOut,
+    /// Lazer's Difficulty Adjust, setting every value that is `Some`
+    DifficultyAdjust {
+        circle_size: Option<f32>,
+        approach_rate: Option<f32>,
+        overall_difficulty: Option<f32>,
+        hp_drain_rate: Option<f32>,
+    },
+    /// Lazer's rate adjust, playing the song at any speed
+    RateAdjust(f64),
+}
+
+impl Mod {
+    /// How fast the song plays, 1 for mods that don't change the speed.
+    pub fn get_clock_rate(&self) -> f64 {
+        match self {
+            Mod::DoubleTime | Mod::Nightcore => 1.5,
+            Mod::HalfTime | Mod::Daycore => 0.75,
+            Mod::RateAdjust(rate) => *rate,
+            _ => 1.0,
+        }
+    }
+}
+
+/// A beatmap with mods applied, see [`Beatmap::with_mods`]. Times in the
+/// beatmap are left as written, the clock rate says how fast they pass.
+#[derive(Debug, Clone)]
+pub struct ModdedBeatmap {
+    beatmap: Beatmap,
+    mods: Vec<Mod>,
+    clock_rate: f64,
+}
+
+impl ModdedBeatmap {
+    /// The beatmap with its difficulty settings and positions adjusted.
+    pub fn get_beatmap(&self) -> &Beatmap {
+        &self.beatmap
+    }
+
+    pub fn into_beatmap(self) -> Beatmap {
+        self.beatmap
+    }
+
+    pub fn get_mods(&self) -> &[Mod] {
+        &self.mods
+    }
+
+    pub fn has_mod(&self, applied: Mod) -> bool {
+        self.mods.contains(&applied)
+    }
+
+    pub fn get_clock_rate(&self) -> f64 {
+        self.clock_rate
+    }
+
+    /// How long before its time an object appears, in real milliseconds.
+    pub fn get_preempt(&self) -> f64 {
+        self.beatmap.get_preempt() / self.clock_rate
+    }
+
+    /// The approach rate that gives the same preempt without a rate change.
+    pub fn get_approach_rate(&self) -> f64 {
+        let preempt = self.get_preempt();
+        if preempt > 1200.0 {
+            (1800.0 - preempt) / 120.0
+        } else {
+            5.0 + (1200.0 - preempt) / 150.0
+        }
+    }
+
+    /// Hit windows in real milliseconds. Mania windows don't change with
+    /// the rate, like in the game.
+    pub fn get_hit_windows(&self) -> Option<HitWindows> {
+        let mut windows = self.beatmap.get_hit_windows()?;
+        if self.get_mode() != GameMode::Mania {
+            let scale = |window: f64| window / self.clock_rate;
+            windows = HitWindows {
+                perfect: windows.perfect.map(scale),
+                great: scale(windows.great),
+                good: windows.good.map(scale),
+                ok: scale(windows.ok),
+                meh: windows.meh.map(scale),
+                miss: scale(windows.miss),
+            };
+        }
+        Some(windows)
+    }
+
+    /// The overall difficulty that gives the same 300 window without a rate
+    /// change. Modes whose windows don't change with the rate keep theirs.
+    pub fn get_overall_difficulty(&self) -> f64 {
+        let great = self.get_hit_windows().map(|windows| windows.great);
+        match (self.get_mode(), great) {
+            (GameMode::Osu, Some(great)) => (80.0 - great) / 6.0,
+            (GameMode::Taiko, Some(great)) => (50.0 - great) / 3.0,
+            _ => self.beatmap.get_overall_difficulty().map_or(5.0, f64::from),
+        }
+    }
+
+    fn get_mode(&self) -> GameMode {
+        self.beatmap.get_general_mode().unwrap_or_default()
+    }
+}
+
+impl Beatmap {
+    /// Applies `mods` in order to a copy of the beatmap. Hard Rock also
+    /// flips osu!standard objects upside down. Fails if the mods don't give
+    /// a positive, finite clock rate.
+    pub fn with_mods(&self, mods: &[Mod]) -> Result<ModdedBeatmap> {
+        let mut beatmap = self.clone();
+        let mut clock_rate = 1.0;
+
+        for applied in mods {
+            let difficulty = &mut beatmap.difficulty;
+            match *applied {
+                Mod::Easy => {
+                    difficulty.scale(0.5, 0.5);
+                }
+                Mod::HardRock => {
+                    difficulty.scale(1.3, 1.4);
+                    if beatmap.get_general_mode().unwrap_or_default() == GameMode::Osu {
+                        beatmap.flip_vertically();
+                    }
+                }
+                Mod::DifficultyAdjust {
+                    circle_size,
+                    approach_rate,
+                    overall_difficulty,
+                    hp_drain_rate,
+                } => {
+                    if let Some(circle_size) = circle_size {
+                        difficulty.change_circle_size(circle_size);
+                    }
+                    if let Some(approach_rate) = approach_rate {
+                        difficulty.change_approach_rate(approach_rate);
+                    }
+                    if let Some(overall_difficulty) = overall_difficulty {
+                        difficulty.change_overall_difficulty(overall_difficulty);
+                    }
+                    if let Some(hp_drain_rate) = hp_drain_rate {
+                        difficulty.change_hp_drain_rate(hp_drain_rate);
+                    }
+                }
+                _ => clock_rate *= applied.get_clock_rate(),
+            }
+        }
+        ensure(
+            clock_rate.is_finite() && clock_rate > 0.0,
+            format!("clock rate must be positive, not {}", clock_rate),
+        )?;
+
+        Ok(ModdedBeatmap {
+            beatmap,
+            mods: mods.to_vec(),
+            clock_rate,
+        })
+    }
+
+    fn flip_vertically(&mut self) {
+        for hit_object in self.hit_objects.iter_mut() {
+            hit_object.change_y(PLAYFIELD_HEIGHT - hit_object.get_y());
+            if let HitObject::Slider(slider) = hit_object {
+                for point in slider.get_curve_points_mut() {
+                    point.change_y(PLAYFIELD_HEIGHT - point.get_y());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEATMAP: &[u8] = b"osu file format v14
+
+[General]
+Mode: 0
+
+[Difficulty]
+HPDrainRate:6
+CircleSize:8
+OverallDifficulty:8
+ApproachRate:9
+
+[TimingPoints]
+0,500,4,1,0,100,1,0
+
+[HitObjects]
+100,100,0,2,0,B|200:50|300:100,1,200
+";
+
+    #[test]
+    fn test_hard_rock() {
+        let beatmap = Beatmap::from_bytes(BEATMAP).unwrap();
+        let modded = beatmap.with_mods(&[Mod::HardRock]).unwrap();
+        let modded = modded.get_beatmap();
+
+        assert_eq!(modded.get_circle_size(), Some(10.0));
+        assert_eq!(modded.get_hp_drain_rate(), Some(6.0 * 1.4));
+        assert_eq!(modded.get_approach_rate(), Some(10.0));
+
+        let HitObject::Slider(slider) = &modded.get_hit_objects()[0] else {
+            panic!("expected a slider");
+        };
+        assert_eq!(slider.get_y(), 284);
+        let points: Vec<i32> = slider
+            .get_curve_points()
+            .iter()
+            .map(|p| p.get_y())
+            .collect();
+        assert_eq!(points, vec![334, 284]);
+        // The original is untouched
+        assert_eq!(beatmap.get_hit_objects()[0].get_y(), 100);
+    }
+
+    #[test]
+    fn test_clock_rate() {
+        let beatmap = Beatmap::from_bytes(BEATMAP).unwrap();
+
+        let modded = beatmap.with_mods(&[Mod::DoubleTime]).unwrap();
+        assert_eq!(modded.get_preempt(), 400.0);
+        assert!((modded.get_approach_rate() - 31.0 / 3.0).abs() < 1e-9);
+        assert!((modded.get_overall_difficulty() - 88.0 / 9.0).abs() < 1e-9);
+
+        let modded = beatmap
+            .with_mods(&[Mod::Easy, Mod::RateAdjust(0.8)])
+            .unwrap();
+        assert_eq!(modded.get_clock_rate(), 0.8);
+        assert_eq!(modded.get_preempt(), 1260.0 / 0.8);
+        assert_eq!(modded.get_beatmap().get_overall_difficulty(), Some(4.0));
+
+        let modded = beatmap
+            .with_mods(&[Mod::DifficultyAdjust {
+                circle_size: None,
+                approach_rate: Some(11.0),
+                overall_difficulty: None,
+                hp_drain_rate: None,
+            }])
+            .unwrap();
+        assert_eq!(modded.get_preempt(), 300.0);
+        assert_eq!(modded.get_beatmap().get_circle_size(), Some(8.0));
+
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(beatmap.with_mods(&[Mod::RateAdjust(rate)]).is_err());
+        }
+    }
+}
@@ -1,16 +1,18 @@
-use std::{ffi::OsString, io::Read, path::Path};
+use std::{ffi::OsString, io::Read, path::Path, str::FromStr};
 
 use crate::{
     beatmap::Beatmap,
-    sanitize::sanitize,
+    error::{Error, Result},
+    sanitize::{command, sanitize},
     section::{
         Colour, Command, Difficulty, Editor, Events, General, HitObject, Metadata, OsuStoryboard,
         TimingPoint,
     },
+    source::{Source, Span},
     token::Section,
 };
 
-const SECTIONS: [&'static str; 8] = [
+const SECTIONS: [&str; 9] = [
     "[General]",
     "[Editor]",
     "[Metadata]",
@@ -19,11 +21,12 @@ const SECTIONS: [&'static str; 8] = [
     "[TimingPoints]",
     "[Colours]",
     "[HitObjects]",
+    "[Variables]",
 ];
 
 impl Beatmap {
-    fn parse(osu_data: &str, directory: OsString, file_name: &str) -> std::io::Result<Beatmap> {
-        let sanitized = sanitize(osu_data);
+    pub(crate) fn parse(osu_data: &str) -> Result<Beatmap> {
+        let lines = sanitize(osu_data);
 
         let mut version = 14;
 
@@ -37,22 +40,21 @@ impl Beatmap {
         let mut difficulty: Difficulty = Difficulty::default();
         let mut events: Events = Events::default();
 
-        let lines = sanitized
-            .lines()
-            .map(|line| line.to_string())
-            .collect::<Vec<String>>();
+        let mut spans: Vec<Span> = Vec::new();
 
         let mut index = 0;
 
         'outer_loop: while let Some(line) = lines.get(index) {
             if index == 0 {
-                if line.contains("osu file format v") {
-                    version = line.split('v').collect::<Vec<&str>>()[1]
+                let header = line.code.trim_start_matches('\u{feff}');
+                if let Some(v) = header.strip_prefix("osu file format v") {
+                    version = v
+                        .trim()
                         .parse::<u8>()
-                        .unwrap();
+                        .map_err(|_| Error::file_format(0).at(line.number, line.raw))?;
                 }
             }
-            if let Ok(section) = Section::parse(line) {
+            if let Ok(section) = Section::parse(line.code) {
                 match section {
                     Section::General
                     | Section::Editor
@@ -61,29 +63,27 @@ impl Beatmap {
                     | Section::Colours => {
                         index += 1;
                         while let Some(line) = lines.get(index) {
-                            if SECTIONS.contains(&line.as_str()) {
+                            if SECTIONS.contains(&line.code) {
                                 continue 'outer_loop;
                             } else {
                                 match section {
-                                    Section::General => {
-                                        general.parse_value(line.as_str());
-                                    }
-                                    Section::Editor => {
-                                        editor.parse_value(line.as_str());
-                                    }
-                                    Section::Metadata => {
-                                        metadata.parse_value(line.as_str());
-                                    }
-                                    Section::Difficulty => {
-                                        difficulty.parse_value(line.as_str());
-                                    }
-                                    Section::Colours => {
-                                        colours.push(Colour::parse(line.as_str())?);
-                                    }
+                                    Section::General => general.parse_value(line.code),
+                                    Section::Editor => editor.parse_value(line.code),
+                                    Section::Metadata => metadata.parse_value(line.code),
+                                    Section::Difficulty => difficulty.parse_value(line.code),
+                                    Section::Colours => Colour::parse(line.code).map(|c| {
+                                        colours.push(c);
+                                        spans.push(Span {
+                                            header: section.header(),
+                                            first: line.number,
+                                            last: line.number,
+                                        });
+                                    }),
                                     _ => {
                                         unreachable!("This should never happen");
                                     }
                                 }
+                                .map_err(|e| e.at(line.number, line.raw))?;
 
                                 index += 1;
                             }
@@ -92,15 +92,24 @@ impl Beatmap {
                     Section::TimingPoints => {
                         index += 1;
                         while let Some(line) = lines.get(index) {
-                            if SECTIONS.contains(&line.as_str()) {
+                            if SECTIONS.contains(&line.code) {
                                 continue 'outer_loop;
                             } else {
                                 let split = line
+                                    .code
                                     .split(',')
                                     .map(|split| split.trim())
                                     .collect::<Vec<&str>>();
 
-                                timing_points.push(TimingPoint::parse(&split[..])?);
+                                timing_points.push(
+                                    TimingPoint::parse(&split[..])
+                                        .map_err(|e| e.at(line.number, line.raw))?,
+                                );
+                                spans.push(Span {
+                                    header: section.header(),
+                                    first: line.number,
+                                    last: line.number,
+                                });
 
                                 index += 1;
                             }
@@ -110,46 +119,76 @@ impl Beatmap {
                         index += 1;
 
                         while let Some(line) = lines.get(index) {
-                            if SECTIONS.contains(&line.as_str()) {
+                            if SECTIONS.contains(&line.code) {
                                 continue 'outer_loop;
                             } else {
-                                if let Ok(mut storyboard) = OsuStoryboard::parse(line.as_str()) {
-                                    index += 1;
-
-                                    'command_loop: while let Some(potential_command) =
-                                        lines.get(index)
-                                    {
-                                        if let Ok(command) = Command::parse(&potential_command) {
-                                            storyboard.add_command(command);
-                                            index += 1;
-                                        } else {
-                                            break 'command_loop;
+                                let mut storyboard = match OsuStoryboard::parse(line.code) {
+                                    Ok(storyboard) => storyboard,
+                                    Err(Error::Storyboard { field: 0, .. }) => {
+                                        let count = events.len();
+                                        events
+                                            .parse_value(line.code)
+                                            .map_err(|e| e.at(line.number, line.raw))?;
+                                        if events.len() > count {
+                                            spans.push(Span {
+                                                header: section.header(),
+                                                first: line.number,
+                                                last: line.number,
+                                            });
                                         }
+                                        index += 1;
+                                        continue;
                                     }
-                                    events.push_storyboard(storyboard);
-                                } else {
-                                    events.parse_value(line.as_str());
+                                    Err(e) => return Err(e.at(line.number, line.raw)),
+                                };
+                                index += 1;
+
+                                let mut span = Span {
+                                    header: section.header(),
+                                    first: line.number,
+                                    last: line.number,
+                                };
+                                while let Some(potential_command) = lines.get(index) {
+                                    let Some((depth, code)) = command(potential_command) else {
+                                        break;
+                                    };
+
+                                    Command::parse(code)
+                                        .and_then(|command| {
+                                            storyboard.add_command_at_depth(depth, command)
+                                        })
+                                        .map_err(|e| {
+                                            e.at(potential_command.number, potential_command.raw)
+                                        })?;
+                                    span.last = potential_command.number;
                                     index += 1;
                                 }
+                                events.push_storyboard(storyboard);
+                                spans.push(span);
                             }
                         }
                     }
+                    // Variables only apply to storyboards, the lines are skipped
+                    // along with anything else outside a known section.
+                    Section::Variables => {}
                     Section::HitObjects => {
                         index += 1;
                         while let Some(line) = lines.get(index) {
                             let split = line
+                                .code
                                 .split(',')
                                 .map(|split| split.trim())
                                 .collect::<Vec<&str>>();
 
-                            match section {
-                                Section::HitObjects => {
-                                    hit_objects.push(HitObject::parse(&split[..])?);
-                                }
-                                _ => {
-                                    unreachable!("This should never happen");
-                                }
-                            }
+                            hit_objects.push(
+                                HitObject::parse(&split[..])
+                                    .map_err(|e| e.at(line.number, line.raw))?,
+                            );
+                            spans.push(Span {
+                                header: section.header(),
+                                first: line.number,
+                                last: line.number,
+                            });
 
                             index += 1;
                         }
@@ -160,9 +199,7 @@ impl Beatmap {
             index += 1;
         }
 
-        Ok(Beatmap::new(
-            file_name.to_string(),
-            directory,
+        let mut beatmap = Beatmap::new(
             version,
             general,
             editor,
@@ -172,31 +209,114 @@ impl Beatmap {
             timing_points,
             colours,
             hit_objects,
-        ))
+        );
+        beatmap.source = Some(Source::new(osu_data, &spans, beatmap.section_entries()));
+
+        Ok(beatmap)
     }
 
-    pub fn parse_file(file: &str) -> std::io::Result<Beatmap> {
+    pub fn parse_file(file: &str) -> Result<Beatmap> {
         let path = Path::new(file);
-        let file_name = path.file_name().unwrap().to_str().unwrap();
-        let directory = OsString::from(path.parent().unwrap());
-        let mut file = std::fs::File::open(path)?;
+        let mut beatmap = Beatmap::from_reader(std::fs::File::open(path)?)?;
 
+        if let Some(file_name) = path.file_name().and_then(|name| name.to_str()) {
+            beatmap.change_file_name(file_name);
+        }
+        beatmap.change_directory(OsString::from(path.parent().unwrap_or(Path::new(""))));
+
+        Ok(beatmap)
+    }
+
+    /// Parses a beatmap from any reader. The file name and directory are left
+    /// unset.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Beatmap> {
         let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        Beatmap::parse(&contents, directory, file_name)
+        reader.read_to_string(&mut contents)?;
+        Beatmap::parse(&contents)
+    }
+
+    /// Parses a beatmap from raw bytes, which must be valid UTF-8.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Beatmap> {
+        let osu_data = std::str::from_utf8(bytes)
+            .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+        Beatmap::parse(osu_data)
     }
 }
 
-#[cfg(test)]
+impl FromStr for Beatmap {
+    type Err = Error;
 
-mod tests {
+    fn from_str(osu_data: &str) -> Result<Beatmap> {
+        Beatmap::parse(osu_data)
+    }
+}
 
+#[cfg(test)]
+mod tests {
     use super::*;
 
     #[test]
     fn test() {
         let mut beatmap: Beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
         beatmap.change_metadata_title("@KorieDrakeChaney was here");
-        beatmap.save_with_name("test_beatmap.osu");
+
+        let path = std::env::temp_dir().join("osu_parser_test_beatmap.osu");
+        beatmap.save_with_name(path.to_str().unwrap()).unwrap();
+        let saved = Beatmap::parse_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(saved.get_metadata_title(), "@KorieDrakeChaney was here");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let osu_data = std::fs::read_to_string("beatmap.osu").unwrap();
+        let beatmap: Beatmap = osu_data.parse().unwrap();
+
+        assert_eq!(beatmap.get_file_name(), None);
+        assert_eq!(beatmap.get_directory(), None);
+        assert!(beatmap.save().is_err());
+
+        let from_reader = Beatmap::from_reader(osu_data.as_bytes()).unwrap();
+        let from_bytes = Beatmap::from_bytes(osu_data.as_bytes()).unwrap();
+        assert_eq!(from_reader.to_string(), beatmap.to_string());
+        assert_eq!(from_bytes.to_string(), beatmap.to_string());
+
+        assert!(matches!(
+            Beatmap::from_bytes(&[0xff, 0xfe, 0x00]),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_lossless() {
+        let osu_data = std::fs::read_to_string("beatmap.osu").unwrap();
+        let beatmap = Beatmap::from_str(&osu_data).unwrap();
+        assert_eq!(beatmap.to_string(), osu_data);
+
+        let osu_data = "osu file format v14\n\n[General]\nAudioFilename:  audio.mp3 // song\nUnknownKey: 1\n\n[Metadata]\nTitle:Old\nCreator:Someone\n\n[HitObjects]\n256,192,1000,1,0,0:0:0:0:\n// spinner\n256,192,2000,12,0,3000,0:0:0:0:\n";
+        let mut beatmap = Beatmap::from_str(osu_data).unwrap();
+        assert_eq!(beatmap.to_string(), osu_data);
+
+        beatmap.change_metadata_title("New");
+        beatmap.change_metadata_beatmap_id(42);
+        beatmap.toggle_spinners();
+        assert_eq!(
+            beatmap.to_string(),
+            "osu file format v14\n\n[General]\nAudioFilename:  audio.mp3 // song\nUnknownKey: 1\n\n[Metadata]\nTitle:New\nCreator:Someone\nBeatmapID:42\n\n[HitObjects]\n256,192,1000,1,0,0:0:0:0:\n// spinner\n"
+        );
+
+        beatmap.set_lossless(false);
+        assert!(!beatmap.is_lossless());
+        assert!(!beatmap.to_string().contains("UnknownKey"));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let osu_data = "osu file format v14\n\n[HitObjects]\n256,192,1000,1,0,0:0:0:0:\n// comment\n256,192,abc,1,0,0:0:0:0:\n";
+        let error = Beatmap::from_str(osu_data).unwrap_err();
+
+        assert!(matches!(error, Error::HitObject { .. }));
+        assert_eq!(error.line(), Some(6));
+        assert_eq!(error.field(), Some(2));
+        assert_eq!(error.raw(), Some("256,192,abc,1,0,0:0:0:0:"));
     }
 }
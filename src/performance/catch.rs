@@ -0,0 +1,107 @@
+use super::{ensure_fits, HitCounts, PerformanceAttributes, Score};
+use crate::{difficulty::CatchDifficultyAttributes, error::Result, mods::Mod};
+
+impl CatchDifficultyAttributes {
+    /// Calculates the pp of `score` on the beatmap these attributes are for.
+    pub fn get_performance(&self, score: &Score) -> Result<PerformanceAttributes> {
+        let counts = self.resolve_counts(score)?;
+        let combo = score.resolve_combo(self.max_combo)? as f64;
+
+        let combo_hits = (counts.n300 + counts.n100 + counts.misses) as f64;
+        let total_hits = combo_hits + (counts.n50 + counts.katu) as f64;
+        if total_hits == 0.0 {
+            return Ok(PerformanceAttributes::default());
+        }
+        let accuracy = (counts.n300 + counts.n100 + counts.n50) as f64 / total_hits;
+        let misses = counts.misses as f64;
+
+        let mut value = (5.0 * (self.star_rating / 0.0049).max(1.0) - 4.0).powi(2) / 100000.0;
+
+        let mut length_bonus = 0.95 + 0.3 * (combo_hits / 2500.0).min(1.0);
+        if combo_hits > 2500.0 {
+            length_bonus += (combo_hits / 2500.0).log10() * 0.475;
+        }
+        value *= length_bonus;
+        value *= 0.97f64.powf(misses);
+
+        if self.max_combo > 0 {
+            value *= (combo.powf(0.8) / (self.max_combo as f64).powf(0.8)).min(1.0);
+        }
+
+        let approach_rate = self.approach_rate;
+        let mut approach_rate_factor = 1.0;
+        if approach_rate > 9.0 {
+            approach_rate_factor += 0.1 * (approach_rate - 9.0);
+        }
+        if approach_rate > 10.0 {
+            approach_rate_factor += 0.1 * (approach_rate - 10.0);
+        } else if approach_rate < 8.0 {
+            approach_rate_factor += 0.025 * (8.0 - approach_rate);
+        }
+        value *= approach_rate_factor;
+
+        if score.has_mod(Mod::Hidden) {
+            if approach_rate <= 10.0 {
+                value *= 1.05 + 0.075 * (10.0 - approach_rate);
+            } else {
+                value *= 1.01 + 0.04 * (11.0 - approach_rate.min(11.0));
+            }
+        }
+        if score.has_mod(Mod::Flashlight) {
+            value *= 1.35 * length_bonus;
+        }
+
+        value *= accuracy.powf(5.5);
+
+        if score.has_mod(Mod::NoFail) {
+            value *= (1.0 - 0.02 * misses).max(0.9);
+        }
+
+        Ok(PerformanceAttributes {
+            pp: value,
+            difficulty: value,
+            ..Default::default()
+        })
+    }
+
+    /// Takes misses from the fruits first, then the droplets, and fills in
+    /// the missed tiny droplets from the accuracy when they aren't given.
+    fn resolve_counts(&self, score: &Score) -> Result<HitCounts> {
+        let combo_objects = self.fruit_count + self.droplet_count;
+        ensure_fits(score.misses, combo_objects)?;
+
+        let n300 = score
+            .n300
+            .unwrap_or_else(|| self.fruit_count.saturating_sub(score.misses));
+        let n100 = score.n100.unwrap_or_else(|| {
+            let droplet_misses = score.misses.saturating_sub(self.fruit_count);
+            self.droplet_count - droplet_misses
+        });
+        ensure_fits(n300 + n100 + score.misses, combo_objects)?;
+
+        let tiny_droplet_misses = match (score.katu, score.n50, score.accuracy_fraction()?) {
+            (Some(katu), _, _) => katu,
+            (None, Some(n50), _) => self.tiny_droplet_count.saturating_sub(n50),
+            (None, None, Some(accuracy)) => {
+                let total = (combo_objects + self.tiny_droplet_count) as f64;
+                let missed = (total * (1.0 - accuracy)).round() - score.misses as f64;
+                missed.clamp(0.0, self.tiny_droplet_count as f64) as usize
+            }
+            (None, None, None) => 0,
+        };
+        ensure_fits(tiny_droplet_misses, self.tiny_droplet_count)?;
+        let n50 = score
+            .n50
+            .unwrap_or(self.tiny_droplet_count - tiny_droplet_misses);
+        ensure_fits(n50 + tiny_droplet_misses, self.tiny_droplet_count)?;
+
+        Ok(HitCounts {
+            n300,
+            n100,
+            n50,
+            misses: score.misses,
+            katu: tiny_droplet_misses,
+            ..Default::default()
+        })
+    }
+}
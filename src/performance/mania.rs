@@ -0,0 +1,74 @@
+use super::{downgrades_for, ensure_fits, HitCounts, PerformanceAttributes, Score};
+use crate::{difficulty::ManiaDifficultyAttributes, error::Result, mods::Mod};
+
+impl ManiaDifficultyAttributes {
+    /// Calculates the pp of `score` on the beatmap these attributes are for.
+    pub fn get_performance(&self, score: &Score) -> Result<PerformanceAttributes> {
+        let counts = resolve_counts(score, self.max_combo)?;
+        let total_hits =
+            (counts.geki + counts.n300 + counts.katu + counts.n100 + counts.n50 + counts.misses)
+                as f64;
+        if total_hits == 0.0 {
+            return Ok(PerformanceAttributes::default());
+        }
+
+        // Perfects are worth a little more than greats, unlike in score accuracy
+        let accuracy = (counts.geki as f64 * 320.0
+            + counts.n300 as f64 * 300.0
+            + counts.katu as f64 * 200.0
+            + counts.n100 as f64 * 100.0
+            + counts.n50 as f64 * 50.0)
+            / (total_hits * 320.0);
+
+        let mut multiplier = 8.0;
+        if score.has_mod(Mod::NoFail) {
+            multiplier *= 0.75;
+        }
+        if score.has_mod(Mod::Easy) {
+            multiplier *= 0.5;
+        }
+
+        let difficulty = (self.star_rating - 0.15).max(0.05).powf(2.2)
+            * (5.0 * accuracy - 4.0).max(0.0)
+            * (1.0 + 0.1 * (total_hits / 1500.0).min(1.0));
+
+        Ok(PerformanceAttributes {
+            pp: difficulty * multiplier,
+            difficulty,
+            ..Default::default()
+        })
+    }
+}
+
+/// Fills in the goods from the accuracy when neither they nor the perfects
+/// are given. Every other judgement defaults to none.
+fn resolve_counts(score: &Score, total: usize) -> Result<HitCounts> {
+    let n300 = score.n300.unwrap_or(0);
+    let n100 = score.n100.unwrap_or(0);
+    let n50 = score.n50.unwrap_or(0);
+    let fixed = score.misses + n300 + n100 + n50;
+    ensure_fits(fixed, total)?;
+    let remaining = total - fixed;
+
+    let katu = match (score.katu, score.geki, score.accuracy_fraction()?) {
+        (Some(katu), _, _) => katu,
+        (None, None, Some(accuracy)) => {
+            let target =
+                accuracy * 320.0 * total as f64 - (n300 * 300 + n100 * 100 + n50 * 50) as f64;
+            downgrades_for(remaining, 320.0, 120.0, target)
+        }
+        _ => 0,
+    };
+    ensure_fits(fixed + katu, total)?;
+    let geki = score.geki.unwrap_or(remaining - katu);
+    ensure_fits(fixed + katu + geki, total)?;
+
+    Ok(HitCounts {
+        n300,
+        n100,
+        n50,
+        misses: score.misses,
+        katu,
+        geki,
+    })
+}
@@ -0,0 +1,290 @@
+//! Performance points, worked out from difficulty attributes and how well a
+//! score was played.
+
+mod catch;
+mod mania;
+mod osu;
+mod taiko;
+
+use crate::{
+    builder::ensure,
+    error::Result,
+    mods::Mod,
+    section::{GameMode, HitObject},
+    Beatmap,
+};
+
+/// How a beatmap was played. Counts that aren't given are worked out from
+/// the accuracy, or assumed to be perfect hits without one.
+///
+/// Judgements use the osu!standard names in every mode: in osu!taiko `n300`
+/// and `n100` are greats and oks, in osu!catch `n300`, `n100` and `n50` are
+/// caught fruits, droplets and tiny droplets with `katu` the missed tiny
+/// droplets, and in osu!mania `geki` and `katu` are perfects and goods.
+#[derive(Debug, Clone, Default)]
+pub struct Score {
+    mods: Vec<Mod>,
+    accuracy: Option<f64>,
+    n300: Option<usize>,
+    n100: Option<usize>,
+    n50: Option<usize>,
+    misses: usize,
+    katu: Option<usize>,
+    geki: Option<usize>,
+    combo: Option<usize>,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mods(mut self, mods: &[Mod]) -> Self {
+        self.mods = mods.to_vec();
+        self
+    }
+
+    /// Accuracy as a percentage, used for the counts that aren't given.
+    pub fn accuracy(mut self, accuracy: f64) -> Self {
+        self.accuracy = Some(accuracy);
+        self
+    }
+
+    pub fn n300(mut self, n300: usize) -> Self {
+        self.n300 = Some(n300);
+        self
+    }
+
+    pub fn n100(mut self, n100: usize) -> Self {
+        self.n100 = Some(n100);
+        self
+    }
+
+    pub fn n50(mut self, n50: usize) -> Self {
+        self.n50 = Some(n50);
+        self
+    }
+
+    pub fn misses(mut self, misses: usize) -> Self {
+        self.misses = misses;
+        self
+    }
+
+    pub fn katu(mut self, katu: usize) -> Self {
+        self.katu = Some(katu);
+        self
+    }
+
+    pub fn geki(mut self, geki: usize) -> Self {
+        self.geki = Some(geki);
+        self
+    }
+
+    /// The highest combo reached, a full combo less the misses if not given.
+    pub fn combo(mut self, combo: usize) -> Self {
+        self.combo = Some(combo);
+        self
+    }
+
+    pub fn get_mods(&self) -> &[Mod] {
+        &self.mods
+    }
+
+    fn has_mod(&self, applied: Mod) -> bool {
+        self.mods.contains(&applied)
+    }
+
+    /// The accuracy as a fraction, checking it is a valid percentage.
+    fn accuracy_fraction(&self) -> Result<Option<f64>> {
+        match self.accuracy {
+            Some(accuracy) => {
+                ensure(
+                    (0.0..=100.0).contains(&accuracy),
+                    format!("accuracy {} is not between 0 and 100", accuracy),
+                )?;
+                Ok(Some(accuracy / 100.0))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn resolve_combo(&self, max_combo: usize) -> Result<usize> {
+        let combo = self
+            .combo
+            .unwrap_or_else(|| max_combo.saturating_sub(self.misses));
+        ensure(
+            combo <= max_combo,
+            format!("combo {} is above the max combo of {}", combo, max_combo),
+        )?;
+        Ok(combo)
+    }
+}
+
+/// Every judgement of a score, with nothing left to work out.
+#[derive(Debug, Clone, Copy, Default)]
+struct HitCounts {
+    n300: usize,
+    n100: usize,
+    n50: usize,
+    misses: usize,
+    katu: usize,
+    geki: usize,
+}
+
+/// Fails unless `used` judgements fit in `total` objects.
+fn ensure_fits(used: usize, total: usize) -> Result<()> {
+    ensure(
+        used <= total,
+        format!("{} judgements don't fit in {} objects", used, total),
+    )
+}
+
+/// How many of `remaining` hits to downgrade to reach `target` accuracy
+/// points, when each one starts at `full` points and loses `loss`.
+fn downgrades_for(remaining: usize, full: f64, loss: f64, target: f64) -> usize {
+    let downgrades = ((remaining as f64 * full - target) / loss).round();
+    downgrades.clamp(0.0, remaining as f64) as usize
+}
+
+/// The pp of a score and the parts it is made of. Parts a mode doesn't
+/// have are 0.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerformanceAttributes {
+    pub pp: f64,
+    pub aim: f64,
+    pub speed: f64,
+    pub accuracy: f64,
+    pub flashlight: f64,
+    /// The strain part in osu!taiko, osu!catch and osu!mania
+    pub difficulty: f64,
+}
+
+impl Beatmap {
+    /// The combo of a full combo in the beatmap's own mode.
+    pub fn get_max_combo(&self) -> usize {
+        let mode = self.get_general_mode().unwrap_or_default();
+        self.hit_objects
+            .iter()
+            .map(|hit_object| match (hit_object, mode) {
+                (HitObject::Circle(_), _) => 1,
+                // Drum rolls don't add to the combo
+                (HitObject::Slider(_), GameMode::Taiko) => 0,
+                (HitObject::Slider(slider), _) => {
+                    let timing = self.get_slider_timing(slider);
+                    // Head, ticks, repeats and tail
+                    1 + timing.get_ticks().len() + timing.get_repeat_times().len() + 1
+                }
+                (HitObject::Spinner(_), GameMode::Osu | GameMode::Unknown(_)) => 1,
+                (HitObject::Spinner(_), _) => 0,
+                (HitObject::Hold(_), _) => 2,
+            })
+            .sum()
+    }
+
+    pub fn get_circle_count(&self) -> usize {
+        self.count_hit_objects(|hit_object| matches!(hit_object, HitObject::Circle(_)))
+    }
+
+    pub fn get_slider_count(&self) -> usize {
+        self.count_hit_objects(|hit_object| matches!(hit_object, HitObject::Slider(_)))
+    }
+
+    pub fn get_spinner_count(&self) -> usize {
+        self.count_hit_objects(|hit_object| matches!(hit_object, HitObject::Spinner(_)))
+    }
+
+    pub fn get_hold_count(&self) -> usize {
+        self.count_hit_objects(|hit_object| matches!(hit_object, HitObject::Hold(_)))
+    }
+
+    fn count_hit_objects(&self, predicate: impl Fn(&HitObject) -> bool) -> usize {
+        self.hit_objects
+            .iter()
+            .filter(|hit_object| predicate(hit_object))
+            .count()
+    }
+
+    /// Calculates the osu!standard pp of `score`, using its mods for the
+    /// difficulty too.
+    pub fn get_osu_performance(&self, score: &Score) -> Result<PerformanceAttributes> {
+        self.get_osu_difficulty(score.get_mods())?
+            .get_performance(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_combo() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+        let attributes = beatmap.get_osu_difficulty(&[]).unwrap();
+
+        assert_eq!(beatmap.get_max_combo(), attributes.max_combo);
+        assert_eq!(beatmap.get_circle_count(), attributes.circle_count);
+        assert_eq!(beatmap.get_slider_count(), attributes.slider_count);
+        assert_eq!(beatmap.get_spinner_count(), attributes.spinner_count);
+        assert_eq!(beatmap.get_hold_count(), 0);
+    }
+
+    #[test]
+    fn test_invalid_score() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+
+        assert!(beatmap
+            .get_osu_performance(&Score::new().accuracy(101.0))
+            .is_err());
+        assert!(beatmap
+            .get_osu_performance(&Score::new().misses(10000))
+            .is_err());
+        assert!(beatmap
+            .get_osu_performance(&Score::new().combo(10000))
+            .is_err());
+    }
+
+    #[test]
+    fn test_other_modes() {
+        let taiko = crate::TaikoDifficultyAttributes {
+            star_rating: 4.0,
+            stamina_difficulty: 2.0,
+            rhythm_difficulty: 1.0,
+            colour_difficulty: 2.0,
+            great_hit_window: 35.0,
+            max_combo: 1000,
+        };
+        let perfect = taiko.get_performance(&Score::new()).unwrap();
+        let worse = taiko.get_performance(&Score::new().accuracy(96.0)).unwrap();
+        assert!(perfect.pp > worse.pp && worse.pp > 0.0);
+
+        let catch = crate::CatchDifficultyAttributes {
+            star_rating: 4.0,
+            approach_rate: 9.0,
+            max_combo: 800,
+            fruit_count: 600,
+            droplet_count: 200,
+            tiny_droplet_count: 400,
+        };
+        let perfect = catch.get_performance(&Score::new()).unwrap();
+        let worse = catch.get_performance(&Score::new().misses(2)).unwrap();
+        assert!(perfect.pp > worse.pp && worse.pp > 0.0);
+
+        let mania = crate::ManiaDifficultyAttributes {
+            star_rating: 4.0,
+            great_hit_window: 40.0,
+            max_combo: 1500,
+        };
+        let perfect = mania.get_performance(&Score::new()).unwrap();
+        assert!((perfect.pp - 8.0 * 3.85f64.powf(2.2) * 1.1).abs() < 1e-9);
+        assert!((perfect.pp - 170.80331).abs() < 1e-3);
+        let easy = mania
+            .get_performance(&Score::new().mods(&[Mod::Easy, Mod::NoFail]))
+            .unwrap();
+        assert!((easy.pp - perfect.pp * 0.375).abs() < 1e-9);
+        // 96.25% accuracy
+        let worse = mania
+            .get_performance(&Score::new().geki(1350).katu(150))
+            .unwrap();
+        assert!((worse.pp - perfect.pp * 0.8125).abs() < 1e-9);
+    }
+}
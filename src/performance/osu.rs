@@ -0,0 +1,324 @@
+use super::{downgrades_for, ensure_fits, HitCounts, PerformanceAttributes, Score};
+use crate::{
+    difficulty::{base_performance, OsuDifficultyAttributes, PERFORMANCE_BASE_MULTIPLIER},
+    error::Result,
+    mods::Mod,
+};
+
+impl OsuDifficultyAttributes {
+    /// Calculates the pp of `score` on the beatmap these attributes are for.
+    pub fn get_performance(&self, score: &Score) -> Result<PerformanceAttributes> {
+        let total = self.circle_count + self.slider_count + self.spinner_count;
+        let counts = resolve_counts(score, total)?;
+        let combo = score.resolve_combo(self.max_combo)?;
+
+        let performance = OsuPerformance {
+            attributes: self,
+            score,
+            counts,
+            combo: combo as f64,
+            total_hits: (counts.n300 + counts.n100 + counts.n50 + counts.misses) as f64,
+            accuracy: 0.0,
+            effective_miss_count: 0.0,
+        };
+        Ok(performance.calculate())
+    }
+}
+
+/// Fills in the 100s from the accuracy when neither they nor the 300s are
+/// given, keeping any 50s.
+fn resolve_counts(score: &Score, total: usize) -> Result<HitCounts> {
+    let n50 = score.n50.unwrap_or(0);
+    ensure_fits(score.misses + n50, total)?;
+    let remaining = total - score.misses - n50;
+
+    let n100 = match (score.n100, score.n300, score.accuracy_fraction()?) {
+        (Some(n100), _, _) => n100,
+        (None, None, Some(accuracy)) => {
+            let target = accuracy * 6.0 * total as f64 - n50 as f64;
+            downgrades_for(remaining, 6.0, 4.0, target)
+        }
+        _ => 0,
+    };
+    ensure_fits(score.misses + n50 + n100, total)?;
+    let n300 = score.n300.unwrap_or(remaining - n100);
+    ensure_fits(score.misses + n50 + n100 + n300, total)?;
+
+    Ok(HitCounts {
+        n300,
+        n100,
+        n50,
+        misses: score.misses,
+        ..Default::default()
+    })
+}
+
+struct OsuPerformance<'a> {
+    attributes: &'a OsuDifficultyAttributes,
+    score: &'a Score,
+    counts: HitCounts,
+    combo: f64,
+    total_hits: f64,
+    accuracy: f64,
+    effective_miss_count: f64,
+}
+
+impl OsuPerformance<'_> {
+    fn calculate(mut self) -> PerformanceAttributes {
+        if self.total_hits == 0.0 {
+            return PerformanceAttributes::default();
+        }
+
+        let counts = self.counts;
+        self.accuracy = (counts.n300 as f64 * 6.0 + counts.n100 as f64 * 2.0 + counts.n50 as f64)
+            / (self.total_hits * 6.0);
+        self.effective_miss_count = self.effective_miss_count();
+
+        let mut multiplier = PERFORMANCE_BASE_MULTIPLIER;
+        if self.score.has_mod(Mod::NoFail) {
+            multiplier *= (1.0 - 0.02 * self.effective_miss_count).max(0.9);
+        }
+        if self.score.has_mod(Mod::SpunOut) {
+            multiplier *= 1.0 - (self.attributes.spinner_count as f64 / self.total_hits).powf(0.85);
+        }
+
+        let aim = self.aim_value();
+        let speed = self.speed_value();
+        let accuracy = self.accuracy_value();
+        let flashlight = self.flashlight_value();
+        let pp = (aim.powf(1.1) + speed.powf(1.1) + accuracy.powf(1.1) + flashlight.powf(1.1))
+            .powf(1.0 / 1.1)
+            * multiplier;
+
+        PerformanceAttributes {
+            pp,
+            aim,
+            speed,
+            accuracy,
+            flashlight,
+            difficulty: 0.0,
+        }
+    }
+
+    /// Misses, or more if the combo was broken in ways that don't show up
+    /// as misses, like dropped slider ends.
+    fn effective_miss_count(&self) -> f64 {
+        let attributes = self.attributes;
+        let counts = self.counts;
+
+        let mut combo_based_miss_count = 0.0;
+        if attributes.slider_count > 0 {
+            let full_combo_threshold =
+                attributes.max_combo as f64 - 0.1 * attributes.slider_count as f64;
+            if self.combo < full_combo_threshold {
+                combo_based_miss_count = full_combo_threshold / self.combo.max(1.0);
+            }
+        }
+
+        let misses = counts.misses as f64;
+        combo_based_miss_count
+            .min((counts.n100 + counts.n50 + counts.misses) as f64)
+            .max(misses)
+    }
+
+    fn length_bonus(&self) -> f64 {
+        let mut length_bonus = 0.95 + 0.4 * (self.total_hits / 2000.0).min(1.0);
+        if self.total_hits > 2000.0 {
+            length_bonus += (self.total_hits / 2000.0).log10() * 0.5;
+        }
+        length_bonus
+    }
+
+    fn combo_scaling_factor(&self) -> f64 {
+        if self.attributes.max_combo == 0 {
+            1.0
+        } else {
+            (self.combo.powf(0.8) / (self.attributes.max_combo as f64).powf(0.8)).min(1.0)
+        }
+    }
+
+    /// How much misses take off, more harshly for `exponent` above 1.
+    fn miss_penalty(&self, exponent: f64) -> f64 {
+        if self.effective_miss_count > 0.0 {
+            0.97 * (1.0 - (self.effective_miss_count / self.total_hits).powf(0.775))
+                .powf(self.effective_miss_count.powf(exponent))
+        } else {
+            1.0
+        }
+    }
+
+    fn aim_value(&self) -> f64 {
+        let attributes = self.attributes;
+        let mut aim_value = base_performance(attributes.aim_difficulty);
+
+        let length_bonus = self.length_bonus();
+        aim_value *= length_bonus;
+        aim_value *= self.miss_penalty(1.0);
+        aim_value *= self.combo_scaling_factor();
+
+        let approach_rate = attributes.approach_rate;
+        let mut approach_rate_factor = 0.0;
+        if approach_rate > 10.33 {
+            approach_rate_factor = 0.3 * (approach_rate - 10.33);
+        } else if approach_rate < 8.0 {
+            approach_rate_factor = 0.05 * (8.0 - approach_rate);
+        }
+        aim_value *= 1.0 + approach_rate_factor * length_bonus;
+
+        // Hidden rewards lower approach rates more
+        if self.score.has_mod(Mod::Hidden) {
+            aim_value *= 1.0 + 0.04 * (12.0 - approach_rate);
+        }
+
+        // Dropping the ends of hard sliders breaks combo without a miss
+        let estimate_difficult_sliders = attributes.slider_count as f64 * 0.15;
+        if attributes.slider_count > 0 {
+            let counts = self.counts;
+            let estimate_slider_ends_dropped = ((counts.n100 + counts.n50 + counts.misses) as f64)
+                .min(attributes.max_combo as f64 - self.combo)
+                .clamp(0.0, estimate_difficult_sliders);
+            let slider_nerf_factor = (1.0 - attributes.slider_factor)
+                * (1.0 - estimate_slider_ends_dropped / estimate_difficult_sliders).powi(3)
+                + attributes.slider_factor;
+            aim_value *= slider_nerf_factor;
+        }
+
+        aim_value *= self.accuracy;
+        aim_value *= 0.98 + attributes.overall_difficulty.powi(2) / 2500.0;
+        aim_value
+    }
+
+    fn speed_value(&self) -> f64 {
+        let attributes = self.attributes;
+        let mut speed_value = base_performance(attributes.speed_difficulty);
+
+        let length_bonus = self.length_bonus();
+        speed_value *= length_bonus;
+        speed_value *= self.miss_penalty(0.875);
+        speed_value *= self.combo_scaling_factor();
+
+        let approach_rate = attributes.approach_rate;
+        if approach_rate > 10.33 {
+            speed_value *= 1.0 + 0.3 * (approach_rate - 10.33) * length_bonus;
+        }
+
+        if self.score.has_mod(Mod::Hidden) {
+            speed_value *= 1.0 + 0.04 * (12.0 - approach_rate);
+        }
+
+        // Accuracy on the notes that are hard to tap, assuming the worst
+        // judgements went to them
+        let counts = self.counts;
+        let (n300, n100, n50) = (counts.n300 as f64, counts.n100 as f64, counts.n50 as f64);
+        let relevant_total_diff = self.total_hits - attributes.speed_note_count;
+        let relevant_n300 = (n300 - relevant_total_diff).max(0.0);
+        let relevant_n100 = (n100 - (relevant_total_diff - n300).max(0.0)).max(0.0);
+        let relevant_n50 = (n50 - (relevant_total_diff - n300 - n100).max(0.0)).max(0.0);
+        let relevant_accuracy = if attributes.speed_note_count == 0.0 {
+            0.0
+        } else {
+            (relevant_n300 * 6.0 + relevant_n100 * 2.0 + relevant_n50)
+                / (attributes.speed_note_count * 6.0)
+        };
+
+        let overall_difficulty = attributes.overall_difficulty;
+        speed_value *= (0.95 + overall_difficulty.powi(2) / 750.0)
+            * ((self.accuracy + relevant_accuracy) / 2.0)
+                .powf((14.5 - overall_difficulty.max(8.0)) / 2.0);
+
+        // Penalise 50s beyond one in every 500 hits
+        let allowed_n50 = self.total_hits / 500.0;
+        if n50 >= allowed_n50 {
+            speed_value *= 0.99f64.powf(n50 - allowed_n50);
+        }
+        speed_value
+    }
+
+    fn accuracy_value(&self) -> f64 {
+        let attributes = self.attributes;
+        let counts = self.counts;
+
+        // Only circles have a hit window, so work out the accuracy on them alone
+        let circle_count = attributes.circle_count as f64;
+        let better_accuracy = if attributes.circle_count > 0 {
+            (((counts.n300 as f64 - (self.total_hits - circle_count)) * 6.0
+                + counts.n100 as f64 * 2.0
+                + counts.n50 as f64)
+                / (circle_count * 6.0))
+                .max(0.0)
+        } else {
+            0.0
+        };
+
+        let mut accuracy_value =
+            1.52163f64.powf(attributes.overall_difficulty) * better_accuracy.powi(24) * 2.83;
+        accuracy_value *= (circle_count / 1000.0).powf(0.3).min(1.15);
+
+        if self.score.has_mod(Mod::Hidden) {
+            accuracy_value *= 1.08;
+        }
+        if self.score.has_mod(Mod::Flashlight) {
+            accuracy_value *= 1.02;
+        }
+        accuracy_value
+    }
+
+    fn flashlight_value(&self) -> f64 {
+        if !self.score.has_mod(Mod::Flashlight) {
+            return 0.0;
+        }
+
+        let attributes = self.attributes;
+        let mut flashlight_value = attributes.flashlight_difficulty.powi(2) * 25.0;
+        flashlight_value *= self.miss_penalty(0.875);
+        flashlight_value *= self.combo_scaling_factor();
+
+        // Longer maps are harder to memorise
+        let mut length_bonus = 0.7 + 0.1 * (self.total_hits / 200.0).min(1.0);
+        if self.total_hits > 200.0 {
+            length_bonus += 0.2 * ((self.total_hits - 200.0) / 200.0).min(1.0);
+        }
+        flashlight_value *= length_bonus;
+
+        flashlight_value *= 0.5 + self.accuracy / 2.0;
+        flashlight_value *= 0.98 + attributes.overall_difficulty.powi(2) / 2500.0;
+        flashlight_value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{performance::Score, Beatmap, Mod};
+
+    #[test]
+    fn test_performance() {
+        let beatmap = Beatmap::parse_file("beatmap.osu").unwrap();
+        let attributes = beatmap.get_osu_difficulty(&[]).unwrap();
+
+        let perfect = attributes.get_performance(&Score::new()).unwrap();
+        assert!(perfect.pp > 0.0);
+        assert!(perfect.aim > 0.0 && perfect.speed > 0.0 && perfect.accuracy > 0.0);
+        assert_eq!(perfect.flashlight, 0.0);
+
+        let worse = attributes
+            .get_performance(&Score::new().accuracy(95.0).misses(3).combo(200))
+            .unwrap();
+        assert!(worse.pp < perfect.pp);
+
+        // Accuracy alone decides the 100s
+        let from_accuracy = attributes
+            .get_performance(&Score::new().accuracy(98.0))
+            .unwrap();
+        let total = attributes.circle_count + attributes.slider_count + attributes.spinner_count;
+        let n100 = ((total as f64 * 0.02 * 6.0) / 4.0).round() as usize;
+        let from_counts = attributes
+            .get_performance(&Score::new().n300(total - n100).n100(n100))
+            .unwrap();
+        assert_eq!(from_accuracy, from_counts);
+
+        let hidden = beatmap
+            .get_osu_performance(&Score::new().mods(&[Mod::Hidden, Mod::DoubleTime]))
+            .unwrap();
+        assert!(hidden.pp > perfect.pp);
+    }
+}
@@ -0,0 +1,115 @@
+use super::{downgrades_for, ensure_fits, HitCounts, PerformanceAttributes, Score};
+use crate::{difficulty::TaikoDifficultyAttributes, error::Result, mods::Mod};
+
+impl TaikoDifficultyAttributes {
+    /// Calculates the pp of `score` on the beatmap these attributes are for.
+    pub fn get_performance(&self, score: &Score) -> Result<PerformanceAttributes> {
+        let counts = resolve_counts(score, self.max_combo)?;
+        let total_hits = (counts.n300 + counts.n100 + counts.misses) as f64;
+        if total_hits == 0.0 {
+            return Ok(PerformanceAttributes::default());
+        }
+
+        let accuracy = (counts.n300 as f64 + counts.n100 as f64 * 0.5) / total_hits;
+        let successful_hits = (counts.n300 + counts.n100) as f64;
+        // Misses count for more on shorter maps
+        let effective_miss_count = if successful_hits > 0.0 {
+            (1000.0 / successful_hits).max(1.0) * counts.misses as f64
+        } else {
+            0.0
+        };
+
+        let mut multiplier = 1.13;
+        if score.has_mod(Mod::Hidden) {
+            multiplier *= 1.075;
+        }
+        if score.has_mod(Mod::Easy) {
+            multiplier *= 0.975;
+        }
+
+        let difficulty = self.difficulty_value(score, total_hits, accuracy, effective_miss_count);
+        let accuracy = self.accuracy_value(score, total_hits, accuracy);
+        let pp = (difficulty.powf(1.1) + accuracy.powf(1.1)).powf(1.0 / 1.1) * multiplier;
+
+        Ok(PerformanceAttributes {
+            pp,
+            accuracy,
+            difficulty,
+            ..Default::default()
+        })
+    }
+
+    fn difficulty_value(
+        &self,
+        score: &Score,
+        total_hits: f64,
+        accuracy: f64,
+        effective_miss_count: f64,
+    ) -> f64 {
+        let mut difficulty_value =
+            (5.0 * (self.star_rating / 0.115).max(1.0) - 4.0).powf(2.25) / 1150.0;
+
+        let length_bonus = 1.0 + 0.1 * (total_hits / 1500.0).min(1.0);
+        difficulty_value *= length_bonus;
+        difficulty_value *= 0.986f64.powf(effective_miss_count);
+
+        if score.has_mod(Mod::Easy) {
+            difficulty_value *= 0.985;
+        }
+        if score.has_mod(Mod::Hidden) {
+            difficulty_value *= 1.025;
+        }
+        if score.has_mod(Mod::HardRock) {
+            difficulty_value *= 1.05;
+        }
+        if score.has_mod(Mod::Flashlight) {
+            difficulty_value *= 1.05 * length_bonus;
+        }
+
+        difficulty_value * accuracy.powi(2)
+    }
+
+    fn accuracy_value(&self, score: &Score, total_hits: f64, accuracy: f64) -> f64 {
+        if self.great_hit_window <= 0.0 {
+            return 0.0;
+        }
+
+        let mut accuracy_value = (60.0 / self.great_hit_window).powf(1.1)
+            * accuracy.powi(8)
+            * self.star_rating.powf(0.4)
+            * 27.0;
+
+        let length_bonus = (total_hits / 1500.0).powf(0.3).min(1.15);
+        accuracy_value *= length_bonus;
+
+        // Reading a map with Hidden and Flashlight is mostly memory
+        if score.has_mod(Mod::Hidden) && score.has_mod(Mod::Flashlight) {
+            accuracy_value *= (1.1 * length_bonus).max(1.0);
+        }
+        accuracy_value
+    }
+}
+
+/// Fills in the oks from the accuracy when neither they nor the greats are given.
+fn resolve_counts(score: &Score, total: usize) -> Result<HitCounts> {
+    ensure_fits(score.misses, total)?;
+    let remaining = total - score.misses;
+
+    let n100 = match (score.n100, score.n300, score.accuracy_fraction()?) {
+        (Some(n100), _, _) => n100,
+        (None, None, Some(accuracy)) => {
+            downgrades_for(remaining, 2.0, 1.0, accuracy * 2.0 * total as f64)
+        }
+        _ => 0,
+    };
+    ensure_fits(score.misses + n100, total)?;
+    let n300 = score.n300.unwrap_or(remaining - n100);
+    ensure_fits(score.misses + n100 + n300, total)?;
+
+    Ok(HitCounts {
+        n300,
+        n100,
+        misses: score.misses,
+        ..Default::default()
+    })
+}
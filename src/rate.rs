@@ -0,0 +1,143 @@
+//! Speeding a whole beatmap up or down, for difficulties played with the
+//! audio re-encoded at another rate.
+
+use crate::{
+    error::{Error, Result},
+    section::TimingIndex,
+    utils::scale_time,
+    Beatmap,
+};
+
+impl Beatmap {
+    /// A copy of the beatmap with every time divided by `rate`, so 1.2 plays
+    /// 20% faster. The version gets a rate suffix, the beatmap ID is cleared
+    /// since this is a new difficulty, and the file name is updated to the new
+    /// version, or cleared if it doesn't follow the usual pattern so the
+    /// original is never overwritten. The audio file is left for the caller
+    /// to re-encode.
+    pub fn with_rate(&self, rate: f64) -> Result<Beatmap> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(Error::build("rate must be positive"));
+        }
+
+        let mut beatmap = self.clone();
+
+        for hit_object in beatmap.hit_objects.iter_mut() {
+            hit_object.scale_times(rate);
+        }
+        for timing_point in beatmap.timing_points.iter_mut() {
+            timing_point.scale_times(rate);
+        }
+        beatmap.timing_index = TimingIndex::new(&beatmap.timing_points);
+        beatmap.events.scale_times(rate);
+        beatmap.editor.scale_times(rate);
+
+        let general = &mut beatmap.general;
+        general.audio_lead_in = general
+            .audio_lead_in
+            .map(|lead_in| scale_time(lead_in, rate));
+        // -1 means no preview time was set
+        general.preview_time = general.preview_time.map(|preview| {
+            if preview < 0 {
+                preview
+            } else {
+                scale_time(preview, rate)
+            }
+        });
+
+        let version = self.get_metadata_version();
+        let rated = format!("{} {}x", version, rate).trim_start().to_string();
+        beatmap.change_metadata_version(&rated);
+        beatmap.change_metadata_beatmap_id(0);
+
+        let file_name = self.get_file_name().and_then(|name| {
+            let suffix = format!("[{}].osu", version);
+            name.strip_suffix(&suffix)
+                .map(|stem| format!("{}[{}].osu", stem, rated))
+        });
+        beatmap.file_name = file_name;
+
+        Ok(beatmap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_rate() {
+        let mut beatmap = Beatmap::from_bytes(
+            b"osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+AudioLeadIn: 600
+PreviewTime: -1
+
+[Editor]
+Bookmarks: 1200,2400
+
+[Metadata]
+Version:Insane
+BeatmapID:123
+
+[Events]
+0,0,\"bg.jpg\",0,0
+2,3600,6000
+Sprite,Foreground,Centre,\"sb.png\",320,240
+ L,1200,2
+  F,0,0,600,1,0
+
+[TimingPoints]
+120,500,4,1,0,100,1,0
+1320,-50,4,1,0,100,0,0
+
+[HitObjects]
+256,192,1200,1,0,0:0:0:0:
+256,192,2400,12,0,4800,0:0:0:0:
+",
+        )
+        .unwrap();
+        beatmap.change_file_name("Artist - Title (Creator) [Insane].osu");
+
+        let rated = beatmap.with_rate(1.2).unwrap();
+        let expected = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+AudioLeadIn: 500
+PreviewTime: -1
+
+[Editor]
+Bookmarks: 1000,2000
+
+[Metadata]
+Version:Insane 1.2x
+BeatmapID:0
+
+[Events]
+0,0,\"bg.jpg\",0,0
+2,3000,5000
+Sprite,Foreground,Centre,\"sb.png\",320,240
+ L,1000,2
+  F,0,0,500,1,0
+
+[TimingPoints]
+100,416.66666,4,1,0,100,1,0
+1100,-50,4,1,0,100,0,0
+
+[HitObjects]
+256,192,1000,1,0,0:0:0:0:
+256,192,2000,12,0,4000,0:0:0:0:
+";
+        assert_eq!(rated.to_string(), expected);
+        assert_eq!(
+            rated.get_file_name(),
+            Some("Artist - Title (Creator) [Insane 1.2x].osu")
+        );
+        assert!((rated.get_bpm_at(1000.0) - 144.0).abs() < 1e-3);
+
+        assert!(beatmap.with_rate(0.0).is_err());
+    }
+}
@@ -1,14 +1,42 @@
-pub fn sanitize(input: &str) -> String {
+/// A line of meaningful content along with where it came from in the input.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    /// 1-based line number in the original input
+    pub number: usize,
+    /// The line exactly as it appeared in the input
+    pub raw: &'a str,
+    /// The line with comments and surrounding whitespace removed
+    pub code: &'a str,
+}
+
+pub fn sanitize(input: &str) -> Vec<Line<'_>> {
     input
         .lines()
-        .filter_map(|line| {
+        .enumerate()
+        .filter_map(|(index, line)| {
             let mut parts = line.splitn(2, "//");
             let code = parts.next().unwrap_or("").trim();
             if !code.is_empty() {
-                Some(format!("{}\n", code))
+                Some(Line {
+                    number: index + 1,
+                    raw: line,
+                    code,
+                })
             } else {
                 None
             }
         })
         .collect()
 }
+
+/// Storyboard commands are indented with spaces or underscores below the
+/// object they belong to, one more for each loop or trigger they are nested
+/// in. Returns the depth of a command line along with the command itself.
+pub fn command<'a>(line: &Line<'a>) -> Option<(usize, &'a str)> {
+    let depth = line.raw.len() - line.raw.trim_start_matches([' ', '_']).len();
+    if depth == 0 {
+        return None;
+    }
+
+    Some((depth, line.code.trim_start_matches([' ', '_'])))
+}
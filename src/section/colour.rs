@@ -1,6 +1,9 @@
-use crate::utils::Color;
+use crate::{
+    error::{Error, Result},
+    utils::Color,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Colour {
     ComboColor(i32, Color),
     SliderTrackOverride(Color),
@@ -8,23 +11,22 @@ pub enum Colour {
 }
 
 impl Colour {
-    pub fn parse(s: &str) -> std::io::Result<Self> {
-        let s: Vec<&str> = s.split(':').map(|s| s.trim()).collect();
+    pub fn parse(s: &str) -> Result<Self> {
+        let (key, value) = s.split_once(':').ok_or(Error::colour(0))?;
+        let key = key.trim();
+        let color = Color::parse(value).ok_or(Error::colour(1))?;
 
-        if s[0].contains("Combo") {
+        if let Some(n) = key.strip_prefix("Combo") {
             Ok(Colour::ComboColor(
-                s[0][5..].parse().unwrap(),
-                Color::from(s[1]),
+                n.parse().map_err(|_| Error::colour(0))?,
+                color,
             ))
-        } else if s[0] == "SliderTrackOverride" {
-            Ok(Colour::SliderTrackOverride(Color::from(s[1])))
-        } else if s[0] == "SliderBorder" {
-            Ok(Colour::SliderBorder(Color::from(s[1])))
+        } else if key == "SliderTrackOverride" {
+            Ok(Colour::SliderTrackOverride(color))
+        } else if key == "SliderBorder" {
+            Ok(Colour::SliderBorder(color))
         } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid Colour token",
-            ))
+            Err(Error::colour(0))
         }
     }
 }
@@ -32,9 +34,9 @@ impl Colour {
 impl std::fmt::Display for Colour {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Colour::ComboColor(n, c) => write!(f, "Combo{} :{}\n", n, c),
-            Colour::SliderTrackOverride(c) => write!(f, "SliderTrackOverride :{}\n", c),
-            Colour::SliderBorder(c) => write!(f, "SliderBorder :{}\n", c),
+            Colour::ComboColor(n, c) => writeln!(f, "Combo{} :{}", n, c),
+            Colour::SliderTrackOverride(c) => writeln!(f, "SliderTrackOverride :{}", c),
+            Colour::SliderBorder(c) => writeln!(f, "SliderBorder :{}", c),
         }
     }
 }
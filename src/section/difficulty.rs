@@ -1,6 +1,64 @@
-use crate::Beatmap;
+use crate::{
+    builder::ensure,
+    error::{Error, Result},
+    Beatmap,
+};
 
-#[derive(Debug)]
+use super::GameMode;
+
+/// Value used for any difficulty setting missing from the file.
+const DEFAULT_DIFFICULTY: f64 = 5.0;
+/// Shortest preempt osu! allows, reached at AR 10.
+const PREEMPT_MIN: f64 = 450.0;
+/// Radius of a circle at a scale of 1, in osu! pixels.
+const OBJECT_RADIUS: f64 = 64.0;
+/// Range osu! keeps the slider multiplier in.
+const SLIDER_MULTIPLIER_RANGE: (f32, f32) = (0.4, 3.6);
+/// Range osu! keeps the slider tick rate in.
+const SLIDER_TICK_RATE_RANGE: (f32, f32) = (0.5, 8.0);
+/// Matches the rounding error of stable's playfield scaling, which lazer
+/// keeps so circle sizes line up.
+const BROKEN_GAMEFIELD_ROUNDING_ALLOWANCE: f64 = 1.00041;
+
+/// `value` kept within `range`, or `default` when it is missing or not a
+/// number.
+fn clamp_setting(value: Option<f32>, default: f64, (min, max): (f32, f32)) -> f64 {
+    value
+        .filter(|value| !value.is_nan())
+        .map_or(default, |value| value.clamp(min, max) as f64)
+}
+
+/// Maps a difficulty setting onto a game value: `min` at 0, `mid` at 5 and
+/// `max` at 10, linear in between and beyond.
+pub(crate) fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
+    if difficulty > 5.0 {
+        mid + (max - mid) * (difficulty - 5.0) / 5.0
+    } else if difficulty < 5.0 {
+        mid + (mid - min) * (difficulty - 5.0) / 5.0
+    } else {
+        mid
+    }
+}
+
+/// Hit windows in milliseconds either side of an object's time. Judgements
+/// the mode doesn't have are `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitWindows {
+    /// The rainbow 300 in mania
+    pub perfect: Option<f64>,
+    /// A 300
+    pub great: f64,
+    /// A 200 in mania
+    pub good: Option<f64>,
+    /// A 100
+    pub ok: f64,
+    /// A 50
+    pub meh: Option<f64>,
+    /// Hits any later than this are ignored instead of counted as misses
+    pub miss: f64,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Difficulty {
     hp_drain_rate: Option<f32>,
     circle_size: Option<f32>,
@@ -34,6 +92,108 @@ impl Difficulty {
     pub fn change_slider_tick_rate(&mut self, rate: f32) {
         self.slider_tick_rate = Some(rate);
     }
+
+    /// Multiplies the circle size by `circle_size` and the other settings by
+    /// `multiplier`, capping them at 10 like Hard Rock and Easy do.
+    pub(crate) fn scale(&mut self, circle_size: f32, multiplier: f32) {
+        let scale = |value: Option<f32>, multiplier: f32| {
+            Some((value.unwrap_or(DEFAULT_DIFFICULTY as f32) * multiplier).min(10.0))
+        };
+        self.circle_size = scale(self.circle_size, circle_size);
+        self.overall_difficulty = scale(self.overall_difficulty, multiplier);
+        self.hp_drain_rate = scale(self.hp_drain_rate, multiplier);
+        // A missing approach rate follows the scaled overall difficulty
+        if self.approach_rate.is_some() {
+            self.approach_rate = scale(self.approach_rate, multiplier);
+        }
+    }
+
+    /// Approach rate, falling back to the overall difficulty for older
+    /// beatmaps written before the two were split.
+    pub fn get_effective_approach_rate(&self) -> f64 {
+        self.approach_rate
+            .or(self.overall_difficulty)
+            .map_or(DEFAULT_DIFFICULTY, f64::from)
+    }
+
+    /// Slider multiplier as osu! plays it, 1.4 when missing and otherwise
+    /// kept between 0.4 and 3.6.
+    pub fn get_effective_slider_multiplier(&self) -> f64 {
+        clamp_setting(self.slider_multiplier, 1.4, SLIDER_MULTIPLIER_RANGE)
+    }
+
+    /// Slider tick rate as osu! plays it, 1 when missing and otherwise kept
+    /// between 0.5 and 8.
+    pub fn get_effective_slider_tick_rate(&self) -> f64 {
+        clamp_setting(self.slider_tick_rate, 1.0, SLIDER_TICK_RATE_RANGE)
+    }
+
+    fn overall_difficulty(&self) -> f64 {
+        self.overall_difficulty
+            .map_or(DEFAULT_DIFFICULTY, f64::from)
+    }
+
+    fn circle_size(&self) -> f64 {
+        self.circle_size.map_or(DEFAULT_DIFFICULTY, f64::from)
+    }
+
+    /// How long before its time an object appears, in milliseconds.
+    pub fn get_preempt(&self) -> f64 {
+        difficulty_range(
+            self.get_effective_approach_rate(),
+            1800.0,
+            1200.0,
+            PREEMPT_MIN,
+        )
+    }
+
+    /// How long an object takes to fade in once it appears, in milliseconds.
+    pub fn get_fade_in(&self) -> f64 {
+        400.0 * (self.get_preempt() / PREEMPT_MIN).min(1.0)
+    }
+
+    /// Hit windows for `mode`. Catch has none.
+    pub fn get_hit_windows(&self, mode: GameMode) -> Option<HitWindows> {
+        let od = self.overall_difficulty();
+        match mode {
+            GameMode::Osu => Some(HitWindows {
+                perfect: None,
+                great: difficulty_range(od, 80.0, 50.0, 20.0),
+                good: None,
+                ok: difficulty_range(od, 140.0, 100.0, 60.0),
+                meh: Some(difficulty_range(od, 200.0, 150.0, 100.0)),
+                miss: 400.0,
+            }),
+            GameMode::Taiko => Some(HitWindows {
+                perfect: None,
+                great: difficulty_range(od, 50.0, 35.0, 20.0),
+                good: None,
+                ok: difficulty_range(od, 120.0, 80.0, 50.0),
+                meh: None,
+                miss: difficulty_range(od, 135.0, 95.0, 70.0),
+            }),
+            GameMode::Mania => Some(HitWindows {
+                perfect: Some(16.0),
+                great: 64.0 - 3.0 * od,
+                good: Some(97.0 - 3.0 * od),
+                ok: 127.0 - 3.0 * od,
+                meh: Some(151.0 - 3.0 * od),
+                miss: 188.0 - 3.0 * od,
+            }),
+            GameMode::Catch | GameMode::Unknown(_) => None,
+        }
+    }
+
+    /// Circle radius in osu! pixels.
+    pub fn get_circle_radius(&self) -> f64 {
+        let scale = (1.0 - 0.7 * (self.circle_size() - 5.0) / 5.0) / 2.0;
+        OBJECT_RADIUS * scale * BROKEN_GAMEFIELD_ROUNDING_ALLOWANCE
+    }
+
+    /// Number of columns of a mania beatmap, which is stored as its circle size.
+    pub fn get_key_count(&self) -> i32 {
+        (self.circle_size().round() as i32).max(1)
+    }
 }
 
 impl std::fmt::Display for Difficulty {
@@ -116,58 +276,207 @@ impl Beatmap {
     pub fn get_slider_tick_rate(&self) -> Option<f32> {
         self.difficulty.slider_tick_rate
     }
-}
 
-impl Default for Difficulty {
-    fn default() -> Self {
-        Difficulty {
-            hp_drain_rate: None,
-            circle_size: None,
-            overall_difficulty: None,
-            approach_rate: None,
-            slider_multiplier: None,
-            slider_tick_rate: None,
-        }
+    pub fn get_effective_approach_rate(&self) -> f64 {
+        self.difficulty.get_effective_approach_rate()
+    }
+
+    pub fn get_effective_slider_multiplier(&self) -> f64 {
+        self.difficulty.get_effective_slider_multiplier()
+    }
+
+    pub fn get_effective_slider_tick_rate(&self) -> f64 {
+        self.difficulty.get_effective_slider_tick_rate()
+    }
+
+    pub fn get_preempt(&self) -> f64 {
+        self.difficulty.get_preempt()
+    }
+
+    pub fn get_fade_in(&self) -> f64 {
+        self.difficulty.get_fade_in()
+    }
+
+    /// Hit windows for the beatmap's own mode.
+    pub fn get_hit_windows(&self) -> Option<HitWindows> {
+        self.difficulty
+            .get_hit_windows(self.get_general_mode().unwrap_or_default())
+    }
+
+    pub fn get_circle_radius(&self) -> f64 {
+        self.difficulty.get_circle_radius()
+    }
+
+    pub fn get_key_count(&self) -> i32 {
+        self.difficulty.get_key_count()
     }
 }
 
 impl Difficulty {
-    pub fn parse_value(&mut self, value: &str) {
-        let parts: Vec<&str> = value.split(':').map(|s| s.trim()).collect();
-        let value = parts[1];
-        match parts[0] {
+    pub fn parse_value(&mut self, value: &str) -> Result<()> {
+        let (key, value) = value.split_once(':').ok_or(Error::difficulty(0))?;
+        let value = value.trim();
+        match key.trim() {
             "HPDrainRate" => {
-                if let Ok(n) = value.parse() {
-                    self.hp_drain_rate = Some(n);
-                }
+                self.hp_drain_rate = Some(value.parse().map_err(|_| Error::difficulty(1))?);
             }
             "CircleSize" => {
-                if let Ok(n) = value.parse() {
-                    self.circle_size = Some(n);
-                }
+                self.circle_size = Some(value.parse().map_err(|_| Error::difficulty(1))?);
             }
             "OverallDifficulty" => {
-                if let Ok(n) = value.parse() {
-                    self.overall_difficulty = Some(n);
-                }
+                self.overall_difficulty = Some(value.parse().map_err(|_| Error::difficulty(1))?);
             }
             "ApproachRate" => {
-                if let Ok(n) = value.parse() {
-                    self.approach_rate = Some(n);
-                }
+                self.approach_rate = Some(value.parse().map_err(|_| Error::difficulty(1))?);
             }
             "SliderMultiplier" => {
-                if let Ok(n) = value.parse() {
-                    self.slider_multiplier = Some(n);
-                }
+                self.slider_multiplier = Some(value.parse().map_err(|_| Error::difficulty(1))?);
             }
             "SliderTickRate" => {
-                if let Ok(n) = value.parse() {
-                    self.slider_tick_rate = Some(n);
-                }
+                self.slider_tick_rate = Some(value.parse().map_err(|_| Error::difficulty(1))?);
             }
 
             _ => {}
         }
+
+        Ok(())
+    }
+}
+
+/// Builds the `[Difficulty]` section of a [`BeatmapBuilder`](crate::BeatmapBuilder).
+///
+/// Starts from the values the osu! editor writes for a new beatmap.
+#[derive(Debug)]
+pub struct DifficultyBuilder {
+    difficulty: Difficulty,
+}
+
+impl Default for DifficultyBuilder {
+    fn default() -> Self {
+        DifficultyBuilder {
+            difficulty: Difficulty {
+                hp_drain_rate: Some(5.0),
+                circle_size: Some(5.0),
+                overall_difficulty: Some(5.0),
+                approach_rate: Some(5.0),
+                slider_multiplier: Some(1.4),
+                slider_tick_rate: Some(1.0),
+            },
+        }
+    }
+}
+
+impl DifficultyBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn hp_drain_rate(mut self, rate: f32) -> Self {
+        self.difficulty.change_hp_drain_rate(rate);
+        self
+    }
+
+    pub fn circle_size(mut self, size: f32) -> Self {
+        self.difficulty.change_circle_size(size);
+        self
+    }
+
+    pub fn overall_difficulty(mut self, difficulty: f32) -> Self {
+        self.difficulty.change_overall_difficulty(difficulty);
+        self
+    }
+
+    pub fn approach_rate(mut self, rate: f32) -> Self {
+        self.difficulty.change_approach_rate(rate);
+        self
+    }
+
+    pub fn slider_multiplier(mut self, multiplier: f32) -> Self {
+        self.difficulty.change_slider_multiplier(multiplier);
+        self
+    }
+
+    pub fn slider_tick_rate(mut self, rate: f32) -> Self {
+        self.difficulty.change_slider_tick_rate(rate);
+        self
+    }
+
+    pub(crate) fn build(self) -> Result<Difficulty> {
+        let difficulty = self.difficulty;
+
+        let ranges = [
+            ("HPDrainRate", difficulty.hp_drain_rate, 0.0, 10.0),
+            ("CircleSize", difficulty.circle_size, 0.0, 10.0),
+            (
+                "OverallDifficulty",
+                difficulty.overall_difficulty,
+                0.0,
+                10.0,
+            ),
+            ("ApproachRate", difficulty.approach_rate, 0.0, 10.0),
+            (
+                "SliderMultiplier",
+                difficulty.slider_multiplier,
+                SLIDER_MULTIPLIER_RANGE.0,
+                SLIDER_MULTIPLIER_RANGE.1,
+            ),
+            (
+                "SliderTickRate",
+                difficulty.slider_tick_rate,
+                SLIDER_TICK_RATE_RANGE.0,
+                SLIDER_TICK_RATE_RANGE.1,
+            ),
+        ];
+        for (key, value, min, max) in ranges {
+            ensure(
+                value.is_some_and(|value| (min..=max).contains(&value)),
+                format!("{} must be between {} and {}", key, min, max),
+            )?;
+        }
+
+        Ok(difficulty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn difficulty(values: &[&str]) -> Difficulty {
+        let mut difficulty = Difficulty::default();
+        for value in values {
+            difficulty.parse_value(value).unwrap();
+        }
+        difficulty
+    }
+
+    #[test]
+    fn test_conversions() {
+        let difficulty = difficulty(&["ApproachRate:9", "OverallDifficulty:8", "CircleSize:4"]);
+        assert_eq!(difficulty.get_preempt(), 600.0);
+        assert_eq!(difficulty.get_fade_in(), 400.0);
+        assert!((difficulty.get_circle_radius() - 36.48).abs() < 0.02);
+        assert_eq!(difficulty.get_key_count(), 4);
+
+        let windows = difficulty.get_hit_windows(GameMode::Osu).unwrap();
+        assert_eq!((windows.great, windows.ok), (32.0, 76.0));
+        assert_eq!(windows.meh, Some(120.0));
+        let windows = difficulty.get_hit_windows(GameMode::Mania).unwrap();
+        assert_eq!((windows.perfect, windows.great), (Some(16.0), 40.0));
+        assert!(difficulty.get_hit_windows(GameMode::Catch).is_none());
+
+        // Older beatmaps have no approach rate
+        let difficulty = self::difficulty(&["OverallDifficulty:10"]);
+        assert_eq!(difficulty.get_preempt(), 450.0);
+        assert_eq!(difficulty.get_fade_in(), 400.0);
+        let difficulty = self::difficulty(&["ApproachRate:0"]);
+        assert_eq!(difficulty.get_preempt(), 1800.0);
+
+        let difficulty = self::difficulty(&["SliderMultiplier:0.1", "SliderTickRate:100000000"]);
+        assert_eq!(difficulty.get_effective_slider_multiplier(), 0.4f32 as f64);
+        assert_eq!(difficulty.get_effective_slider_tick_rate(), 8.0);
+        let difficulty = self::difficulty(&["SliderTickRate:NaN"]);
+        assert_eq!(difficulty.get_effective_slider_multiplier(), 1.4);
+        assert_eq!(difficulty.get_effective_slider_tick_rate(), 1.0);
     }
 }
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use crate::{
+    error::{Error, Result},
+    utils::scale_time,
+};
+
+#[derive(Debug, Clone, Default)]
 pub struct Editor {
     bookmarks: Option<Vec<i32>>,
     distance_spacing: Option<f32>,
@@ -24,7 +29,7 @@ impl std::fmt::Display for Editor {
                     display_string.push_str(&format!("{},", bookmark));
                 }
             }
-            display_string.push_str("\n");
+            display_string.push('\n');
         }
 
         if let Some(distance_spacing) = &self.distance_spacing {
@@ -44,55 +49,35 @@ impl std::fmt::Display for Editor {
     }
 }
 
-impl Default for Editor {
-    fn default() -> Self {
-        Editor {
-            bookmarks: None,
-            distance_spacing: None,
-            beat_divisor: None,
-            grid_size: None,
-            timeline_zoom: None,
-        }
-    }
-}
-
 impl Editor {
-    pub fn parse_value(&mut self, value: &str) {
-        let parts: Vec<&str> = value.split(':').map(|s| s.trim()).collect();
-        let value = parts[1];
+    pub fn parse_value(&mut self, value: &str) -> Result<()> {
+        let (key, value) = value.split_once(':').ok_or(Error::editor(0))?;
+        let value = value.trim();
 
-        match parts[0] {
+        match key.trim() {
             "Bookmarks" => {
                 let mut bookmarks = Vec::new();
-                for b in value.split(',') {
-                    if let Ok(n) = b.parse() {
-                        bookmarks.push(n);
-                    }
+                for b in value.split(',').map(|b| b.trim()).filter(|b| !b.is_empty()) {
+                    bookmarks.push(b.parse().map_err(|_| Error::editor(1))?);
                 }
                 self.bookmarks = Some(bookmarks);
             }
             "DistanceSpacing" => {
-                if let Ok(n) = value.parse() {
-                    self.distance_spacing = Some(n);
-                }
+                self.distance_spacing = Some(value.parse().map_err(|_| Error::editor(1))?);
             }
             "BeatDivisor" => {
-                if let Ok(n) = value.parse() {
-                    self.beat_divisor = Some(n);
-                }
+                self.beat_divisor = Some(value.parse().map_err(|_| Error::editor(1))?);
             }
             "GridSize" => {
-                if let Ok(n) = value.parse() {
-                    self.grid_size = Some(n);
-                }
+                self.grid_size = Some(value.parse().map_err(|_| Error::editor(1))?);
             }
             "TimelineZoom" => {
-                if let Ok(n) = value.parse() {
-                    self.timeline_zoom = Some(n);
-                }
+                self.timeline_zoom = Some(value.parse().map_err(|_| Error::editor(1))?);
             }
             _ => {}
         }
+
+        Ok(())
     }
 
     pub fn is_empty(&self) -> bool {
@@ -102,4 +87,10 @@ impl Editor {
             && self.grid_size.is_none()
             && self.timeline_zoom.is_none()
     }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        for bookmark in self.bookmarks.iter_mut().flatten() {
+            *bookmark = scale_time(*bookmark, rate);
+        }
+    }
 }
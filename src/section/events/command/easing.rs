@@ -0,0 +1,305 @@
+/// Easing curve of a storyboard command
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Decelerates, the same curve as `QuadOut`
+    Out,
+    /// Accelerates, the same curve as `QuadIn`
+    In,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticHalfOut,
+    ElasticQuarterOut,
+    ElasticInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    /// An easing this crate doesn't know, kept so it is written back as is. It eases linearly
+    Unknown(i32),
+}
+
+impl Easing {
+    /// Eases `progress` from 0 to 1 along the curve. Some curves overshoot,
+    /// so the result can leave the 0 to 1 range in between.
+    pub fn apply(self, progress: f64) -> f64 {
+        use std::f64::consts::PI;
+
+        const ELASTIC: f64 = 2.0 * PI / 0.3;
+        const ELASTIC_PHASE: f64 = 0.3 / 4.0;
+        const BACK: f64 = 1.70158;
+        const BACK_IN_OUT: f64 = BACK * 1.525;
+
+        // Offsets that make the expo and elastic curves start at 0 and end at 1
+        let expo_offset = 2f64.powi(-10);
+        let elastic_offset = 2f64.powi(-11);
+        let elastic_half_offset = 2f64.powi(-10) * ((0.5 - ELASTIC_PHASE) * ELASTIC).sin();
+        let elastic_quarter_offset = 2f64.powi(-10) * ((0.25 - ELASTIC_PHASE) * ELASTIC).sin();
+        let elastic_in_out_offset =
+            2f64.powi(-10) * ((1.0 - ELASTIC_PHASE * 1.5) * ELASTIC / 1.5).sin();
+
+        let t = progress;
+        match self {
+            Easing::Linear | Easing::Unknown(_) => t,
+            Easing::In | Easing::QuadIn => t * t,
+            Easing::Out | Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => in_out(t, |t| t * t),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => out(t, |t| t.powi(3)),
+            Easing::CubicInOut => in_out(t, |t| t.powi(3)),
+            Easing::QuartIn => t.powi(4),
+            Easing::QuartOut => out(t, |t| t.powi(4)),
+            Easing::QuartInOut => in_out(t, |t| t.powi(4)),
+            Easing::QuintIn => t.powi(5),
+            Easing::QuintOut => out(t, |t| t.powi(5)),
+            Easing::QuintInOut => in_out(t, |t| t.powi(5)),
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => 0.5 - 0.5 * (t * PI).cos(),
+            Easing::ExpoIn => 2f64.powf(10.0 * (t - 1.0)) + expo_offset * (t - 1.0),
+            Easing::ExpoOut => 1.0 - 2f64.powf(-10.0 * t) + expo_offset * t,
+            Easing::ExpoInOut => {
+                if t < 0.5 {
+                    0.5 * (2f64.powf(20.0 * t - 10.0) + expo_offset * (2.0 * t - 1.0))
+                } else {
+                    1.0 - 0.5 * (2f64.powf(-20.0 * t + 10.0) + expo_offset * (1.0 - 2.0 * t))
+                }
+            }
+            Easing::CircIn => 1.0 - (1.0 - t * t).sqrt(),
+            Easing::CircOut => (1.0 - (t - 1.0) * (t - 1.0)).sqrt(),
+            Easing::CircInOut => {
+                let t = t * 2.0;
+                if t < 1.0 {
+                    0.5 - 0.5 * (1.0 - t * t).sqrt()
+                } else {
+                    0.5 * (1.0 - (t - 2.0) * (t - 2.0)).sqrt() + 0.5
+                }
+            }
+            Easing::ElasticIn => {
+                -2f64.powf(10.0 * t - 10.0) * ((1.0 - ELASTIC_PHASE - t) * ELASTIC).sin()
+                    + elastic_offset * (1.0 - t)
+            }
+            Easing::ElasticOut => {
+                2f64.powf(-10.0 * t) * ((t - ELASTIC_PHASE) * ELASTIC).sin() + 1.0
+                    - elastic_offset * t
+            }
+            Easing::ElasticHalfOut => {
+                2f64.powf(-10.0 * t) * ((0.5 * t - ELASTIC_PHASE) * ELASTIC).sin() + 1.0
+                    - elastic_half_offset * t
+            }
+            Easing::ElasticQuarterOut => {
+                2f64.powf(-10.0 * t) * ((0.25 * t - ELASTIC_PHASE) * ELASTIC).sin() + 1.0
+                    - elastic_quarter_offset * t
+            }
+            Easing::ElasticInOut => {
+                let t = t * 2.0;
+                if t < 1.0 {
+                    -0.5 * (2f64.powf(10.0 * t - 10.0)
+                        * ((1.0 - ELASTIC_PHASE * 1.5 - t) * ELASTIC / 1.5).sin()
+                        - elastic_in_out_offset * (1.0 - t))
+                } else {
+                    let t = t - 1.0;
+                    0.5 * (2f64.powf(-10.0 * t) * ((t - ELASTIC_PHASE * 1.5) * ELASTIC / 1.5).sin()
+                        - elastic_in_out_offset * t)
+                        + 1.0
+                }
+            }
+            Easing::BackIn => t * t * ((BACK + 1.0) * t - BACK),
+            Easing::BackOut => out(t, |t| t * t * ((BACK + 1.0) * t - BACK)),
+            Easing::BackInOut => {
+                let t = t * 2.0;
+                if t < 1.0 {
+                    0.5 * t * t * ((BACK_IN_OUT + 1.0) * t - BACK_IN_OUT)
+                } else {
+                    let t = t - 2.0;
+                    0.5 * (t * t * ((BACK_IN_OUT + 1.0) * t + BACK_IN_OUT) + 2.0)
+                }
+            }
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => {
+                if t < 0.5 {
+                    0.5 - 0.5 * bounce_out(1.0 - t * 2.0)
+                } else {
+                    bounce_out((t - 0.5) * 2.0) * 0.5 + 0.5
+                }
+            }
+        }
+    }
+}
+
+/// The mirror image of an ease in curve.
+fn out(t: f64, ease_in: impl Fn(f64) -> f64) -> f64 {
+    1.0 - ease_in(1.0 - t)
+}
+
+/// Eases in for the first half and out for the second.
+fn in_out(t: f64, ease_in: impl Fn(f64) -> f64) -> f64 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+fn bounce_out(t: f64) -> f64 {
+    const BOUNCE: f64 = 1.0 / 2.75;
+
+    if t < BOUNCE {
+        7.5625 * t * t
+    } else if t < 2.0 * BOUNCE {
+        let t = t - 1.5 * BOUNCE;
+        7.5625 * t * t + 0.75
+    } else if t < 2.5 * BOUNCE {
+        let t = t - 2.25 * BOUNCE;
+        7.5625 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 * BOUNCE;
+        7.5625 * t * t + 0.984375
+    }
+}
+
+impl From<i32> for Easing {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Easing::Linear,
+            1 => Easing::Out,
+            2 => Easing::In,
+            3 => Easing::QuadIn,
+            4 => Easing::QuadOut,
+            5 => Easing::QuadInOut,
+            6 => Easing::CubicIn,
+            7 => Easing::CubicOut,
+            8 => Easing::CubicInOut,
+            9 => Easing::QuartIn,
+            10 => Easing::QuartOut,
+            11 => Easing::QuartInOut,
+            12 => Easing::QuintIn,
+            13 => Easing::QuintOut,
+            14 => Easing::QuintInOut,
+            15 => Easing::SineIn,
+            16 => Easing::SineOut,
+            17 => Easing::SineInOut,
+            18 => Easing::ExpoIn,
+            19 => Easing::ExpoOut,
+            20 => Easing::ExpoInOut,
+            21 => Easing::CircIn,
+            22 => Easing::CircOut,
+            23 => Easing::CircInOut,
+            24 => Easing::ElasticIn,
+            25 => Easing::ElasticOut,
+            26 => Easing::ElasticHalfOut,
+            27 => Easing::ElasticQuarterOut,
+            28 => Easing::ElasticInOut,
+            29 => Easing::BackIn,
+            30 => Easing::BackOut,
+            31 => Easing::BackInOut,
+            32 => Easing::BounceIn,
+            33 => Easing::BounceOut,
+            34 => Easing::BounceInOut,
+            value => Easing::Unknown(value),
+        }
+    }
+}
+
+impl From<Easing> for i32 {
+    fn from(value: Easing) -> Self {
+        match value {
+            Easing::Linear => 0,
+            Easing::Out => 1,
+            Easing::In => 2,
+            Easing::QuadIn => 3,
+            Easing::QuadOut => 4,
+            Easing::QuadInOut => 5,
+            Easing::CubicIn => 6,
+            Easing::CubicOut => 7,
+            Easing::CubicInOut => 8,
+            Easing::QuartIn => 9,
+            Easing::QuartOut => 10,
+            Easing::QuartInOut => 11,
+            Easing::QuintIn => 12,
+            Easing::QuintOut => 13,
+            Easing::QuintInOut => 14,
+            Easing::SineIn => 15,
+            Easing::SineOut => 16,
+            Easing::SineInOut => 17,
+            Easing::ExpoIn => 18,
+            Easing::ExpoOut => 19,
+            Easing::ExpoInOut => 20,
+            Easing::CircIn => 21,
+            Easing::CircOut => 22,
+            Easing::CircInOut => 23,
+            Easing::ElasticIn => 24,
+            Easing::ElasticOut => 25,
+            Easing::ElasticHalfOut => 26,
+            Easing::ElasticQuarterOut => 27,
+            Easing::ElasticInOut => 28,
+            Easing::BackIn => 29,
+            Easing::BackOut => 30,
+            Easing::BackInOut => 31,
+            Easing::BounceIn => 32,
+            Easing::BounceOut => 33,
+            Easing::BounceInOut => 34,
+            Easing::Unknown(value) => value,
+        }
+    }
+}
+
+impl std::str::FromStr for Easing {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse::<i32>().map(Easing::from)
+    }
+}
+
+impl std::fmt::Display for Easing {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing() {
+        for value in 0..35 {
+            let easing = Easing::from(value);
+            assert_ne!(easing, Easing::Unknown(value));
+            assert!(easing.apply(0.0).abs() < 1e-9, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-9, "{:?}", easing);
+        }
+
+        assert_eq!(Easing::from(35), Easing::Unknown(35));
+        assert_eq!("35".parse::<Easing>().unwrap().to_string(), "35");
+        assert_eq!(Easing::QuadInOut.apply(0.25), 0.125);
+        assert_eq!(Easing::QuadIn.apply(0.5), Easing::In.apply(0.5));
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+    }
+}
@@ -1,6 +1,10 @@
-#[derive(Debug)]
+use crate::{error::Result, utils::scale_time};
+
+use super::{parse_field, parse_timing, value_indices, Easing};
+
+#[derive(Debug, Clone)]
 pub struct FadeCommand {
-    easing: i32,
+    easing: Easing,
     start_time: i32,
     end_time: i32,
     start_opacity: f32,
@@ -8,82 +12,40 @@ pub struct FadeCommand {
 }
 
 impl FadeCommand {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        let easing = match s[0].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Command token: {}", s[0]),
-                ))
-            }
-        };
-
-        let start_time = match s[1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Command token: {}", s[1]),
-                ))
-            }
-        };
-
-        let has_end_opacity = (s.len() == 4 && s[s.len() - 2].is_empty()) || s.len() == 5;
-
-        let end_opacity = match s[s.len() - 1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Command token: {}", s[s.len() - 1]),
-                ))
-            }
-        };
-
-        let start_opacity = if s[s.len() - 2].is_empty() || !has_end_opacity {
-            end_opacity
-        } else {
-            match s[s.len() - 2].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Command token: {}", s[s.len() - 2]),
-                    ))
-                }
-            }
-        };
-
-        let end_time = if s.len() == 4 && has_end_opacity {
-            start_time
-        } else {
-            match s[2].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Command token: {}", s[2]),
-                    ))
-                }
-            }
-        };
+    pub fn parse(s: &[&str]) -> Result<Self> {
+        let (easing, start_time, end_time) = parse_timing(s)?;
+        let (start, end) = value_indices(s, 1)?;
 
         Ok(FadeCommand {
             easing,
             start_time,
             end_time,
-            start_opacity,
-            end_opacity,
+            start_opacity: parse_field(s, start)?,
+            end_opacity: parse_field(s, end)?,
         })
     }
 }
 
+impl FadeCommand {
+    pub fn get_easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn change_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.start_time = scale_time(self.start_time, rate);
+        self.end_time = scale_time(self.end_time, rate);
+    }
+}
+
 impl std::fmt::Display for FadeCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
+        writeln!(
             f,
-            "F,{},{},{},{},{}\n",
+            "F,{},{},{},{},{}",
             self.easing, self.start_time, self.end_time, self.start_opacity, self.end_opacity
         )
     }
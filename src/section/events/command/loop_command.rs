@@ -1,22 +1,52 @@
-#[derive(Debug)]
+use crate::{error::Result, utils::scale_time};
+
+use super::{indented, parse_field, Command};
+
+#[derive(Debug, Clone)]
 pub struct LoopCommand {
     start_time: i32,
     loop_count: i32,
+    commands: Vec<Command>,
 }
 
 impl std::fmt::Display for LoopCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "L,{},{}\n", self.start_time, self.loop_count)
+        let mut display_string = format!("L,{},{}\n", self.start_time, self.loop_count);
+
+        for command in &self.commands {
+            display_string += &indented(command);
+        }
+
+        write!(f, "{}", display_string)
     }
 }
 
 impl LoopCommand {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        let start_time = s[0].parse().unwrap();
-        let loop_count = s[1].parse().unwrap();
+    pub fn parse(s: &[&str]) -> Result<Self> {
         Ok(LoopCommand {
-            start_time,
-            loop_count,
+            start_time: parse_field(s, 0)?,
+            loop_count: parse_field(s, 1)?,
+            commands: Vec::new(),
         })
     }
+
+    /// Commands repeated on every iteration, timed relative to the loop start.
+    pub fn get_commands(&self) -> &Vec<Command> {
+        &self.commands
+    }
+
+    pub fn get_commands_mut(&mut self) -> &mut Vec<Command> {
+        &mut self.commands
+    }
+
+    pub fn add_command(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.start_time = scale_time(self.start_time, rate);
+        for command in self.commands.iter_mut() {
+            command.scale_times(rate);
+        }
+    }
 }
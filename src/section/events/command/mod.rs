@@ -1,4 +1,5 @@
 mod color_command;
+mod easing;
 mod fade_command;
 mod loop_command;
 mod move_command;
@@ -10,6 +11,10 @@ mod scale_command;
 mod trigger_command;
 mod vector_scale_command;
 
+use crate::error::{Error, Result};
+
+pub use easing::Easing;
+
 use color_command::ColorCommand;
 use fade_command::FadeCommand;
 use loop_command::LoopCommand;
@@ -22,7 +27,51 @@ use scale_command::ScaleCommand;
 use trigger_command::TriggerCommand;
 use vector_scale_command::VectorScaleCommand;
 
-#[derive(Debug)]
+/// Parses the value at `index` of a command's arguments, the arguments
+/// starting after the command's identifier.
+fn parse_field<T: std::str::FromStr>(s: &[&str], index: usize) -> Result<T> {
+    s.get(index)
+        .and_then(|value| value.parse().ok())
+        .ok_or(Error::command(index + 1))
+}
+
+/// Parses the `easing,start_time,end_time` arguments shared by every
+/// transform command. An empty end time means the command ends as it starts.
+fn parse_timing(s: &[&str]) -> Result<(Easing, i32, i32)> {
+    let easing = parse_field(s, 0)?;
+    let start_time = parse_field(s, 1)?;
+    let end_time = match s.get(2) {
+        Some(&"") => start_time,
+        _ => parse_field(s, 2)?,
+    };
+
+    Ok((easing, start_time, end_time))
+}
+
+/// Returns the indices of the start and end values of a transform command
+/// made of `count` values each. A single set of values is used for both.
+fn value_indices(s: &[&str], count: usize) -> Result<(usize, usize)> {
+    let values = s.len().saturating_sub(3);
+    if values == count {
+        Ok((3, 3))
+    } else if values == count * 2 {
+        Ok((3, 3 + count))
+    } else {
+        Err(Error::command(s.len().max(3)))
+    }
+}
+
+/// Renders a command one level deeper than its parent, so nested loops and
+/// triggers pick up an extra space per level.
+pub(crate) fn indented(command: &Command) -> String {
+    command
+        .to_string()
+        .lines()
+        .map(|line| format!(" {}\n", line))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
 pub enum Command {
     Fade(FadeCommand),
     Scale(ScaleCommand),
@@ -38,7 +87,7 @@ pub enum Command {
 }
 
 impl Command {
-    pub fn parse(s: &str) -> std::io::Result<Self> {
+    pub fn parse(s: &str) -> Result<Self> {
         let parts = s.split(",").collect::<Vec<&str>>();
         match parts[0] {
             "F" => Ok(Command::Fade(FadeCommand::parse(&parts[1..])?)),
@@ -54,10 +103,74 @@ impl Command {
             "P" => Ok(Command::Parameter(ParameterCommand::parse(&parts[1..])?)),
             "L" => Ok(Command::Loop(LoopCommand::parse(&parts[1..])?)),
             "T" => Ok(Command::Trigger(TriggerCommand::parse(&parts[1..])?)),
-            _ => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Invalid Command token: {}", parts[0]),
-            )),
+            _ => Err(Error::command(0)),
+        }
+    }
+
+    /// The commands nested inside a loop or trigger.
+    pub fn get_commands(&self) -> Option<&Vec<Command>> {
+        match self {
+            Command::Loop(loop_command) => Some(loop_command.get_commands()),
+            Command::Trigger(trigger_command) => Some(trigger_command.get_commands()),
+            _ => None,
+        }
+    }
+
+    /// The easing of a transform command, `None` for loops and triggers.
+    pub fn get_easing(&self) -> Option<Easing> {
+        match self {
+            Command::Fade(c) => Some(c.get_easing()),
+            Command::Scale(c) => Some(c.get_easing()),
+            Command::VectorScale(c) => Some(c.get_easing()),
+            Command::Rotate(c) => Some(c.get_easing()),
+            Command::Move(c) => Some(c.get_easing()),
+            Command::MoveX(c) => Some(c.get_easing()),
+            Command::MoveY(c) => Some(c.get_easing()),
+            Command::Color(c) => Some(c.get_easing()),
+            Command::Parameter(c) => Some(c.get_easing()),
+            Command::Loop(_) | Command::Trigger(_) => None,
+        }
+    }
+
+    /// Loops and triggers have no easing and are left as they are.
+    pub fn change_easing(&mut self, easing: Easing) {
+        match self {
+            Command::Fade(c) => c.change_easing(easing),
+            Command::Scale(c) => c.change_easing(easing),
+            Command::VectorScale(c) => c.change_easing(easing),
+            Command::Rotate(c) => c.change_easing(easing),
+            Command::Move(c) => c.change_easing(easing),
+            Command::MoveX(c) => c.change_easing(easing),
+            Command::MoveY(c) => c.change_easing(easing),
+            Command::Color(c) => c.change_easing(easing),
+            Command::Parameter(c) => c.change_easing(easing),
+            Command::Loop(_) | Command::Trigger(_) => {}
+        }
+    }
+
+    pub fn get_commands_mut(&mut self) -> Option<&mut Vec<Command>> {
+        match self {
+            Command::Loop(loop_command) => Some(loop_command.get_commands_mut()),
+            Command::Trigger(trigger_command) => Some(trigger_command.get_commands_mut()),
+            _ => None,
+        }
+    }
+
+    /// Divides every time by `rate`, including those of nested commands,
+    /// which stay relative to their loop or trigger.
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        match self {
+            Command::Fade(c) => c.scale_times(rate),
+            Command::Scale(c) => c.scale_times(rate),
+            Command::VectorScale(c) => c.scale_times(rate),
+            Command::Rotate(c) => c.scale_times(rate),
+            Command::Move(c) => c.scale_times(rate),
+            Command::MoveX(c) => c.scale_times(rate),
+            Command::MoveY(c) => c.scale_times(rate),
+            Command::Color(c) => c.scale_times(rate),
+            Command::Parameter(c) => c.scale_times(rate),
+            Command::Loop(c) => c.scale_times(rate),
+            Command::Trigger(c) => c.scale_times(rate),
         }
     }
 }
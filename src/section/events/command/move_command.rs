@@ -1,6 +1,10 @@
-#[derive(Debug)]
+use crate::{error::Result, utils::scale_time};
+
+use super::{parse_field, parse_timing, value_indices, Easing};
+
+#[derive(Debug, Clone)]
 pub struct MoveCommand {
-    easing: i32,
+    easing: Easing,
     start_time: i32,
     end_time: i32,
     start_x: f32,
@@ -9,11 +13,26 @@ pub struct MoveCommand {
     end_y: f32,
 }
 
+impl MoveCommand {
+    pub fn get_easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn change_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.start_time = scale_time(self.start_time, rate);
+        self.end_time = scale_time(self.end_time, rate);
+    }
+}
+
 impl std::fmt::Display for MoveCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
+        writeln!(
             f,
-            "M,{},{},{},{},{},{},{}\n",
+            "M,{},{},{},{},{},{},{}",
             self.easing,
             self.start_time,
             self.end_time,
@@ -26,107 +45,18 @@ impl std::fmt::Display for MoveCommand {
 }
 
 impl MoveCommand {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        let start_x;
-        let start_y;
-        let end_time;
-
-        let easing = match s[0].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Command token: {}", s[0]),
-                ))
-            }
-        };
-
-        let start_time = match s[1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Command token: {}", s[1]),
-                ))
-            }
-        };
-
-        let end_index = s.len() - 1;
-
-        let end_x = match s[end_index - 1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Command token: {}", s[end_index - 2]),
-                ))
-            }
-        };
-
-        let end_y = match s[end_index].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Command token: {}", s[end_index]),
-                ))
-            }
-        };
-
-        if s[end_index - 2].is_empty() && end_index - 3 == 1 {
-            start_x = end_x;
-            start_y = end_y;
-            end_time = start_time;
-        } else if s[end_index - 2].is_empty() {
-            start_y = end_y;
-            end_time = start_time;
-            start_x = match s[end_index - 3].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Command token: {}", s[end_index - 4]),
-                    ))
-                }
-            }
-        } else {
-            end_time = match s[2].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Command token: {}", s[end_index - 1]),
-                    ))
-                }
-            };
-            start_y = match s[end_index - 2].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Command token: {}", s[end_index - 2]),
-                    ))
-                }
-            };
-            start_x = match s[end_index - 3].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Command token: {}", s[end_index - 3]),
-                    ))
-                }
-            };
-        }
+    pub fn parse(s: &[&str]) -> Result<Self> {
+        let (easing, start_time, end_time) = parse_timing(s)?;
+        let (start, end) = value_indices(s, 2)?;
 
         Ok(MoveCommand {
             easing,
             start_time,
             end_time,
-            start_x,
-            start_y,
-            end_x,
-            end_y,
+            start_x: parse_field(s, start)?,
+            start_y: parse_field(s, start + 1)?,
+            end_x: parse_field(s, end)?,
+            end_y: parse_field(s, end + 1)?,
         })
     }
 }
@@ -1,90 +1,52 @@
-#[derive(Debug)]
+use crate::{error::Result, utils::scale_time};
+
+use super::{parse_field, parse_timing, value_indices, Easing};
+
+#[derive(Debug, Clone)]
 pub struct MoveYCommand {
-    easing: i32,
+    easing: Easing,
     start_time: i32,
     end_time: i32,
-    start_y: i32,
-    end_y: i32,
+    start_y: f32,
+    end_y: f32,
+}
+
+impl MoveYCommand {
+    pub fn get_easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn change_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.start_time = scale_time(self.start_time, rate);
+        self.end_time = scale_time(self.end_time, rate);
+    }
 }
 
 impl std::fmt::Display for MoveYCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
+        writeln!(
             f,
-            "MY,{},{},{},{},{}\n",
+            "MY,{},{},{},{},{}",
             self.easing, self.start_time, self.end_time, self.start_y, self.end_y
         )
     }
 }
 
 impl MoveYCommand {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        let easing = match s[0].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid MoveX Command token: {}", s[0]),
-                ))
-            }
-        };
-
-        let start_time = match s[1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid MoveX Command token: {}", s[1]),
-                ))
-            }
-        };
-
-        let has_end_position = (s.len() == 4 && s[s.len() - 2].is_empty()) || s.len() == 5;
-
-        let end_y = match s[s.len() - 1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid MoveX Command token: {}", s[4]),
-                ))
-            }
-        };
-
-        let start_y = if s[s.len() - 2].is_empty() || !has_end_position {
-            end_y
-        } else {
-            match s[s.len() - 2].parse() {
-                Ok(y) => y,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid MoveX Command token: {}", s[3]),
-                    ))
-                }
-            }
-        };
-
-        let end_time = if s.len() == 4 && has_end_position {
-            start_time
-        } else {
-            match s[2].parse() {
-                Ok(y) => y,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid MoveX Command token: {}", s[2]),
-                    ))
-                }
-            }
-        };
+    pub fn parse(s: &[&str]) -> Result<Self> {
+        let (easing, start_time, end_time) = parse_timing(s)?;
+        let (start, end) = value_indices(s, 1)?;
 
         Ok(MoveYCommand {
             easing,
             start_time,
             end_time,
-            start_y,
-            end_y,
+            start_y: parse_field(s, start)?,
+            end_y: parse_field(s, end)?,
         })
     }
 }
@@ -1,4 +1,8 @@
-#[derive(Debug)]
+use crate::{error::Result, utils::scale_time};
+
+use super::{parse_timing, value_indices, Easing};
+
+#[derive(Debug, Clone)]
 pub enum Effect {
     Additive,
     HorizontalFlip,
@@ -29,70 +33,49 @@ impl From<&str> for Effect {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParameterCommand {
-    easing: i32,
+    easing: Easing,
     start_time: i32,
     end_time: i32,
     parameter_type: Effect,
 }
 
+impl ParameterCommand {
+    pub fn get_easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn change_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.start_time = scale_time(self.start_time, rate);
+        self.end_time = scale_time(self.end_time, rate);
+    }
+}
+
 impl std::fmt::Display for ParameterCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
+        writeln!(
             f,
-            "P,{},{},{},{}\n",
+            "P,{},{},{},{}",
             self.easing, self.start_time, self.end_time, self.parameter_type
         )
     }
 }
 
 impl ParameterCommand {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        if s.len() < 4 {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Invalid length of {}, needs 4", s.len()),
-            ));
-        }
-
-        let easing = match s[0].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Parameter Command token: {}", s[0]),
-                ))
-            }
-        };
-
-        let start_time = match s[1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Parameter Command token: {}", s[1]),
-                ))
-            }
-        };
-
-        let end_time = match s[2].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Parameter Command token: {}", s[2]),
-                ))
-            }
-        };
-
-        let parameter_type = Effect::from(s[3]);
+    pub fn parse(s: &[&str]) -> Result<Self> {
+        let (easing, start_time, end_time) = parse_timing(s)?;
+        let (start, _) = value_indices(s, 1)?;
 
         Ok(ParameterCommand {
             easing,
             start_time,
             end_time,
-            parameter_type,
+            parameter_type: Effect::from(s[start]),
         })
     }
 }
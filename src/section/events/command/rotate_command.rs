@@ -1,90 +1,52 @@
-#[derive(Debug)]
+use crate::{error::Result, utils::scale_time};
+
+use super::{parse_field, parse_timing, value_indices, Easing};
+
+#[derive(Debug, Clone)]
 pub struct RotateCommand {
-    easing: i32,
+    easing: Easing,
     start_time: i32,
     end_time: i32,
     start_angle: f32,
     end_angle: f32,
 }
 
+impl RotateCommand {
+    pub fn get_easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn change_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.start_time = scale_time(self.start_time, rate);
+        self.end_time = scale_time(self.end_time, rate);
+    }
+}
+
 impl std::fmt::Display for RotateCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
+        writeln!(
             f,
-            "R,{},{},{},{},{}\n",
+            "R,{},{},{},{},{}",
             self.easing, self.start_time, self.end_time, self.start_angle, self.end_angle
         )
     }
 }
 
 impl RotateCommand {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        let easing = match s[0].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Rotate Command token: {}", s[0]),
-                ))
-            }
-        };
-
-        let start_time = match s[1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Rotate Command token: {}", s[1]),
-                ))
-            }
-        };
-
-        let has_end_angle = (s.len() == 4 && s[s.len() - 2].is_empty()) || s.len() == 5;
-
-        let end_angle = match s[s.len() - 1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Rotate Command token: {}", s[s.len() - 1]),
-                ))
-            }
-        };
-
-        let start_angle = if s[s.len() - 2].is_empty() || !has_end_angle {
-            end_angle
-        } else {
-            match s[s.len() - 2].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Rotate Command token: {}", s[s.len() - 2]),
-                    ))
-                }
-            }
-        };
-
-        let end_time = if s.len() == 4 && has_end_angle {
-            start_time
-        } else {
-            match s[2].parse() {
-                Ok(x) => x,
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Invalid Command token: {}", s[2]),
-                    ))
-                }
-            }
-        };
+    pub fn parse(s: &[&str]) -> Result<Self> {
+        let (easing, start_time, end_time) = parse_timing(s)?;
+        let (start, end) = value_indices(s, 1)?;
 
         Ok(RotateCommand {
             easing,
             start_time,
             end_time,
-            start_angle,
-            end_angle,
+            start_angle: parse_field(s, start)?,
+            end_angle: parse_field(s, end)?,
         })
     }
 }
@@ -1,9 +1,17 @@
-#[derive(Debug)]
+use crate::{
+    error::{Error, Result},
+    utils::scale_time,
+};
+
+use super::{indented, parse_field, Command};
+
+#[derive(Debug, Clone)]
 pub struct TriggerCommand {
     trigger_type: Trigger,
     start_time: i32,
     end_time: i32,
     group: Option<String>,
+    commands: Vec<Command>,
 }
 
 impl std::fmt::Display for TriggerCommand {
@@ -16,57 +24,64 @@ impl std::fmt::Display for TriggerCommand {
         if let Some(group) = &self.group {
             display_string.push_str(&format!(",{}", group));
         }
+        display_string.push('\n');
 
-        write!(f, "{}\n", display_string)
+        for command in &self.commands {
+            display_string += &indented(command);
+        }
+
+        write!(f, "{}", display_string)
     }
 }
 
 impl TriggerCommand {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        let trigger_type = Trigger::from(s[0]);
-
-        let start_time = match s[1].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Trigger Command token: {}", s[1]),
-                ))
-            }
-        };
-
-        let end_time = match s[2].parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Invalid Trigger Command token: {}", s[2]),
-                ))
-            }
-        };
-
-        let group = if s.len() > 3 {
-            Some(s[3].to_string())
-        } else {
-            None
-        };
+    pub fn parse(s: &[&str]) -> Result<Self> {
+        let trigger_type = Trigger::from(*s.first().ok_or(Error::command(1))?);
+        let start_time = parse_field(s, 1)?;
+        let end_time = parse_field(s, 2)?;
+        let group = s.get(3).map(|group| group.to_string());
 
         Ok(TriggerCommand {
             trigger_type,
             start_time,
             end_time,
             group,
+            commands: Vec::new(),
         })
     }
+
+    /// Commands run each time the trigger fires, timed relative to the trigger.
+    pub fn get_commands(&self) -> &Vec<Command> {
+        &self.commands
+    }
+
+    pub fn get_commands_mut(&mut self) -> &mut Vec<Command> {
+        &mut self.commands
+    }
+
+    pub fn add_command(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.start_time = scale_time(self.start_time, rate);
+        self.end_time = scale_time(self.end_time, rate);
+        for command in self.commands.iter_mut() {
+            command.scale_times(rate);
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Trigger {
     HitSoundClap,
     HitSoundFinish,
     HitSoundWhistle,
     Passing,
     Failing,
+    /// Any other trigger, such as `HitSoundSoftWhistle` or `HitSound`, kept
+    /// as written.
+    Other(String),
 }
 
 impl From<&str> for Trigger {
@@ -77,7 +92,7 @@ impl From<&str> for Trigger {
             "HitSoundWhistle" => Trigger::HitSoundWhistle,
             "Passing" => Trigger::Passing,
             "Failing" => Trigger::Failing,
-            _ => Trigger::HitSoundClap,
+            _ => Trigger::Other(s.to_string()),
         }
     }
 }
@@ -90,6 +105,24 @@ impl std::fmt::Display for Trigger {
             Trigger::HitSoundWhistle => write!(f, "HitSoundWhistle"),
             Trigger::Passing => write!(f, "Passing"),
             Trigger::Failing => write!(f, "Failing"),
+            Trigger::Other(name) => write!(f, "{}", name),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger() {
+        assert_eq!(Trigger::from("Passing"), Trigger::Passing);
+        assert_eq!(
+            Trigger::from("HitSoundDrumWhistleFinish"),
+            Trigger::Other("HitSoundDrumWhistleFinish".to_string())
+        );
+
+        let trigger = TriggerCommand::parse(&["HitSoundSoftClap2", "0", "1000"]).unwrap();
+        assert_eq!(trigger.to_string(), "T,HitSoundSoftClap2,0,1000\n");
+    }
+}
@@ -1,14 +1,19 @@
 mod command;
 mod storyboard;
 
-use std::{ffi::OsString, path::PathBuf};
+use std::path::PathBuf;
 
-pub use command::Command;
+pub use command::{Command, Easing};
 pub use storyboard::OsuStoryboard;
 
-use crate::Beatmap;
+use crate::{
+    error::{Error, Result},
+    source::Entry,
+    utils::scale_time,
+    Beatmap,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OsuBackground {
     start_time: i32,
     filename: String,
@@ -32,7 +37,7 @@ impl OsuBackground {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OsuVideo {
     start_time: i32,
     filename: String,
@@ -56,7 +61,7 @@ impl OsuVideo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OsuBreak {
     start_time: i32,
     end_time: i32,
@@ -69,9 +74,17 @@ impl OsuBreak {
             end_time,
         }
     }
+
+    pub fn get_start_time(&self) -> i32 {
+        self.start_time
+    }
+
+    pub fn get_end_time(&self) -> i32 {
+        self.end_time
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OsuEvent {
     event_type: String,
     start_time: i32,
@@ -88,7 +101,7 @@ impl OsuEvent {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Event {
     Background(OsuBackground),
     Video(OsuVideo),
@@ -101,22 +114,20 @@ impl std::fmt::Display for Event {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Event::Background(background) => {
-                let mut display_string = String::from(format!(
-                    "0,{},\"{}\"",
-                    background.start_time, background.filename
-                ));
+                let mut display_string =
+                    format!("0,{},\"{}\"", background.start_time, background.filename);
                 if let Some(x_offset) = background.x_offset {
                     display_string.push_str(&format!(",{}", x_offset));
                 }
                 if let Some(y_offset) = background.y_offset {
                     display_string.push_str(&format!(",{}", y_offset));
                 }
-                display_string.push_str("\n");
+                display_string.push('\n');
                 write!(f, "{}", display_string)
             }
             Event::Video(video) => {
                 let mut display_string =
-                    String::from(format!("Video,{},\"{}\"", video.start_time, video.filename));
+                    format!("Video,{},\"{}\"", video.start_time, video.filename);
                 if let Some(x_offset) = video.x_offset {
                     display_string.push_str(&format!(",{}", x_offset));
                 }
@@ -124,11 +135,11 @@ impl std::fmt::Display for Event {
                     display_string.push_str(&format!(",{}", y_offset));
                 }
 
-                display_string.push_str("\n");
+                display_string.push('\n');
                 write!(f, "{}", display_string)
             }
             Event::Break(osu_break) => {
-                write!(f, "2,{},{}\n", osu_break.start_time, osu_break.end_time)
+                writeln!(f, "2,{},{}", osu_break.start_time, osu_break.end_time)
             }
             Event::Storyboard(storyboard) => write!(f, "{}", storyboard),
             Event::Basic(osu_event) => {
@@ -136,15 +147,15 @@ impl std::fmt::Display for Event {
                 if let Some(p) = &osu_event.params {
                     for (index, param) in p.iter().enumerate() {
                         if index == p.len() - 1 {
-                            params.push_str(&param);
+                            params.push_str(param);
                         } else {
                             params.push_str(&format!("{},", param));
                         }
                     }
                 }
-                write!(
+                writeln!(
                     f,
-                    "{},{},{}\n",
+                    "{},{},{}",
                     osu_event.event_type, osu_event.start_time, params
                 )
             }
@@ -152,7 +163,7 @@ impl std::fmt::Display for Event {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct Events {
     events: Vec<Event>,
 }
@@ -177,9 +188,9 @@ impl Beatmap {
     pub fn get_background_path(&self) -> Option<String> {
         if let Some(background) = self.events.get_background() {
             return Some(
-                OsString::from(PathBuf::from(self.get_directory()).join(background))
-                    .to_str()
-                    .unwrap()
+                PathBuf::from(self.get_directory().cloned().unwrap_or_default())
+                    .join(background)
+                    .to_string_lossy()
                     .to_string(),
             );
         }
@@ -189,9 +200,9 @@ impl Beatmap {
     pub fn get_video_path(&self) -> Option<String> {
         if let Some(video) = self.events.get_video() {
             return Some(
-                OsString::from(PathBuf::from(self.get_directory()).join(video))
-                    .to_str()
-                    .unwrap()
+                PathBuf::from(self.get_directory().cloned().unwrap_or_default())
+                    .join(video)
+                    .to_string_lossy()
                     .to_string(),
             );
         }
@@ -199,62 +210,61 @@ impl Beatmap {
     }
 }
 
-impl Default for Events {
-    fn default() -> Self {
-        Events { events: Vec::new() }
+fn parse_field<T: std::str::FromStr>(parts: &[&str], index: usize) -> Result<T> {
+    parts
+        .get(index)
+        .and_then(|part| part.parse().ok())
+        .ok_or(Error::event(index))
+}
+
+fn parse_optional_field<T: std::str::FromStr>(parts: &[&str], index: usize) -> Result<Option<T>> {
+    match parts.get(index) {
+        Some(_) => parse_field(parts, index).map(Some),
+        None => Ok(None),
     }
 }
 
+fn parse_file_name(parts: &[&str], index: usize) -> Result<String> {
+    parts
+        .get(index)
+        .map(|part| part.trim_matches('"').to_string())
+        .ok_or(Error::event(index))
+}
+
 impl Events {
-    pub fn parse_value(&mut self, value: &str) {
+    pub fn parse_value(&mut self, value: &str) -> Result<()> {
         let parts: Vec<&str> = value.split(',').map(|s| s.trim()).collect();
         match parts[0] {
             "0" => {
-                let start_time = parts[1].parse().unwrap();
-                let file_name = String::from(&parts[2][1..parts[2].len() - 1]);
-
-                let x_offset = if parts.len() > 3 {
-                    Some(parts[3].parse().unwrap())
-                } else {
-                    None
-                };
-                let y_offset = if parts.len() > 4 {
-                    Some(parts[4].parse().unwrap())
-                } else {
-                    None
-                };
+                let start_time = parse_field(&parts, 1)?;
+                let file_name = parse_file_name(&parts, 2)?;
+                let x_offset = parse_optional_field(&parts, 3)?;
+                let y_offset = parse_optional_field(&parts, 4)?;
+
                 self.events.push(Event::Background(OsuBackground::new(
                     start_time, file_name, x_offset, y_offset,
                 )));
             }
             "1" | "Video" => {
-                let start_time = parts[1].parse().unwrap();
-                let file_name = String::from(&parts[2][1..parts[2].len() - 1]);
-                let x_offset = if parts.len() > 3 {
-                    Some(parts[3].parse().unwrap())
-                } else {
-                    None
-                };
-                let y_offset = if parts.len() > 4 {
-                    Some(parts[4].parse().unwrap())
-                } else {
-                    None
-                };
+                let start_time = parse_field(&parts, 1)?;
+                let file_name = parse_file_name(&parts, 2)?;
+                let x_offset = parse_optional_field(&parts, 3)?;
+                let y_offset = parse_optional_field(&parts, 4)?;
 
                 self.events.push(Event::Video(OsuVideo::new(
                     start_time, file_name, x_offset, y_offset,
                 )));
             }
             "2" | "Break" => {
-                let start_time = parts[1].parse().unwrap();
-                let end_time = parts[2].parse().unwrap();
+                let start_time = parse_field(&parts, 1)?;
+                let end_time = parse_field(&parts, 2)?;
 
                 self.events
                     .push(Event::Break(OsuBreak::new(start_time, end_time)));
             }
             _ => {
                 if parts.len() > 2 {
-                    let start_time = parts[1].parse().unwrap();
+                    let start_time = parse_field(&parts, 1)?;
                     let mut params = Vec::new();
                     for p in parts[2..].iter() {
                         params.push(p.to_string());
@@ -267,6 +277,48 @@ impl Events {
                 }
             }
         }
+
+        Ok(())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub(crate) fn entries(&self) -> Vec<Entry> {
+        self.events.iter().map(Entry::item).collect()
+    }
+
+    /// Total length of every break, in milliseconds.
+    pub(crate) fn get_break_time(&self) -> f64 {
+        self.events
+            .iter()
+            .map(|event| match event {
+                Event::Break(osu_break) => (osu_break.end_time - osu_break.start_time) as f64,
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        for event in self.events.iter_mut() {
+            match event {
+                Event::Background(background) => {
+                    background.start_time = scale_time(background.start_time, rate);
+                }
+                Event::Video(video) => {
+                    video.start_time = scale_time(video.start_time, rate);
+                }
+                Event::Break(osu_break) => {
+                    osu_break.start_time = scale_time(osu_break.start_time, rate);
+                    osu_break.end_time = scale_time(osu_break.end_time, rate);
+                }
+                Event::Storyboard(storyboard) => storyboard.scale_times(rate),
+                Event::Basic(osu_event) => {
+                    osu_event.start_time = scale_time(osu_event.start_time, rate);
+                }
+            }
+        }
     }
 
     pub fn push_storyboard(&mut self, storyboard: OsuStoryboard) {
@@ -282,6 +334,13 @@ impl Events {
         None
     }
 
+    pub fn get_breaks(&self) -> impl Iterator<Item = &OsuBreak> {
+        self.events.iter().filter_map(|event| match event {
+            Event::Break(osu_break) => Some(osu_break),
+            _ => None,
+        })
+    }
+
     pub fn get_video(&self) -> Option<&str> {
         for event in &self.events {
             if let Event::Video(video) = event {
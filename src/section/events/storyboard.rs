@@ -1,8 +1,13 @@
 use std::ffi::OsString;
 
-use super::Command;
+use crate::{
+    error::{Error, Result},
+    utils::scale_time,
+};
 
-#[derive(Debug)]
+use super::{command::indented, Command};
+
+#[derive(Debug, Clone)]
 pub enum StoryboardType {
     Sprite(SpriteType),
     Animation(AnimationType),
@@ -12,14 +17,14 @@ pub enum StoryboardType {
 impl std::fmt::Display for StoryboardType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            StoryboardType::Sprite(sprite) => write!(f, "Sprite,{}", sprite),
-            StoryboardType::Animation(animation) => write!(f, "Animation,{}", animation),
-            StoryboardType::Sample(sample) => write!(f, "Sample,{}", sample),
+            StoryboardType::Sprite(sprite) => write!(f, "{}", sprite),
+            StoryboardType::Animation(animation) => write!(f, "{}", animation),
+            StoryboardType::Sample(sample) => write!(f, "{}", sample),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum StoryboardLayer {
     Background,
     Fail,
@@ -31,16 +36,29 @@ pub enum StoryboardLayer {
 impl From<&str> for StoryboardLayer {
     fn from(s: &str) -> Self {
         match s {
-            "Background" => StoryboardLayer::Background,
-            "Fail" => StoryboardLayer::Fail,
-            "Pass" => StoryboardLayer::Pass,
-            "Foreground" => StoryboardLayer::Foreground,
-            "Overlay" => StoryboardLayer::Overlay,
+            "Background" | "0" => StoryboardLayer::Background,
+            "Fail" | "1" => StoryboardLayer::Fail,
+            "Pass" | "2" => StoryboardLayer::Pass,
+            "Foreground" | "3" => StoryboardLayer::Foreground,
+            "Overlay" | "4" => StoryboardLayer::Overlay,
             _ => StoryboardLayer::Background,
         }
     }
 }
 
+impl StoryboardLayer {
+    /// Numeric form of the layer, as used by `Sample` declarations.
+    pub fn index(&self) -> i32 {
+        match self {
+            StoryboardLayer::Background => 0,
+            StoryboardLayer::Fail => 1,
+            StoryboardLayer::Pass => 2,
+            StoryboardLayer::Foreground => 3,
+            StoryboardLayer::Overlay => 4,
+        }
+    }
+}
+
 impl std::fmt::Display for StoryboardLayer {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -53,7 +71,7 @@ impl std::fmt::Display for StoryboardLayer {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LoopType {
     LoopForever,
     LoopOnce,
@@ -78,7 +96,7 @@ impl std::fmt::Display for LoopType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Origin {
     TopLeft,
     TopCentre,
@@ -124,7 +142,7 @@ impl std::fmt::Display for Origin {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SpriteType {
     layer: StoryboardLayer,
     origin: Origin,
@@ -158,14 +176,14 @@ impl std::fmt::Display for SpriteType {
             "Sprite,{},{},{},{},{}",
             self.layer,
             self.origin,
-            self.image_path.to_str().unwrap(),
+            self.image_path.to_string_lossy(),
             self.offset_x,
             self.offset_y
         )
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AnimationType {
     layer: StoryboardLayer,
     origin: Origin,
@@ -178,6 +196,7 @@ pub struct AnimationType {
 }
 
 impl AnimationType {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         layer: StoryboardLayer,
         origin: Origin,
@@ -208,7 +227,7 @@ impl std::fmt::Display for AnimationType {
             "Animation,{},{},{},{},{},{},{},{}",
             self.layer,
             self.origin,
-            self.image_path.to_str().unwrap(),
+            self.image_path.to_string_lossy(),
             self.offset_x,
             self.offset_y,
             self.frame_count,
@@ -218,7 +237,7 @@ impl std::fmt::Display for AnimationType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SampleType {
     layer: StoryboardLayer,
     image_path: OsString,
@@ -242,204 +261,65 @@ impl std::fmt::Display for SampleType {
         write!(
             f,
             "Sample,{},{},{},{}",
-            self.layer,
-            self.image_path.to_str().unwrap(),
-            self.volume,
-            self.time
+            self.time,
+            self.layer.index(),
+            self.image_path.to_string_lossy(),
+            self.volume
         )
     }
 }
 
-#[derive(Debug)]
+fn field<'a>(parts: &[&'a str], index: usize) -> Result<&'a str> {
+    parts.get(index).copied().ok_or(Error::storyboard(index))
+}
+
+fn parse_field<T: std::str::FromStr>(parts: &[&str], index: usize) -> Result<T> {
+    field(parts, index)?
+        .parse()
+        .map_err(|_| Error::storyboard(index))
+}
+
+#[derive(Debug, Clone)]
 pub struct OsuStoryboard {
     storyboard_type: StoryboardType,
     commands: Vec<Command>,
 }
 
 impl OsuStoryboard {
-    pub fn parse(value: &str) -> std::io::Result<Self> {
+    pub fn parse(value: &str) -> Result<Self> {
         let parts = value.split(',').map(|s| s.trim()).collect::<Vec<&str>>();
 
         let storyboard_type = match parts[0] {
-            "Sprite" => {
-                let layer = if parts.len() > 1 {
-                    StoryboardLayer::from(parts[1])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let origin = if parts.len() > 2 {
-                    Origin::from(parts[2])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let image_path = if parts.len() > 3 {
-                    OsString::from(parts[3])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let offset_x = if parts.len() > 4 {
-                    parts[4].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let offset_y = if parts.len() > 5 {
-                    parts[5].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                StoryboardType::Sprite(SpriteType::new(
-                    layer, origin, image_path, offset_x, offset_y,
-                ))
-            }
-            "Animation" => {
-                let layer = if parts.len() > 1 {
-                    StoryboardLayer::from(parts[1])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let origin = if parts.len() > 2 {
-                    Origin::from(parts[2])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let image_path = if parts.len() > 3 {
-                    OsString::from(parts[3])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let offset_x = if parts.len() > 4 {
-                    parts[4].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let offset_y = if parts.len() > 5 {
-                    parts[5].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let frame_count = if parts.len() > 6 {
-                    parts[6].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let frame_delay = if parts.len() > 7 {
-                    parts[7].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let loop_type = if parts.len() > 8 {
-                    LoopType::from(parts[8])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                StoryboardType::Animation(AnimationType::new(
-                    layer,
-                    origin,
-                    image_path,
-                    offset_x,
-                    offset_y,
-                    frame_count,
-                    frame_delay,
-                    loop_type,
-                ))
-            }
-            "Sample" => {
-                let layer = if parts.len() > 1 {
-                    StoryboardLayer::from(parts[1])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let image_path = if parts.len() > 2 {
-                    OsString::from(parts[2])
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let volume = if parts.len() > 3 {
-                    parts[3].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                let time = if parts.len() > 4 {
-                    parts[4].parse().unwrap()
-                } else {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid data from {}", value),
-                    ));
-                };
-
-                StoryboardType::Sample(SampleType::new(layer, image_path, volume, time))
-            }
-            _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    "Invalid Storyboard token",
-                ))
-            }
+            "Sprite" => StoryboardType::Sprite(SpriteType::new(
+                StoryboardLayer::from(field(&parts, 1)?),
+                Origin::from(field(&parts, 2)?),
+                OsString::from(field(&parts, 3)?),
+                parse_field(&parts, 4)?,
+                parse_field(&parts, 5)?,
+            )),
+            "Animation" => StoryboardType::Animation(AnimationType::new(
+                StoryboardLayer::from(field(&parts, 1)?),
+                Origin::from(field(&parts, 2)?),
+                OsString::from(field(&parts, 3)?),
+                parse_field(&parts, 4)?,
+                parse_field(&parts, 5)?,
+                parse_field(&parts, 6)?,
+                parse_field(&parts, 7)?,
+                parts
+                    .get(8)
+                    .map(|loop_type| LoopType::from(*loop_type))
+                    .unwrap_or(LoopType::LoopForever),
+            )),
+            "Sample" => StoryboardType::Sample(SampleType::new(
+                StoryboardLayer::from(field(&parts, 2)?),
+                OsString::from(field(&parts, 3)?),
+                match parts.get(4) {
+                    Some(_) => parse_field(&parts, 4)?,
+                    None => 100,
+                },
+                parse_field(&parts, 1)?,
+            )),
+            _ => return Err(Error::storyboard(0)),
         };
 
         Ok(OsuStoryboard {
@@ -451,6 +331,45 @@ impl OsuStoryboard {
     pub fn add_command(&mut self, command: Command) {
         self.commands.push(command);
     }
+
+    /// Adds a command `depth` levels deep, nesting it inside the last loop or
+    /// trigger at each level above it.
+    pub fn add_command_at_depth(&mut self, depth: usize, command: Command) -> Result<()> {
+        let mut commands = &mut self.commands;
+        for _ in 1..depth {
+            commands = commands
+                .last_mut()
+                .and_then(|parent| parent.get_commands_mut())
+                .ok_or(Error::command(0))?;
+        }
+        commands.push(command);
+
+        Ok(())
+    }
+
+    pub fn get_commands(&self) -> &Vec<Command> {
+        &self.commands
+    }
+
+    pub fn get_commands_mut(&mut self) -> &mut Vec<Command> {
+        &mut self.commands
+    }
+
+    /// Scales command times, sample times and animation frame delays.
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        match &mut self.storyboard_type {
+            StoryboardType::Sprite(_) => {}
+            StoryboardType::Animation(animation) => {
+                animation.frame_delay = scale_time(animation.frame_delay, rate);
+            }
+            StoryboardType::Sample(sample) => {
+                sample.time = scale_time(sample.time, rate);
+            }
+        }
+        for command in self.commands.iter_mut() {
+            command.scale_times(rate);
+        }
+    }
 }
 
 impl std::fmt::Display for OsuStoryboard {
@@ -461,10 +380,8 @@ impl std::fmt::Display for OsuStoryboard {
             StoryboardType::Sample(sample) => format!("{}\n", sample),
         };
 
-        let scope = 0; // TODO: Implement scope for nested commands
         for command in &self.commands {
-            display_string += &" ".repeat(scope + 1);
-            display_string += &format!("{}", command);
+            display_string += &indented(command);
         }
         write!(f, "{}", display_string)
     }
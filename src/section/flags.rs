@@ -0,0 +1,231 @@
+//! Bit flag types for the packed integer fields of hit objects. Both keep
+//! every bit they were parsed from, known or not, so they write back as is.
+
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+/// Sounds played when a hit object (or a slider edge) is hit. The normal
+/// sound always plays, the flag only forces it where a skin would skip it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct HitSound(i32);
+
+impl HitSound {
+    pub const NONE: HitSound = HitSound(0);
+    pub const NORMAL: HitSound = HitSound(1);
+    pub const WHISTLE: HitSound = HitSound(2);
+    pub const FINISH: HitSound = HitSound(4);
+    pub const CLAP: HitSound = HitSound(8);
+
+    pub fn from_bits(bits: i32) -> Self {
+        HitSound(bits)
+    }
+
+    pub fn bits(self) -> i32 {
+        self.0
+    }
+
+    /// Whether every sound in `other` is set.
+    pub fn contains(self, other: HitSound) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: HitSound) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: HitSound) {
+        self.0 &= !other.0;
+    }
+
+    pub fn set(&mut self, other: HitSound, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for HitSound {
+    type Output = HitSound;
+
+    fn bitor(self, other: HitSound) -> HitSound {
+        HitSound(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for HitSound {
+    fn bitor_assign(&mut self, other: HitSound) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for HitSound {
+    type Output = HitSound;
+
+    fn bitand(self, other: HitSound) -> HitSound {
+        HitSound(self.0 & other.0)
+    }
+}
+
+impl std::str::FromStr for HitSound {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(HitSound)
+    }
+}
+
+impl std::fmt::Display for HitSound {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The type field of a hit object: what kind of object it is, whether it
+/// starts a new combo and how many combo colours to skip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ObjectType(i32);
+
+impl ObjectType {
+    pub const CIRCLE: ObjectType = ObjectType(1);
+    pub const SLIDER: ObjectType = ObjectType(2);
+    pub const NEW_COMBO: ObjectType = ObjectType(4);
+    pub const SPINNER: ObjectType = ObjectType(8);
+    pub const HOLD: ObjectType = ObjectType(128);
+    /// The bits saying what kind of object this is
+    pub const KIND: ObjectType =
+        ObjectType(Self::CIRCLE.0 | Self::SLIDER.0 | Self::SPINNER.0 | Self::HOLD.0);
+    const COMBO_SKIP_SHIFT: i32 = 4;
+    const COMBO_SKIP: i32 = 0b111 << Self::COMBO_SKIP_SHIFT;
+
+    pub fn from_bits(bits: i32) -> Self {
+        ObjectType(bits)
+    }
+
+    pub fn bits(self) -> i32 {
+        self.0
+    }
+
+    /// Whether every flag in `other` is set.
+    pub fn contains(self, other: ObjectType) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_circle(self) -> bool {
+        self.contains(Self::CIRCLE)
+    }
+
+    pub fn is_slider(self) -> bool {
+        self.contains(Self::SLIDER)
+    }
+
+    pub fn is_spinner(self) -> bool {
+        self.contains(Self::SPINNER)
+    }
+
+    pub fn is_hold(self) -> bool {
+        self.contains(Self::HOLD)
+    }
+
+    pub fn new_combo(self) -> bool {
+        self.contains(Self::NEW_COMBO)
+    }
+
+    pub fn set_new_combo(&mut self, new_combo: bool) {
+        if new_combo {
+            self.0 |= Self::NEW_COMBO.0;
+        } else {
+            self.0 &= !Self::NEW_COMBO.0;
+        }
+    }
+
+    /// How many combo colours a new combo skips, from 0 to 7.
+    pub fn combo_skip(self) -> i32 {
+        (self.0 & Self::COMBO_SKIP) >> Self::COMBO_SKIP_SHIFT
+    }
+
+    /// Only the lowest 3 bits of `combo_skip` are kept.
+    pub fn set_combo_skip(&mut self, combo_skip: i32) {
+        self.0 =
+            self.0 & !Self::COMBO_SKIP | (combo_skip << Self::COMBO_SKIP_SHIFT) & Self::COMBO_SKIP;
+    }
+
+    /// Keeps the kind bits of `self` and takes everything else from `other`.
+    pub(crate) fn with_flags_of(self, other: ObjectType) -> Self {
+        ObjectType(other.0 & !Self::KIND.0 | self.0 & Self::KIND.0)
+    }
+}
+
+impl BitOr for ObjectType {
+    type Output = ObjectType;
+
+    fn bitor(self, other: ObjectType) -> ObjectType {
+        ObjectType(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for ObjectType {
+    fn bitor_assign(&mut self, other: ObjectType) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for ObjectType {
+    type Output = ObjectType;
+
+    fn bitand(self, other: ObjectType) -> ObjectType {
+        ObjectType(self.0 & other.0)
+    }
+}
+
+impl std::str::FromStr for ObjectType {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(ObjectType)
+    }
+}
+
+impl std::fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_sound() {
+        let mut hit_sound = HitSound::WHISTLE | HitSound::CLAP;
+        assert_eq!(hit_sound.bits(), 10);
+        assert!(hit_sound.contains(HitSound::CLAP));
+        assert!(!hit_sound.contains(HitSound::FINISH | HitSound::CLAP));
+
+        hit_sound.set(HitSound::FINISH, true);
+        hit_sound.remove(HitSound::WHISTLE);
+        assert_eq!(hit_sound, HitSound::FINISH | HitSound::CLAP);
+        assert_eq!("42".parse::<HitSound>().unwrap().to_string(), "42");
+    }
+
+    #[test]
+    fn test_object_type() {
+        let mut object_type: ObjectType = "38".parse().unwrap();
+        assert!(object_type.is_slider() && object_type.new_combo());
+        assert_eq!(object_type.combo_skip(), 2);
+
+        object_type.set_combo_skip(9);
+        assert_eq!(object_type.combo_skip(), 1);
+        object_type.set_new_combo(false);
+        assert_eq!(object_type.bits(), 18);
+
+        let flags = ObjectType::CIRCLE | ObjectType::NEW_COMBO;
+        assert_eq!(object_type.with_flags_of(flags).bits(), 6);
+        assert_eq!(object_type & ObjectType::KIND, ObjectType::SLIDER);
+    }
+}
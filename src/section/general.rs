@@ -1,7 +1,12 @@
-use crate::Beatmap;
+use crate::{
+    builder::ensure,
+    error::{Error, Result},
+    Beatmap,
+};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum SampleSet {
+    #[default]
     Normal,
     Soft,
     Drum,
@@ -17,12 +22,6 @@ impl std::fmt::Display for SampleSet {
     }
 }
 
-impl Default for SampleSet {
-    fn default() -> Self {
-        SampleSet::Normal
-    }
-}
-
 impl From<&str> for SampleSet {
     fn from(s: &str) -> Self {
         match s {
@@ -33,8 +32,9 @@ impl From<&str> for SampleSet {
         }
     }
 }
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum OverlayPosition {
+    #[default]
     NoChange,
     Below,
     Above,
@@ -50,12 +50,6 @@ impl std::fmt::Display for OverlayPosition {
     }
 }
 
-impl Default for OverlayPosition {
-    fn default() -> Self {
-        OverlayPosition::NoChange
-    }
-}
-
 impl From<&str> for OverlayPosition {
     fn from(s: &str) -> Self {
         match s {
@@ -66,7 +60,110 @@ impl From<&str> for OverlayPosition {
         }
     }
 }
-#[derive(Debug)]
+/// Ruleset a beatmap is made for
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum GameMode {
+    #[default]
+    Osu,
+    Taiko,
+    Catch,
+    Mania,
+    /// A mode this crate doesn't know, kept so it is written back as is
+    Unknown(i32),
+}
+
+impl From<i32> for GameMode {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => GameMode::Osu,
+            1 => GameMode::Taiko,
+            2 => GameMode::Catch,
+            3 => GameMode::Mania,
+            value => GameMode::Unknown(value),
+        }
+    }
+}
+
+impl From<GameMode> for i32 {
+    fn from(value: GameMode) -> Self {
+        match value {
+            GameMode::Osu => 0,
+            GameMode::Taiko => 1,
+            GameMode::Catch => 2,
+            GameMode::Mania => 3,
+            GameMode::Unknown(value) => value,
+        }
+    }
+}
+
+impl std::str::FromStr for GameMode {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse::<i32>().map(GameMode::from)
+    }
+}
+
+impl std::fmt::Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
+
+/// Speed of the countdown before the first hit object
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum Countdown {
+    /// No countdown
+    #[default]
+    None,
+    Normal,
+    /// Half speed
+    Half,
+    /// Double speed
+    Double,
+    /// A countdown this crate doesn't know, kept so it is written back as is
+    Unknown(i32),
+}
+
+impl From<i32> for Countdown {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Countdown::None,
+            1 => Countdown::Normal,
+            2 => Countdown::Half,
+            3 => Countdown::Double,
+            value => Countdown::Unknown(value),
+        }
+    }
+}
+
+impl From<Countdown> for i32 {
+    fn from(value: Countdown) -> Self {
+        match value {
+            Countdown::None => 0,
+            Countdown::Normal => 1,
+            Countdown::Half => 2,
+            Countdown::Double => 3,
+            Countdown::Unknown(value) => value,
+        }
+    }
+}
+
+impl std::str::FromStr for Countdown {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse::<i32>().map(Countdown::from)
+    }
+}
+
+impl std::fmt::Display for Countdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct General {
     /// Location of the audio file
     pub audio_file: Option<String>,
@@ -75,13 +172,13 @@ pub struct General {
     /// Time in milliseconds when the audio preview should start
     pub preview_time: Option<i32>,
     /// Speed of the coundown before the first hit object
-    pub countdown: Option<i32>,
+    pub countdown: Option<Countdown>,
     /// Sample set that will be used if timing points do not override it
     pub sample_set: Option<SampleSet>,
     /// Multiplier for teh threshold in the time where hit objects placed close together stack
     pub stack_leniency: Option<f32>,
     /// Game mode
-    pub mode: Option<i32>,
+    pub mode: Option<GameMode>,
     /// Whether or not breaks have a letterboxing effect
     pub letterbox_in_breaks: Option<bool>,
     /// Whether or not the storyboard can use the user's skin images
@@ -109,33 +206,6 @@ pub struct General {
     pub always_show_playfield: Option<bool>,
 }
 
-impl Default for General {
-    fn default() -> Self {
-        General {
-            audio_file: None,
-            audio_lead_in: None,
-            preview_time: None,
-            countdown: None,
-            sample_set: None,
-            stack_leniency: None,
-            mode: None,
-            letterbox_in_breaks: None,
-            use_skin_sprites: None,
-            overlay_position: None,
-            skin_preference: None,
-            epilepsy_warning: None,
-            countdown_offset: None,
-            special_style: None,
-            widescreen_storyboard: None,
-            samples_match_playback_rate: None,
-
-            audio_hash: None,
-            story_fire_in_front: None,
-            always_show_playfield: None,
-        }
-    }
-}
-
 impl std::fmt::Display for General {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut display_string = String::from("[General]\n");
@@ -205,23 +275,31 @@ impl std::fmt::Display for General {
 }
 
 impl General {
-    pub fn parse_value(&mut self, value: &str) {
-        let parts: Vec<&str> = value.split(':').map(|s| s.trim()).collect();
-        let value = parts[1];
-        match parts[0] {
+    pub fn parse_value(&mut self, value: &str) -> Result<()> {
+        let (key, value) = value.split_once(':').ok_or(Error::general(0))?;
+        let value = value.trim();
+        match key.trim() {
             "AudioFilename" => self.audio_file = Some(value.to_string()),
-            "AudioLeadIn" => self.audio_lead_in = Some(value.parse().unwrap()),
-            "PreviewTime" => self.preview_time = Some(value.parse().unwrap()),
-            "Countdown" => self.countdown = Some(value.parse().unwrap()),
+            "AudioLeadIn" => {
+                self.audio_lead_in = Some(value.parse().map_err(|_| Error::general(1))?)
+            }
+            "PreviewTime" => {
+                self.preview_time = Some(value.parse().map_err(|_| Error::general(1))?)
+            }
+            "Countdown" => self.countdown = Some(value.parse().map_err(|_| Error::general(1))?),
             "SampleSet" => self.sample_set = Some(SampleSet::from(value)),
-            "StackLeniency" => self.stack_leniency = Some(value.parse().unwrap()),
-            "Mode" => self.mode = Some(value.parse().unwrap()),
+            "StackLeniency" => {
+                self.stack_leniency = Some(value.parse().map_err(|_| Error::general(1))?)
+            }
+            "Mode" => self.mode = Some(value.parse().map_err(|_| Error::general(1))?),
             "LetterboxInBreaks" => self.letterbox_in_breaks = Some(value != "0"),
             "UseSkinSprites" => self.use_skin_sprites = Some(value != "0"),
             "OverlayPosition" => self.overlay_position = Some(OverlayPosition::from(value)),
             "SkinPreference" => self.skin_preference = Some(value.to_string()),
             "EpilepsyWarning" => self.epilepsy_warning = Some(value != "0"),
-            "CountdownOffset" => self.countdown_offset = Some(value.parse().unwrap()),
+            "CountdownOffset" => {
+                self.countdown_offset = Some(value.parse().map_err(|_| Error::general(1))?)
+            }
             "SpecialStyle" => self.special_style = Some(value != "0"),
             "WidescreenStoryboard" => self.widescreen_storyboard = Some(value != "0"),
             "SamplesMatchPlaybackRate" => self.samples_match_playback_rate = Some(value != "0"),
@@ -231,6 +309,8 @@ impl General {
             "AlwaysShowPlayfield" => self.always_show_playfield = Some(value != "0"),
             _ => {}
         }
+
+        Ok(())
     }
 }
 
@@ -247,7 +327,7 @@ impl General {
         self.preview_time
     }
 
-    pub fn get_countdown(&self) -> Option<i32> {
+    pub fn get_countdown(&self) -> Option<Countdown> {
         self.countdown
     }
 
@@ -259,7 +339,7 @@ impl General {
         self.stack_leniency
     }
 
-    pub fn get_mode(&self) -> Option<i32> {
+    pub fn get_mode(&self) -> Option<GameMode> {
         self.mode
     }
 
@@ -323,7 +403,7 @@ impl General {
         self.preview_time = Some(preview_time);
     }
 
-    pub fn change_countdown(&mut self, countdown: i32) {
+    pub fn change_countdown(&mut self, countdown: Countdown) {
         self.countdown = Some(countdown);
     }
 
@@ -335,7 +415,7 @@ impl General {
         self.stack_leniency = Some(stack_leniency);
     }
 
-    pub fn change_mode(&mut self, mode: i32) {
+    pub fn change_mode(&mut self, mode: GameMode) {
         self.mode = Some(mode);
     }
 
@@ -401,7 +481,7 @@ impl Beatmap {
         self.general.get_preview_time()
     }
 
-    pub fn get_general_countdown(&self) -> Option<i32> {
+    pub fn get_general_countdown(&self) -> Option<Countdown> {
         self.general.get_countdown()
     }
 
@@ -413,7 +493,7 @@ impl Beatmap {
         self.general.get_stack_leniency()
     }
 
-    pub fn get_general_mode(&self) -> Option<i32> {
+    pub fn get_general_mode(&self) -> Option<GameMode> {
         self.general.get_mode()
     }
 
@@ -477,7 +557,7 @@ impl Beatmap {
         self.general.change_preview_time(preview_time);
     }
 
-    pub fn change_general_countdown(&mut self, countdown: i32) {
+    pub fn change_general_countdown(&mut self, countdown: Countdown) {
         self.general.change_countdown(countdown);
     }
 
@@ -489,7 +569,7 @@ impl Beatmap {
         self.general.change_stack_leniency(stack_leniency);
     }
 
-    pub fn change_general_mode(&mut self, mode: i32) {
+    pub fn change_general_mode(&mut self, mode: GameMode) {
         self.general.change_mode(mode);
     }
 
@@ -547,3 +627,171 @@ impl Beatmap {
             .change_always_show_playfield(always_show_playfield);
     }
 }
+
+/// Builds the `[General]` section of a [`BeatmapBuilder`](crate::BeatmapBuilder).
+///
+/// Starts from the values the osu! editor writes for a new beatmap.
+#[derive(Debug)]
+pub struct GeneralBuilder {
+    general: General,
+}
+
+impl Default for GeneralBuilder {
+    fn default() -> Self {
+        GeneralBuilder {
+            general: General {
+                audio_lead_in: Some(0),
+                preview_time: Some(-1),
+                countdown: Some(Countdown::None),
+                sample_set: Some(SampleSet::Normal),
+                stack_leniency: Some(0.7),
+                mode: Some(GameMode::Osu),
+                letterbox_in_breaks: Some(false),
+                widescreen_storyboard: Some(false),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl GeneralBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn audio_file(mut self, audio_file: &str) -> Self {
+        self.general.change_audio_file(audio_file);
+        self
+    }
+
+    pub fn audio_lead_in(mut self, audio_lead_in: i32) -> Self {
+        self.general.change_audio_lead_in(audio_lead_in);
+        self
+    }
+
+    pub fn preview_time(mut self, preview_time: i32) -> Self {
+        self.general.change_preview_time(preview_time);
+        self
+    }
+
+    pub fn countdown(mut self, countdown: Countdown) -> Self {
+        self.general.change_countdown(countdown);
+        self
+    }
+
+    pub fn sample_set(mut self, sample_set: SampleSet) -> Self {
+        self.general.change_sample_set(sample_set);
+        self
+    }
+
+    pub fn stack_leniency(mut self, stack_leniency: f32) -> Self {
+        self.general.change_stack_leniency(stack_leniency);
+        self
+    }
+
+    pub fn mode(mut self, mode: GameMode) -> Self {
+        self.general.change_mode(mode);
+        self
+    }
+
+    pub fn letterbox_in_breaks(mut self, letterbox_in_breaks: bool) -> Self {
+        self.general.change_letterbox_in_breaks(letterbox_in_breaks);
+        self
+    }
+
+    pub fn use_skin_sprites(mut self, use_skin_sprites: bool) -> Self {
+        self.general.change_use_skin_sprites(use_skin_sprites);
+        self
+    }
+
+    pub fn overlay_position(mut self, overlay_position: OverlayPosition) -> Self {
+        self.general.change_overlay_position(overlay_position);
+        self
+    }
+
+    pub fn skin_preference(mut self, skin_preference: &str) -> Self {
+        self.general.change_skin_preference(skin_preference);
+        self
+    }
+
+    pub fn epilepsy_warning(mut self, epilepsy_warning: bool) -> Self {
+        self.general.change_epilepsy_warning(epilepsy_warning);
+        self
+    }
+
+    pub fn countdown_offset(mut self, countdown_offset: i32) -> Self {
+        self.general.change_countdown_offset(countdown_offset);
+        self
+    }
+
+    pub fn special_style(mut self, special_style: bool) -> Self {
+        self.general.change_special_style(special_style);
+        self
+    }
+
+    pub fn widescreen_storyboard(mut self, widescreen_storyboard: bool) -> Self {
+        self.general
+            .change_widescreen_storyboard(widescreen_storyboard);
+        self
+    }
+
+    pub fn samples_match_playback_rate(mut self, samples_match_playback_rate: bool) -> Self {
+        self.general
+            .change_samples_match_playback_rate(samples_match_playback_rate);
+        self
+    }
+
+    pub(crate) fn build(self) -> Result<General> {
+        let general = self.general;
+
+        ensure(
+            general
+                .audio_file
+                .as_deref()
+                .is_some_and(|file| !file.is_empty()),
+            "AudioFilename is required",
+        )?;
+        ensure(
+            general
+                .mode
+                .is_some_and(|mode| !matches!(mode, GameMode::Unknown(_))),
+            "Mode must be between 0 and 3",
+        )?;
+        ensure(
+            general
+                .countdown
+                .is_some_and(|countdown| !matches!(countdown, Countdown::Unknown(_))),
+            "Countdown must be between 0 and 3",
+        )?;
+        ensure(
+            general
+                .stack_leniency
+                .is_some_and(|leniency| (0.0..=1.0).contains(&leniency)),
+            "StackLeniency must be between 0 and 1",
+        )?;
+        ensure(
+            general.audio_lead_in.is_some_and(|lead_in| lead_in >= 0),
+            "AudioLeadIn can not be negative",
+        )?;
+
+        Ok(general)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_values() {
+        let mut general = General::default();
+        general.parse_value("Mode: 3").unwrap();
+        general.parse_value("Countdown: 9").unwrap();
+        assert_eq!(general.get_mode(), Some(GameMode::Mania));
+        assert_eq!(general.get_countdown(), Some(Countdown::Unknown(9)));
+
+        general.parse_value("Mode: 7").unwrap();
+        assert_eq!(general.get_mode(), Some(GameMode::Unknown(7)));
+        assert_eq!(general.to_string(), "[General]\nCountdown: 9\nMode: 7\n");
+    }
+}
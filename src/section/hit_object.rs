@@ -1,52 +1,115 @@
 use std::ffi::OsString;
 
-#[derive(Debug)]
+use crate::{
+    builder::ensure,
+    error::{Error, Result},
+    slider_path::SliderPath,
+    utils::{scale_time, Vec2},
+};
+
+use super::{HitSound, ObjectType, SampleBank};
+
+/// The most slides osu! plays; lazer refuses sliders with more.
+pub(crate) const MAX_SLIDES: i32 = 9000;
+/// The furthest from 0 a position or curve point can be, like in osu!.
+/// Paths can't be flattened much further out than this.
+pub(crate) const MAX_COORDINATE_VALUE: i32 = 131072;
+
+fn is_coordinate(value: i32) -> bool {
+    (-MAX_COORDINATE_VALUE..=MAX_COORDINATE_VALUE).contains(&value)
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct HitSample {
-    normal_set: i32,
-    addition_set: i32,
+    normal_set: SampleBank,
+    addition_set: SampleBank,
     index: i32,
     volume: i32,
     filename: Option<OsString>,
 }
 
 impl HitSample {
-    pub fn parse(s: &str) -> std::io::Result<Self> {
-        let samples = s.split(":").collect::<Vec<&str>>();
+    pub fn new(normal_set: SampleBank, addition_set: SampleBank, index: i32, volume: i32) -> Self {
+        HitSample {
+            normal_set,
+            addition_set,
+            index,
+            volume,
+            filename: None,
+        }
+    }
+
+    /// Plays `filename` instead of the sample set's sounds.
+    pub fn with_filename(mut self, filename: &str) -> Self {
+        self.filename = Some(OsString::from(filename));
+        self
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let samples = s.split(':').collect::<Vec<&str>>();
+        let sample = |index: usize| -> Result<i32> {
+            match samples.get(index) {
+                Some(sample) => sample.parse().map_err(|_| Error::hit_object(0)),
+                None => Ok(0),
+            }
+        };
+
         Ok(HitSample {
-            normal_set: samples[0].parse().unwrap(),
-            addition_set: if samples.len() > 1 {
-                samples[1].parse().unwrap()
-            } else {
-                0
-            },
-            index: if samples.len() > 2 {
-                samples[2].parse().unwrap()
-            } else {
-                0
-            },
-            volume: if samples.len() > 3 {
-                samples[3].parse().unwrap()
-            } else {
-                0
-            },
-            filename: if samples.len() > 4 {
-                Some(OsString::from(samples[4]))
-            } else {
-                None
-            },
+            normal_set: SampleBank::from(sample(0)?),
+            addition_set: SampleBank::from(sample(1)?),
+            index: sample(2)?,
+            volume: sample(3)?,
+            filename: samples.get(4).map(OsString::from),
         })
     }
 }
 
-impl Default for HitSample {
-    fn default() -> Self {
-        HitSample {
-            normal_set: 0,
-            addition_set: 0,
-            index: 0,
-            volume: 0,
-            filename: None,
-        }
+impl HitSample {
+    pub fn get_normal_set(&self) -> SampleBank {
+        self.normal_set
+    }
+
+    pub fn change_normal_set(&mut self, normal_set: SampleBank) {
+        self.normal_set = normal_set;
+    }
+
+    pub fn get_addition_set(&self) -> SampleBank {
+        self.addition_set
+    }
+
+    pub fn change_addition_set(&mut self, addition_set: SampleBank) {
+        self.addition_set = addition_set;
+    }
+
+    /// Custom sample index, 0 uses the skin's samples
+    pub fn get_index(&self) -> i32 {
+        self.index
+    }
+
+    pub fn change_index(&mut self, index: i32) {
+        self.index = index;
+    }
+
+    /// Volume from 0 to 100, 0 uses the timing point's volume
+    pub fn get_volume(&self) -> i32 {
+        self.volume
+    }
+
+    pub fn change_volume(&mut self, volume: i32) {
+        self.volume = volume;
+    }
+
+    /// Sound file played instead of the sample set's sounds
+    pub fn get_filename(&self) -> Option<&OsString> {
+        self.filename.as_ref()
+    }
+
+    pub fn change_filename(&mut self, filename: &str) {
+        self.filename = Some(OsString::from(filename));
+    }
+
+    pub fn remove_filename(&mut self) {
+        self.filename = None;
     }
 }
 
@@ -60,7 +123,7 @@ impl std::fmt::Display for HitSample {
                 self.addition_set,
                 self.index,
                 self.volume,
-                filename.to_str().unwrap()
+                filename.to_string_lossy()
             )
         } else {
             write!(
@@ -71,51 +134,69 @@ impl std::fmt::Display for HitSample {
         }
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EdgeSet {
-    normal_set: i32,
-    addition_set: i32,
+    normal_set: SampleBank,
+    addition_set: SampleBank,
 }
 
 impl EdgeSet {
-    pub fn parse(s: &str) -> std::io::Result<Self> {
-        let mut iter = s.split(":");
+    pub fn new(normal_set: SampleBank, addition_set: SampleBank) -> Self {
+        EdgeSet {
+            normal_set,
+            addition_set,
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let (normal_set, addition_set) = s.split_once(':').ok_or(Error::hit_object(0))?;
         Ok(EdgeSet {
-            normal_set: iter.next().unwrap().parse().unwrap(),
-            addition_set: iter.next().unwrap().parse().unwrap(),
+            normal_set: normal_set.parse().map_err(|_| Error::hit_object(0))?,
+            addition_set: addition_set.parse().map_err(|_| Error::hit_object(0))?,
         })
     }
 }
 
+impl EdgeSet {
+    pub fn get_normal_set(&self) -> SampleBank {
+        self.normal_set
+    }
+
+    pub fn change_normal_set(&mut self, normal_set: SampleBank) {
+        self.normal_set = normal_set;
+    }
+
+    pub fn get_addition_set(&self) -> SampleBank {
+        self.addition_set
+    }
+
+    pub fn change_addition_set(&mut self, addition_set: SampleBank) {
+        self.addition_set = addition_set;
+    }
+}
+
 impl std::fmt::Display for EdgeSet {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}:{}", self.normal_set, self.addition_set)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CircleHitObject {
     x: i32,
     y: i32,
     time: i32,
-    object_type: i32,
-    hit_sound: i32,
+    object_type: ObjectType,
+    hit_sound: HitSound,
     params: Vec<String>,
     hit_sample: Option<HitSample>,
 }
 
 impl CircleHitObject {
-    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: i32) -> std::io::Result<Self> {
+    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: ObjectType) -> Result<Self> {
         let mut params: Vec<String> = Vec::new();
         let mut hit_sample = None;
 
-        let hit_sound = if let Ok(n) = s[4].parse() {
-            n
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid CircleHitObject token",
-            ));
-        };
+        let hit_sound = parse_field(s, 4)?;
 
         if s.len() > 6 {
             for p in &s[5..s.len() - 1] {
@@ -123,10 +204,11 @@ impl CircleHitObject {
             }
         }
 
-        if s[s.len() - 1].contains(":") {
-            hit_sample = Some(HitSample::parse(s[s.len() - 1])?);
+        let last = s.len() - 1;
+        if last > 4 && s[last].contains(':') {
+            hit_sample = Some(HitSample::parse(s[last]).map_err(|e| e.offset_field(last))?);
         } else if s.len() > 6 {
-            params.push(s[s.len() - 1].to_string());
+            params.push(s[last].to_string());
         }
 
         Ok(CircleHitObject {
@@ -141,6 +223,76 @@ impl CircleHitObject {
     }
 }
 
+impl CircleHitObject {
+    pub fn get_x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn change_x(&mut self, x: i32) {
+        self.x = x;
+    }
+
+    pub fn get_y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn change_y(&mut self, y: i32) {
+        self.y = y;
+    }
+
+    /// Time in milliseconds from the start of the audio
+    pub fn get_time(&self) -> i32 {
+        self.time
+    }
+
+    pub fn change_time(&mut self, time: i32) {
+        self.time = time;
+    }
+
+    pub fn get_object_type(&self) -> ObjectType {
+        self.object_type
+    }
+
+    /// Changes the new combo flag and colour skip. The bits saying what kind
+    /// of object this is are kept as they are.
+    pub fn change_object_type(&mut self, object_type: ObjectType) {
+        self.object_type = self.object_type.with_flags_of(object_type);
+    }
+
+    pub fn get_hit_sound(&self) -> HitSound {
+        self.hit_sound
+    }
+
+    pub fn change_hit_sound(&mut self, hit_sound: HitSound) {
+        self.hit_sound = hit_sound;
+    }
+
+    pub fn get_hit_sample(&self) -> Option<&HitSample> {
+        self.hit_sample.as_ref()
+    }
+
+    pub fn get_hit_sample_mut(&mut self) -> Option<&mut HitSample> {
+        self.hit_sample.as_mut()
+    }
+
+    pub fn change_hit_sample(&mut self, hit_sample: HitSample) {
+        self.hit_sample = Some(hit_sample);
+    }
+
+    pub fn remove_hit_sample(&mut self) {
+        self.hit_sample = None;
+    }
+
+    /// Extra fields between the hit sound and hit sample, kept as written
+    pub fn get_params(&self) -> &Vec<String> {
+        &self.params
+    }
+
+    pub fn get_params_mut(&mut self) -> &mut Vec<String> {
+        &mut self.params
+    }
+}
+
 impl std::fmt::Display for CircleHitObject {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut display_string = format!(
@@ -159,9 +311,8 @@ impl std::fmt::Display for CircleHitObject {
             }
         }
 
-        match &self.hit_sample {
-            Some(h) => display_string += &format!(",{}", h.to_string()),
-            None => {}
+        if let Some(h) = &self.hit_sample {
+            display_string += &format!(",{}", h);
         }
 
         if self.hit_sample.is_none() && self.params.is_empty() {
@@ -171,7 +322,7 @@ impl std::fmt::Display for CircleHitObject {
         write!(f, "{}", display_string)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CurveType {
     Bezier,
     CentripetalCatmullRom,
@@ -201,19 +352,44 @@ impl std::fmt::Display for CurveType {
         }
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CurvePoint {
     x: i32,
     y: i32,
 }
 
-impl From<&str> for CurvePoint {
-    fn from(s: &str) -> Self {
-        let mut iter = s.split(":");
-        CurvePoint {
-            x: iter.next().unwrap().parse().unwrap(),
-            y: iter.next().unwrap().parse().unwrap(),
-        }
+impl CurvePoint {
+    pub fn new(x: i32, y: i32) -> Self {
+        CurvePoint { x, y }
+    }
+
+    /// Parses an `x:y` pair, returning `None` if it is malformed or further
+    /// out than osu! allows.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (x, y) = s.split_once(':')?;
+        let point = CurvePoint {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+        };
+        (is_coordinate(point.x) && is_coordinate(point.y)).then_some(point)
+    }
+}
+
+impl CurvePoint {
+    pub fn get_x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn change_x(&mut self, x: i32) {
+        self.x = x;
+    }
+
+    pub fn get_y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn change_y(&mut self, y: i32) {
+        self.y = y;
     }
 }
 
@@ -222,80 +398,56 @@ impl std::fmt::Display for CurvePoint {
         write!(f, "{}:{}", self.x, self.y)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SliderHitObject {
     x: i32,
     y: i32,
     time: i32,
-    object_type: i32,
-    hit_sound: i32,
+    object_type: ObjectType,
+    hit_sound: HitSound,
     curve_type: CurveType,
     curve_points: Vec<CurvePoint>,
     slides: i32,
     length: f32,
-    edge_sounds: Vec<i32>,
+    edge_sounds: Vec<HitSound>,
     edge_sets: Vec<EdgeSet>,
     hit_sample: Option<HitSample>,
 }
 
 impl SliderHitObject {
-    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: i32) -> std::io::Result<Self> {
+    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: ObjectType) -> Result<Self> {
         let mut curve_points = Vec::new();
         let mut edge_sounds = Vec::new();
         let mut edge_sets = Vec::new();
         let mut hit_sample = None;
 
-        let hit_sound = if let Ok(n) = s[4].parse() {
-            n
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid SliderHitObject token",
-            ));
-        };
+        let hit_sound = parse_field(s, 4)?;
 
-        let mut iter = s[5].split("|");
-        let curve_type = CurveType::from(iter.next().unwrap());
-        while let Some(point) = iter.next() {
-            curve_points.push(CurvePoint::from(point));
+        let mut iter = s.get(5).ok_or(Error::hit_object(5))?.split('|');
+        let curve_type = CurveType::from(iter.next().unwrap_or_default());
+        for point in iter {
+            curve_points.push(CurvePoint::parse(point).ok_or(Error::hit_object(5))?);
         }
 
-        let slides = if let Ok(n) = s[6].parse() {
-            n
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid SliderHitObject token",
-            ));
-        };
-
-        let length = if let Ok(n) = s[7].parse() {
-            n
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid SliderHitObject token",
-            ));
-        };
+        let slides = parse_field(s, 6)?;
+        let length = parse_field(s, 7)?;
 
         if s.len() > 8 {
-            let sounds = s[8].split("|");
+            let sounds = s[8].split('|');
             for s in sounds {
-                edge_sounds.push(s.parse().unwrap());
+                edge_sounds.push(s.parse().map_err(|_| Error::hit_object(8))?);
             }
         }
 
         if s.len() > 9 {
-            let sets = s[9].split("|");
+            let sets = s[9].split('|');
             for s in sets {
-                edge_sets.push(EdgeSet::parse(s)?);
+                edge_sets.push(EdgeSet::parse(s).map_err(|e| e.offset_field(9))?);
             }
         }
 
-        if s.len() > 10 {
-            if s[10].contains(":") {
-                hit_sample = Some(HitSample::parse(s[10])?);
-            }
+        if s.len() > 10 && s[10].contains(":") {
+            hit_sample = Some(HitSample::parse(s[10]).map_err(|e| e.offset_field(10))?);
         }
 
         Ok(SliderHitObject {
@@ -315,6 +467,160 @@ impl SliderHitObject {
     }
 }
 
+impl SliderHitObject {
+    pub fn get_x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn change_x(&mut self, x: i32) {
+        self.x = x;
+    }
+
+    pub fn get_y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn change_y(&mut self, y: i32) {
+        self.y = y;
+    }
+
+    /// Time in milliseconds from the start of the audio
+    pub fn get_time(&self) -> i32 {
+        self.time
+    }
+
+    pub fn change_time(&mut self, time: i32) {
+        self.time = time;
+    }
+
+    pub fn get_object_type(&self) -> ObjectType {
+        self.object_type
+    }
+
+    /// Changes the new combo flag and colour skip. The bits saying what kind
+    /// of object this is are kept as they are.
+    pub fn change_object_type(&mut self, object_type: ObjectType) {
+        self.object_type = self.object_type.with_flags_of(object_type);
+    }
+
+    pub fn get_hit_sound(&self) -> HitSound {
+        self.hit_sound
+    }
+
+    pub fn change_hit_sound(&mut self, hit_sound: HitSound) {
+        self.hit_sound = hit_sound;
+    }
+
+    pub fn get_hit_sample(&self) -> Option<&HitSample> {
+        self.hit_sample.as_ref()
+    }
+
+    pub fn get_hit_sample_mut(&mut self) -> Option<&mut HitSample> {
+        self.hit_sample.as_mut()
+    }
+
+    pub fn change_hit_sample(&mut self, hit_sample: HitSample) {
+        self.hit_sample = Some(hit_sample);
+    }
+
+    pub fn remove_hit_sample(&mut self) {
+        self.hit_sample = None;
+    }
+
+    pub fn get_curve_type(&self) -> CurveType {
+        self.curve_type
+    }
+
+    pub fn change_curve_type(&mut self, curve_type: CurveType) {
+        self.curve_type = curve_type;
+    }
+
+    /// Anchors of the slider's path, after its starting position
+    pub fn get_curve_points(&self) -> &Vec<CurvePoint> {
+        &self.curve_points
+    }
+
+    pub fn get_curve_points_mut(&mut self) -> &mut Vec<CurvePoint> {
+        &mut self.curve_points
+    }
+
+    /// How many times the slider is travelled, 1 for no repeats
+    pub fn get_slides(&self) -> i32 {
+        self.slides
+    }
+
+    pub fn change_slides(&mut self, slides: i32) {
+        self.slides = slides;
+    }
+
+    /// How many spans the slider plays, at least one and at most
+    /// [`MAX_SLIDES`] however many slides the file asks for.
+    pub(crate) fn get_span_count(&self) -> usize {
+        self.slides.clamp(1, MAX_SLIDES) as usize
+    }
+
+    /// Visual length in osu! pixels
+    pub fn get_length(&self) -> f32 {
+        self.length
+    }
+
+    pub fn change_length(&mut self, length: f32) {
+        self.length = length;
+    }
+
+    /// Hit sound of each edge, from the head to the tail
+    pub fn get_edge_sounds(&self) -> &Vec<HitSound> {
+        &self.edge_sounds
+    }
+
+    pub fn get_edge_sounds_mut(&mut self) -> &mut Vec<HitSound> {
+        &mut self.edge_sounds
+    }
+
+    /// Sample sets of each edge, from the head to the tail
+    pub fn get_edge_sets(&self) -> &Vec<EdgeSet> {
+        &self.edge_sets
+    }
+
+    pub fn get_edge_sets_mut(&mut self) -> &mut Vec<EdgeSet> {
+        &mut self.edge_sets
+    }
+}
+
+impl SliderHitObject {
+    /// The path the slider follows, cut or extended to its declared length
+    /// unless that is zero.
+    pub fn get_path(&self) -> SliderPath {
+        // Points changed after parsing may be out of range
+        let position = |x: i32, y: i32| {
+            let clamp = |value: i32| value.clamp(-MAX_COORDINATE_VALUE, MAX_COORDINATE_VALUE);
+            Vec2::new(clamp(x) as f32, clamp(y) as f32)
+        };
+        let control_points: Vec<Vec2> = self
+            .curve_points
+            .iter()
+            .map(|point| position(point.x, point.y))
+            .collect();
+
+        SliderPath::new(
+            position(self.x, self.y),
+            self.curve_type,
+            &control_points,
+            (self.length > 0.0).then_some(self.length as f64),
+        )
+    }
+
+    /// Where the slider ends after all of its slides, back at the head when
+    /// it is travelled an even number of times.
+    pub fn get_end_position(&self) -> Vec2 {
+        if self.slides % 2 == 0 {
+            Vec2::new(self.x as f32, self.y as f32)
+        } else {
+            self.get_path().end_position()
+        }
+    }
+}
+
 impl std::fmt::Display for SliderHitObject {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut display_string = format!(
@@ -347,51 +653,33 @@ impl std::fmt::Display for SliderHitObject {
             }
         }
 
-        match &self.hit_sample {
-            Some(h) => display_string += &format!(",{}", h.to_string()),
-            None => {}
+        if let Some(h) = &self.hit_sample {
+            display_string += &format!(",{}", h);
         }
 
         write!(f, "{}", display_string)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SpinnerHitObject {
     x: i32,
     y: i32,
     time: i32,
-    object_type: i32,
-    hit_sound: i32,
+    object_type: ObjectType,
+    hit_sound: HitSound,
     end_time: i32,
     hit_sample: Option<HitSample>,
 }
 
 impl SpinnerHitObject {
-    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: i32) -> std::io::Result<Self> {
+    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: ObjectType) -> Result<Self> {
         let mut hit_sample = None;
 
-        let hit_sound = if let Ok(n) = s[4].parse() {
-            n
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid SpinnerHitObject token",
-            ));
-        };
-
-        let end_time = if let Ok(n) = s[5].parse() {
-            n
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid SpinnerHitObject token",
-            ));
-        };
+        let hit_sound = parse_field(s, 4)?;
+        let end_time = parse_field(s, 5)?;
 
-        if s.len() > 6 {
-            if s[6].contains(":") {
-                hit_sample = Some(HitSample::parse(s[6])?);
-            }
+        if s.len() > 6 && s[6].contains(":") {
+            hit_sample = Some(HitSample::parse(s[6]).map_err(|e| e.offset_field(6))?);
         }
 
         Ok(SpinnerHitObject {
@@ -406,6 +694,75 @@ impl SpinnerHitObject {
     }
 }
 
+impl SpinnerHitObject {
+    pub fn get_x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn change_x(&mut self, x: i32) {
+        self.x = x;
+    }
+
+    pub fn get_y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn change_y(&mut self, y: i32) {
+        self.y = y;
+    }
+
+    /// Time in milliseconds from the start of the audio
+    pub fn get_time(&self) -> i32 {
+        self.time
+    }
+
+    pub fn change_time(&mut self, time: i32) {
+        self.time = time;
+    }
+
+    pub fn get_object_type(&self) -> ObjectType {
+        self.object_type
+    }
+
+    /// Changes the new combo flag and colour skip. The bits saying what kind
+    /// of object this is are kept as they are.
+    pub fn change_object_type(&mut self, object_type: ObjectType) {
+        self.object_type = self.object_type.with_flags_of(object_type);
+    }
+
+    pub fn get_hit_sound(&self) -> HitSound {
+        self.hit_sound
+    }
+
+    pub fn change_hit_sound(&mut self, hit_sound: HitSound) {
+        self.hit_sound = hit_sound;
+    }
+
+    pub fn get_hit_sample(&self) -> Option<&HitSample> {
+        self.hit_sample.as_ref()
+    }
+
+    pub fn get_hit_sample_mut(&mut self) -> Option<&mut HitSample> {
+        self.hit_sample.as_mut()
+    }
+
+    pub fn change_hit_sample(&mut self, hit_sample: HitSample) {
+        self.hit_sample = Some(hit_sample);
+    }
+
+    pub fn remove_hit_sample(&mut self) {
+        self.hit_sample = None;
+    }
+
+    pub fn get_end_time(&self) -> i32 {
+        self.end_time
+    }
+
+    pub fn change_end_time(&mut self, end_time: i32) {
+        self.end_time = end_time;
+    }
+}
+
 impl std::fmt::Display for SpinnerHitObject {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut display_string = format!(
@@ -413,44 +770,40 @@ impl std::fmt::Display for SpinnerHitObject {
             self.x, self.y, self.time, self.object_type, self.hit_sound, self.end_time,
         );
 
-        match &self.hit_sample {
-            Some(h) => display_string += &format!(",{}", h.to_string()),
-            None => {}
+        if let Some(h) = &self.hit_sample {
+            display_string += &format!(",{}", h);
         }
 
         write!(f, "{}", display_string)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct HoldHitObject {
     x: i32,
     y: i32,
     time: i32,
-    object_type: i32,
-    hit_sound: i32,
+    object_type: ObjectType,
+    hit_sound: HitSound,
     end_time: i32,
     hit_sample: Option<HitSample>,
 }
 
 impl HoldHitObject {
-    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: i32) -> std::io::Result<Self> {
+    pub fn parse(s: &[&str], x: i32, y: i32, time: i32, object_type: ObjectType) -> Result<Self> {
         let mut hit_sample = None;
         let mut end_time = 0;
 
-        let hit_sound = if let Ok(n) = s[4].parse() {
-            n
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid HoldHitObject token",
-            ));
-        };
+        let hit_sound = parse_field(s, 4)?;
 
-        if s.len() > 5 {
-            if s[5].contains(":") {
-                let mut split = s[5].splitn(2, ':');
-                end_time = split.next().unwrap().parse().unwrap();
-                hit_sample = Some(HitSample::parse(split.next().unwrap())?);
+        if let Some(extras) = s.get(5) {
+            let (end, sample) = match extras.split_once(':') {
+                Some((end, sample)) => (end, Some(sample)),
+                None => (*extras, None),
+            };
+
+            end_time = end.parse().map_err(|_| Error::hit_object(5))?;
+            if let Some(sample) = sample {
+                hit_sample = Some(HitSample::parse(sample).map_err(|e| e.offset_field(5))?);
             }
         }
 
@@ -466,6 +819,75 @@ impl HoldHitObject {
     }
 }
 
+impl HoldHitObject {
+    pub fn get_x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn change_x(&mut self, x: i32) {
+        self.x = x;
+    }
+
+    pub fn get_y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn change_y(&mut self, y: i32) {
+        self.y = y;
+    }
+
+    /// Time in milliseconds from the start of the audio
+    pub fn get_time(&self) -> i32 {
+        self.time
+    }
+
+    pub fn change_time(&mut self, time: i32) {
+        self.time = time;
+    }
+
+    pub fn get_object_type(&self) -> ObjectType {
+        self.object_type
+    }
+
+    /// Changes the new combo flag and colour skip. The bits saying what kind
+    /// of object this is are kept as they are.
+    pub fn change_object_type(&mut self, object_type: ObjectType) {
+        self.object_type = self.object_type.with_flags_of(object_type);
+    }
+
+    pub fn get_hit_sound(&self) -> HitSound {
+        self.hit_sound
+    }
+
+    pub fn change_hit_sound(&mut self, hit_sound: HitSound) {
+        self.hit_sound = hit_sound;
+    }
+
+    pub fn get_hit_sample(&self) -> Option<&HitSample> {
+        self.hit_sample.as_ref()
+    }
+
+    pub fn get_hit_sample_mut(&mut self) -> Option<&mut HitSample> {
+        self.hit_sample.as_mut()
+    }
+
+    pub fn change_hit_sample(&mut self, hit_sample: HitSample) {
+        self.hit_sample = Some(hit_sample);
+    }
+
+    pub fn remove_hit_sample(&mut self) {
+        self.hit_sample = None;
+    }
+
+    pub fn get_end_time(&self) -> i32 {
+        self.end_time
+    }
+
+    pub fn change_end_time(&mut self, end_time: i32) {
+        self.end_time = end_time;
+    }
+}
+
 impl std::fmt::Display for HoldHitObject {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut display_string = format!(
@@ -473,15 +895,27 @@ impl std::fmt::Display for HoldHitObject {
             self.x, self.y, self.time, self.object_type, self.hit_sound, self.end_time,
         );
 
-        match &self.hit_sample {
-            Some(h) => display_string += &format!(":{}", h.to_string()),
-            None => {}
+        if let Some(h) = &self.hit_sample {
+            display_string += &format!(":{}", h);
         }
 
         write!(f, "{}", display_string)
     }
 }
-#[derive(Debug)]
+fn parse_field<T: std::str::FromStr>(s: &[&str], index: usize) -> Result<T> {
+    s.get(index)
+        .and_then(|value| value.parse().ok())
+        .ok_or(Error::hit_object(index))
+}
+
+fn parse_coordinate(s: &[&str], index: usize) -> Result<i32> {
+    parse_field(s, index)
+        .ok()
+        .filter(|value| is_coordinate(*value))
+        .ok_or(Error::hit_object(index))
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum HitObject {
     Circle(CircleHitObject),
     Slider(SliderHitObject),
@@ -490,13 +924,13 @@ pub enum HitObject {
 }
 
 impl HitObject {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
-        let x = s[0].parse().unwrap();
-        let y = s[1].parse().unwrap();
-        let time = s[2].parse().unwrap();
-        let object_type = s[3].parse().unwrap();
+    pub fn parse(s: &[&str]) -> Result<Self> {
+        let x = parse_coordinate(s, 0)?;
+        let y = parse_coordinate(s, 1)?;
+        let time = parse_field(s, 2)?;
+        let object_type: ObjectType = parse_field(s, 3)?;
 
-        if object_type & 1 != 0 {
+        if object_type.is_circle() {
             Ok(HitObject::Circle(CircleHitObject::parse(
                 s,
                 x,
@@ -504,7 +938,7 @@ impl HitObject {
                 time,
                 object_type,
             )?))
-        } else if object_type & 2 != 0 {
+        } else if object_type.is_slider() {
             Ok(HitObject::Slider(SliderHitObject::parse(
                 s,
                 x,
@@ -512,7 +946,7 @@ impl HitObject {
                 time,
                 object_type,
             )?))
-        } else if object_type & 8 != 0 {
+        } else if object_type.is_spinner() {
             Ok(HitObject::Spinner(SpinnerHitObject::parse(
                 s,
                 x,
@@ -520,7 +954,7 @@ impl HitObject {
                 time,
                 object_type,
             )?))
-        } else if object_type & 128 != 0 {
+        } else if object_type.is_hold() {
             Ok(HitObject::Hold(HoldHitObject::parse(
                 s,
                 x,
@@ -529,10 +963,149 @@ impl HitObject {
                 object_type,
             )?))
         } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid HitObject token",
-            ))
+            Err(Error::hit_object(3))
+        }
+    }
+}
+
+impl HitObject {
+    pub fn get_x(&self) -> i32 {
+        match self {
+            HitObject::Circle(c) => c.get_x(),
+            HitObject::Slider(s) => s.get_x(),
+            HitObject::Spinner(s) => s.get_x(),
+            HitObject::Hold(h) => h.get_x(),
+        }
+    }
+
+    pub fn get_y(&self) -> i32 {
+        match self {
+            HitObject::Circle(c) => c.get_y(),
+            HitObject::Slider(s) => s.get_y(),
+            HitObject::Spinner(s) => s.get_y(),
+            HitObject::Hold(h) => h.get_y(),
+        }
+    }
+
+    /// Time in milliseconds from the start of the audio
+    pub fn get_time(&self) -> i32 {
+        match self {
+            HitObject::Circle(c) => c.get_time(),
+            HitObject::Slider(s) => s.get_time(),
+            HitObject::Spinner(s) => s.get_time(),
+            HitObject::Hold(h) => h.get_time(),
+        }
+    }
+
+    pub fn get_object_type(&self) -> ObjectType {
+        match self {
+            HitObject::Circle(c) => c.get_object_type(),
+            HitObject::Slider(s) => s.get_object_type(),
+            HitObject::Spinner(s) => s.get_object_type(),
+            HitObject::Hold(h) => h.get_object_type(),
+        }
+    }
+
+    pub fn get_hit_sound(&self) -> HitSound {
+        match self {
+            HitObject::Circle(c) => c.get_hit_sound(),
+            HitObject::Slider(s) => s.get_hit_sound(),
+            HitObject::Spinner(s) => s.get_hit_sound(),
+            HitObject::Hold(h) => h.get_hit_sound(),
+        }
+    }
+
+    pub fn get_hit_sample(&self) -> Option<&HitSample> {
+        match self {
+            HitObject::Circle(c) => c.get_hit_sample(),
+            HitObject::Slider(s) => s.get_hit_sample(),
+            HitObject::Spinner(s) => s.get_hit_sample(),
+            HitObject::Hold(h) => h.get_hit_sample(),
+        }
+    }
+
+    pub fn get_hit_sample_mut(&mut self) -> Option<&mut HitSample> {
+        match self {
+            HitObject::Circle(c) => c.get_hit_sample_mut(),
+            HitObject::Slider(s) => s.get_hit_sample_mut(),
+            HitObject::Spinner(s) => s.get_hit_sample_mut(),
+            HitObject::Hold(h) => h.get_hit_sample_mut(),
+        }
+    }
+
+    pub fn change_x(&mut self, x: i32) {
+        match self {
+            HitObject::Circle(c) => c.change_x(x),
+            HitObject::Slider(s) => s.change_x(x),
+            HitObject::Spinner(s) => s.change_x(x),
+            HitObject::Hold(h) => h.change_x(x),
+        }
+    }
+
+    pub fn change_y(&mut self, y: i32) {
+        match self {
+            HitObject::Circle(c) => c.change_y(y),
+            HitObject::Slider(s) => s.change_y(y),
+            HitObject::Spinner(s) => s.change_y(y),
+            HitObject::Hold(h) => h.change_y(y),
+        }
+    }
+
+    pub fn change_time(&mut self, time: i32) {
+        match self {
+            HitObject::Circle(c) => c.change_time(time),
+            HitObject::Slider(s) => s.change_time(time),
+            HitObject::Spinner(s) => s.change_time(time),
+            HitObject::Hold(h) => h.change_time(time),
+        }
+    }
+
+    /// Changes the new combo flag and colour skip bits, see
+    /// [`CircleHitObject::change_object_type`].
+    pub fn change_object_type(&mut self, object_type: ObjectType) {
+        match self {
+            HitObject::Circle(c) => c.change_object_type(object_type),
+            HitObject::Slider(s) => s.change_object_type(object_type),
+            HitObject::Spinner(s) => s.change_object_type(object_type),
+            HitObject::Hold(h) => h.change_object_type(object_type),
+        }
+    }
+
+    pub fn change_hit_sound(&mut self, hit_sound: HitSound) {
+        match self {
+            HitObject::Circle(c) => c.change_hit_sound(hit_sound),
+            HitObject::Slider(s) => s.change_hit_sound(hit_sound),
+            HitObject::Spinner(s) => s.change_hit_sound(hit_sound),
+            HitObject::Hold(h) => h.change_hit_sound(hit_sound),
+        }
+    }
+
+    pub fn change_hit_sample(&mut self, hit_sample: HitSample) {
+        match self {
+            HitObject::Circle(c) => c.change_hit_sample(hit_sample),
+            HitObject::Slider(s) => s.change_hit_sample(hit_sample),
+            HitObject::Spinner(s) => s.change_hit_sample(hit_sample),
+            HitObject::Hold(h) => h.change_hit_sample(hit_sample),
+        }
+    }
+
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.change_time(scale_time(self.get_time(), rate));
+        match self {
+            HitObject::Spinner(s) => s.end_time = scale_time(s.end_time, rate),
+            HitObject::Hold(h) => h.end_time = scale_time(h.end_time, rate),
+            HitObject::Circle(_) | HitObject::Slider(_) => {}
+        }
+    }
+
+    /// End time of spinners and holds. Circles end when they start, and a
+    /// slider's end depends on the timing points so it is `None` here.
+    pub fn get_end_time(&self) -> Option<i32> {
+        match self {
+            HitObject::Circle(c) => Some(c.time),
+            HitObject::Slider(_) => None,
+            HitObject::Spinner(s) => Some(s.end_time),
+            HitObject::Hold(h) => Some(h.end_time),
         }
     }
 }
@@ -540,10 +1113,461 @@ impl HitObject {
 impl std::fmt::Display for HitObject {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            HitObject::Circle(c) => write!(f, "{}\n", c),
-            HitObject::Slider(s) => write!(f, "{}\n", s),
-            HitObject::Spinner(s) => write!(f, "{}\n", s),
-            HitObject::Hold(h) => write!(f, "{}\n", h),
+            HitObject::Circle(c) => writeln!(f, "{}", c),
+            HitObject::Slider(s) => writeln!(f, "{}", s),
+            HitObject::Spinner(s) => writeln!(f, "{}", s),
+            HitObject::Hold(h) => writeln!(f, "{}", h),
+        }
+    }
+}
+
+/// The fields every hit object builder shares.
+#[derive(Debug, Default)]
+struct HitObjectFields {
+    x: i32,
+    y: i32,
+    time: i32,
+    new_combo: bool,
+    combo_skip: i32,
+    hit_sound: HitSound,
+    hit_sample: HitSample,
+}
+
+impl HitObjectFields {
+    fn new(x: i32, y: i32, time: i32) -> Self {
+        HitObjectFields {
+            x,
+            y,
+            time,
+            ..Default::default()
+        }
+    }
+
+    /// The type of an object of `kind`, with the combo flags added.
+    fn object_type(&self, kind: ObjectType) -> ObjectType {
+        let mut object_type = kind;
+        object_type.set_new_combo(self.new_combo);
+        object_type.set_combo_skip(self.combo_skip);
+        object_type
+    }
+
+    fn validate(&self) -> Result<()> {
+        ensure(
+            (0..=15).contains(&self.hit_sound.bits()),
+            "hit sound must be between 0 and 15",
+        )?;
+        ensure(
+            (0..=7).contains(&self.combo_skip),
+            "combo colour skip must be between 0 and 7",
+        )?;
+        ensure(
+            self.combo_skip == 0 || self.new_combo,
+            "combo colour skip needs a new combo",
+        )
+    }
+}
+
+/// Builds a hit circle for a [`BeatmapBuilder`](crate::BeatmapBuilder).
+#[derive(Debug)]
+pub struct CircleBuilder {
+    fields: HitObjectFields,
+}
+
+impl CircleBuilder {
+    pub fn new(x: i32, y: i32, time: i32) -> Self {
+        CircleBuilder {
+            fields: HitObjectFields::new(x, y, time),
+        }
+    }
+
+    pub fn new_combo(mut self, new_combo: bool) -> Self {
+        self.fields.new_combo = new_combo;
+        self
+    }
+
+    /// Skips this many combo colours when starting a new combo.
+    pub fn combo_skip(mut self, combo_skip: i32) -> Self {
+        self.fields.combo_skip = combo_skip;
+        self
+    }
+
+    pub fn hit_sound(mut self, hit_sound: HitSound) -> Self {
+        self.fields.hit_sound = hit_sound;
+        self
+    }
+
+    pub fn hit_sample(mut self, hit_sample: HitSample) -> Self {
+        self.fields.hit_sample = hit_sample;
+        self
+    }
+
+    fn build(self) -> Result<CircleHitObject> {
+        self.fields.validate()?;
+
+        Ok(CircleHitObject {
+            x: self.fields.x,
+            y: self.fields.y,
+            time: self.fields.time,
+            object_type: self.fields.object_type(ObjectType::CIRCLE),
+            hit_sound: self.fields.hit_sound,
+            params: Vec::new(),
+            hit_sample: Some(self.fields.hit_sample),
+        })
+    }
+}
+
+/// Builds a slider for a [`BeatmapBuilder`](crate::BeatmapBuilder).
+///
+/// Edge sounds and sets default to none on every edge when not given.
+#[derive(Debug)]
+pub struct SliderBuilder {
+    fields: HitObjectFields,
+    curve_type: CurveType,
+    curve_points: Vec<CurvePoint>,
+    slides: i32,
+    length: f32,
+    edge_sounds: Vec<HitSound>,
+    edge_sets: Vec<EdgeSet>,
+}
+
+impl SliderBuilder {
+    pub fn new(x: i32, y: i32, time: i32, curve_type: CurveType, length: f32) -> Self {
+        SliderBuilder {
+            fields: HitObjectFields::new(x, y, time),
+            curve_type,
+            curve_points: Vec::new(),
+            slides: 1,
+            length,
+            edge_sounds: Vec::new(),
+            edge_sets: Vec::new(),
+        }
+    }
+
+    pub fn new_combo(mut self, new_combo: bool) -> Self {
+        self.fields.new_combo = new_combo;
+        self
+    }
+
+    /// Skips this many combo colours when starting a new combo.
+    pub fn combo_skip(mut self, combo_skip: i32) -> Self {
+        self.fields.combo_skip = combo_skip;
+        self
+    }
+
+    pub fn hit_sound(mut self, hit_sound: HitSound) -> Self {
+        self.fields.hit_sound = hit_sound;
+        self
+    }
+
+    pub fn hit_sample(mut self, hit_sample: HitSample) -> Self {
+        self.fields.hit_sample = hit_sample;
+        self
+    }
+
+    /// Adds an anchor to the slider's path.
+    pub fn point(mut self, x: i32, y: i32) -> Self {
+        self.curve_points.push(CurvePoint::new(x, y));
+        self
+    }
+
+    /// How many times the slider is travelled, 1 for no repeats.
+    pub fn slides(mut self, slides: i32) -> Self {
+        self.slides = slides;
+        self
+    }
+
+    /// Adds the hit sound and sample set of the next edge. A slider has one
+    /// edge more than it has slides.
+    pub fn edge(mut self, sound: HitSound, set: EdgeSet) -> Self {
+        self.edge_sounds.push(sound);
+        self.edge_sets.push(set);
+        self
+    }
+
+    fn build(mut self) -> Result<SliderHitObject> {
+        self.fields.validate()?;
+        ensure(
+            !self.curve_points.is_empty(),
+            "slider needs at least one curve point",
+        )?;
+        ensure(
+            self.curve_type != CurveType::Perfect || self.curve_points.len() == 2,
+            "perfect circle slider needs exactly two curve points",
+        )?;
+        ensure(self.slides >= 1, "slider needs at least one slide")?;
+        ensure(
+            self.slides <= MAX_SLIDES,
+            format!("slider can have at most {} slides", MAX_SLIDES),
+        )?;
+        ensure(
+            self.length.is_finite() && self.length > 0.0,
+            "slider length must be positive",
+        )?;
+
+        let edges = self.slides as usize + 1;
+        if self.edge_sounds.is_empty() {
+            self.edge_sounds = vec![HitSound::NONE; edges];
+            self.edge_sets = (0..edges)
+                .map(|_| EdgeSet::new(SampleBank::Auto, SampleBank::Auto))
+                .collect();
+        }
+        ensure(
+            self.edge_sounds.len() == edges,
+            format!("slider with {} slides needs {} edges", self.slides, edges),
+        )?;
+        ensure(
+            self.edge_sounds
+                .iter()
+                .all(|sound| (0..=15).contains(&sound.bits())),
+            "edge sound must be between 0 and 15",
+        )?;
+
+        Ok(SliderHitObject {
+            x: self.fields.x,
+            y: self.fields.y,
+            time: self.fields.time,
+            object_type: self.fields.object_type(ObjectType::SLIDER),
+            hit_sound: self.fields.hit_sound,
+            curve_type: self.curve_type,
+            curve_points: self.curve_points,
+            slides: self.slides,
+            length: self.length,
+            edge_sounds: self.edge_sounds,
+            edge_sets: self.edge_sets,
+            hit_sample: Some(self.fields.hit_sample),
+        })
+    }
+}
+
+/// Builds a spinner for a [`BeatmapBuilder`](crate::BeatmapBuilder).
+///
+/// Spinners sit in the centre of the playfield and start a new combo, as
+/// the osu! editor places them.
+#[derive(Debug)]
+pub struct SpinnerBuilder {
+    fields: HitObjectFields,
+    end_time: i32,
+}
+
+impl SpinnerBuilder {
+    pub fn new(time: i32, end_time: i32) -> Self {
+        SpinnerBuilder {
+            fields: HitObjectFields {
+                new_combo: true,
+                ..HitObjectFields::new(256, 192, time)
+            },
+            end_time,
+        }
+    }
+
+    pub fn new_combo(mut self, new_combo: bool) -> Self {
+        self.fields.new_combo = new_combo;
+        self
+    }
+
+    /// Skips this many combo colours when starting a new combo.
+    pub fn combo_skip(mut self, combo_skip: i32) -> Self {
+        self.fields.combo_skip = combo_skip;
+        self
+    }
+
+    pub fn hit_sound(mut self, hit_sound: HitSound) -> Self {
+        self.fields.hit_sound = hit_sound;
+        self
+    }
+
+    pub fn hit_sample(mut self, hit_sample: HitSample) -> Self {
+        self.fields.hit_sample = hit_sample;
+        self
+    }
+
+    fn build(self) -> Result<SpinnerHitObject> {
+        self.fields.validate()?;
+        ensure(
+            self.end_time > self.fields.time,
+            "spinner must end after it starts",
+        )?;
+
+        Ok(SpinnerHitObject {
+            x: self.fields.x,
+            y: self.fields.y,
+            time: self.fields.time,
+            object_type: self.fields.object_type(ObjectType::SPINNER),
+            hit_sound: self.fields.hit_sound,
+            end_time: self.end_time,
+            hit_sample: Some(self.fields.hit_sample),
+        })
+    }
+}
+
+/// Builds an osu!mania hold note for a [`BeatmapBuilder`](crate::BeatmapBuilder).
+#[derive(Debug)]
+pub struct HoldBuilder {
+    fields: HitObjectFields,
+    end_time: i32,
+}
+
+impl HoldBuilder {
+    /// `x` picks the column the same way it does for a mania hit circle.
+    pub fn new(x: i32, time: i32, end_time: i32) -> Self {
+        HoldBuilder {
+            fields: HitObjectFields::new(x, 192, time),
+            end_time,
+        }
+    }
+
+    pub fn hit_sound(mut self, hit_sound: HitSound) -> Self {
+        self.fields.hit_sound = hit_sound;
+        self
+    }
+
+    pub fn hit_sample(mut self, hit_sample: HitSample) -> Self {
+        self.fields.hit_sample = hit_sample;
+        self
+    }
+
+    fn build(self) -> Result<HoldHitObject> {
+        self.fields.validate()?;
+        ensure(
+            self.end_time > self.fields.time,
+            "hold note must end after it starts",
+        )?;
+
+        Ok(HoldHitObject {
+            x: self.fields.x,
+            y: self.fields.y,
+            time: self.fields.time,
+            object_type: self.fields.object_type(ObjectType::HOLD),
+            hit_sound: self.fields.hit_sound,
+            end_time: self.end_time,
+            hit_sample: Some(self.fields.hit_sample),
+        })
+    }
+}
+
+/// Any of the hit object builders, see [`BeatmapBuilder::hit_object`](crate::BeatmapBuilder::hit_object).
+#[derive(Debug)]
+pub enum HitObjectBuilder {
+    Circle(CircleBuilder),
+    Slider(SliderBuilder),
+    Spinner(SpinnerBuilder),
+    Hold(HoldBuilder),
+}
+
+impl HitObjectBuilder {
+    pub(crate) fn get_time(&self) -> i32 {
+        match self {
+            HitObjectBuilder::Circle(c) => c.fields.time,
+            HitObjectBuilder::Slider(s) => s.fields.time,
+            HitObjectBuilder::Spinner(s) => s.fields.time,
+            HitObjectBuilder::Hold(h) => h.fields.time,
         }
     }
+
+    pub(crate) fn build(self) -> Result<HitObject> {
+        Ok(match self {
+            HitObjectBuilder::Circle(c) => HitObject::Circle(c.build()?),
+            HitObjectBuilder::Slider(s) => HitObject::Slider(s.build()?),
+            HitObjectBuilder::Spinner(s) => HitObject::Spinner(s.build()?),
+            HitObjectBuilder::Hold(h) => HitObject::Hold(h.build()?),
+        })
+    }
+}
+
+impl From<CircleBuilder> for HitObjectBuilder {
+    fn from(builder: CircleBuilder) -> Self {
+        HitObjectBuilder::Circle(builder)
+    }
+}
+
+impl From<SliderBuilder> for HitObjectBuilder {
+    fn from(builder: SliderBuilder) -> Self {
+        HitObjectBuilder::Slider(builder)
+    }
+}
+
+impl From<SpinnerBuilder> for HitObjectBuilder {
+    fn from(builder: SpinnerBuilder) -> Self {
+        HitObjectBuilder::Spinner(builder)
+    }
+}
+
+impl From<HoldBuilder> for HitObjectBuilder {
+    fn from(builder: HoldBuilder) -> Self {
+        HitObjectBuilder::Hold(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> HitObject {
+        HitObject::parse(&line.split(',').collect::<Vec<&str>>()).unwrap()
+    }
+
+    #[test]
+    fn test_accessors() {
+        let mut slider =
+            parse("100,100,2000,6,2,B|200:100|300:150,2,140,2|0|8,1:0|0:0|2:3,1:2:0:70:");
+        assert_eq!(
+            (slider.get_x(), slider.get_y(), slider.get_time()),
+            (100, 100, 2000)
+        );
+        assert_eq!(slider.get_end_time(), None);
+
+        let HitObject::Slider(s) = &mut slider else {
+            panic!("expected a slider");
+        };
+        assert_eq!(s.get_curve_type(), CurveType::Bezier);
+        assert_eq!(s.get_curve_points()[1].get_x(), 300);
+        assert_eq!(s.get_slides(), 2);
+        assert_eq!(
+            s.get_edge_sounds(),
+            &vec![HitSound::WHISTLE, HitSound::NONE, HitSound::CLAP]
+        );
+        assert_eq!(s.get_edge_sets()[2].get_addition_set(), SampleBank::Drum);
+        assert_eq!(s.get_hit_sample().unwrap().get_volume(), 70);
+
+        s.change_curve_type(CurveType::Linear);
+        s.get_curve_points_mut().truncate(1);
+        s.get_curve_points_mut()[0].change_y(120);
+        s.change_slides(1);
+        s.change_length(100.5);
+        s.get_edge_sounds_mut().truncate(2);
+        s.get_edge_sets_mut().truncate(2);
+        s.get_hit_sample_mut().unwrap().change_filename("slide.wav");
+
+        slider.change_x(50);
+        slider.change_time(2500);
+        slider.change_hit_sound(HitSound::CLAP);
+        slider.change_object_type(
+            ObjectType::CIRCLE | ObjectType::NEW_COMBO | ObjectType::from_bits(16),
+        );
+        assert_eq!(
+            slider.to_string(),
+            "50,100,2500,22,8,L|200:120,1,100.5,2|0,1:0|0:0,1:2:0:70:slide.wav\n"
+        );
+
+        let mut hold = parse("192,192,500,128,0,1000:0:0:0:0:");
+        assert_eq!(hold.get_end_time(), Some(1000));
+        if let HitObject::Hold(h) = &mut hold {
+            h.change_end_time(1500);
+            h.remove_hit_sample();
+        }
+        assert_eq!(hold.to_string(), "192,192,500,128,0,1500\n");
+    }
+
+    #[test]
+    fn test_coordinate_limit() {
+        let line = "199,118,24329,2,0,B|247:2147483647|281:83,1,90";
+        assert!(HitObject::parse(&line.split(',').collect::<Vec<&str>>()).is_err());
+
+        let HitObject::Slider(mut slider) = parse("199,118,24329,2,0,B|247:131072|281:83,1,90")
+        else {
+            panic!("expected a slider");
+        };
+        slider.get_curve_points_mut()[0].change_y(i32::MAX);
+        assert!((slider.get_path().get_length() - 90.0).abs() < 1e-3);
+    }
 }
@@ -1,5 +1,10 @@
-use crate::Beatmap;
-#[derive(Debug)]
+use crate::{
+    builder::ensure,
+    error::{Error, Result},
+    Beatmap,
+};
+
+#[derive(Debug, Clone, Default)]
 pub struct Metadata {
     title: Option<String>,
     title_unicode: Option<String>,
@@ -204,28 +209,11 @@ impl Beatmap {
     }
 }
 
-impl Default for Metadata {
-    fn default() -> Self {
-        Metadata {
-            title: None,
-            title_unicode: None,
-            artist: None,
-            artist_unicode: None,
-            creator: None,
-            version: None,
-            source: None,
-            tags: None,
-            beatmap_id: None,
-            beatmap_set_id: None,
-        }
-    }
-}
-
 impl Metadata {
-    pub fn parse_value(&mut self, value: &str) {
-        let parts: Vec<&str> = value.split(':').map(|s| s.trim()).collect();
-        let value = parts[1];
-        match parts[0] {
+    pub fn parse_value(&mut self, value: &str) -> Result<()> {
+        let (key, value) = value.split_once(':').ok_or(Error::metadata(0))?;
+        let value = value.trim();
+        match key.trim() {
             "Title" => self.title = Some(value.to_string()),
             "TitleUnicode" => self.title_unicode = Some(value.to_string()),
             "Artist" => self.artist = Some(value.to_string()),
@@ -234,9 +222,112 @@ impl Metadata {
             "Version" => self.version = Some(value.to_string()),
             "Source" => self.source = Some(value.to_string()),
             "Tags" => self.tags = Some(value.to_string()),
-            "BeatmapID" => self.beatmap_id = Some(value.parse().unwrap()),
-            "BeatmapSetID" => self.beatmap_set_id = Some(value.parse().unwrap()),
+            "BeatmapID" => self.beatmap_id = Some(value.parse().map_err(|_| Error::metadata(1))?),
+            "BeatmapSetID" => {
+                self.beatmap_set_id = Some(value.parse().map_err(|_| Error::metadata(1))?)
+            }
             _ => {}
         }
+
+        Ok(())
+    }
+}
+
+/// Builds the `[Metadata]` section of a [`BeatmapBuilder`](crate::BeatmapBuilder).
+///
+/// Title, artist, creator and version are required. The unicode title and
+/// artist fall back to the romanised ones when not given.
+#[derive(Debug, Default)]
+pub struct MetadataBuilder {
+    metadata: Metadata,
+}
+
+impl MetadataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.metadata.change_name(title);
+        self
+    }
+
+    pub fn unicode_title(mut self, title: &str) -> Self {
+        self.metadata.change_unicode_name(title);
+        self
+    }
+
+    pub fn artist(mut self, artist: &str) -> Self {
+        self.metadata.change_artist(artist);
+        self
+    }
+
+    pub fn unicode_artist(mut self, artist: &str) -> Self {
+        self.metadata.change_unicode_artist(artist);
+        self
+    }
+
+    pub fn creator(mut self, creator: &str) -> Self {
+        self.metadata.change_creator(creator);
+        self
+    }
+
+    pub fn version(mut self, version: &str) -> Self {
+        self.metadata.change_version(version);
+        self
+    }
+
+    pub fn source(mut self, source: &str) -> Self {
+        self.metadata.change_source(source);
+        self
+    }
+
+    pub fn tags(mut self, tags: &str) -> Self {
+        self.metadata.change_tags(tags);
+        self
+    }
+
+    pub fn beatmap_id(mut self, id: i32) -> Self {
+        self.metadata.change_beatmap_id(id);
+        self
+    }
+
+    pub fn beatmap_set_id(mut self, id: i32) -> Self {
+        self.metadata.change_beatmap_set_id(id);
+        self
+    }
+
+    pub(crate) fn build(self) -> Result<Metadata> {
+        let mut metadata = self.metadata;
+
+        let required = [
+            ("Title", &metadata.title),
+            ("Artist", &metadata.artist),
+            ("Creator", &metadata.creator),
+            ("Version", &metadata.version),
+        ];
+        for (key, value) in required {
+            ensure(
+                value
+                    .as_deref()
+                    .is_some_and(|value| !value.trim().is_empty()),
+                format!("{} is required", key),
+            )?;
+        }
+        for (key, value) in [("Title", &metadata.title), ("Artist", &metadata.artist)] {
+            ensure(
+                value.as_deref().is_some_and(|value| value.is_ascii()),
+                format!("{} must be romanised, use the unicode field instead", key),
+            )?;
+        }
+
+        metadata.title_unicode = metadata.title_unicode.or(metadata.title.clone());
+        metadata.artist_unicode = metadata.artist_unicode.or(metadata.artist.clone());
+        metadata.source.get_or_insert_with(String::new);
+        metadata.tags.get_or_insert_with(String::new);
+        metadata.beatmap_id.get_or_insert(0);
+        metadata.beatmap_set_id.get_or_insert(-1);
+
+        Ok(metadata)
     }
 }
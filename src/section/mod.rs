@@ -2,16 +2,28 @@ mod colour;
 mod difficulty;
 mod editor;
 mod events;
+mod flags;
 mod general;
 mod hit_object;
 mod metadata;
+mod sample_bank;
 mod timing_point;
+mod variables;
 
 pub use colour::Colour;
-pub use difficulty::Difficulty;
+pub(crate) use difficulty::difficulty_range;
+pub use difficulty::{Difficulty, DifficultyBuilder, HitWindows};
 pub use editor::Editor;
-pub use events::{Command, Events, OsuStoryboard};
-pub use general::General;
-pub use hit_object::HitObject;
-pub use metadata::Metadata;
-pub use timing_point::TimingPoint;
+pub use events::{Command, Easing, Events, OsuStoryboard};
+pub use flags::{HitSound, ObjectType};
+pub use general::{Countdown, GameMode, General, GeneralBuilder, OverlayPosition, SampleSet};
+pub use hit_object::{
+    CircleBuilder, CircleHitObject, CurvePoint, CurveType, EdgeSet, HitObject, HitObjectBuilder,
+    HitSample, HoldBuilder, HoldHitObject, SliderBuilder, SliderHitObject, SpinnerBuilder,
+    SpinnerHitObject,
+};
+pub use metadata::{Metadata, MetadataBuilder};
+pub use sample_bank::SampleBank;
+pub(crate) use timing_point::TimingIndex;
+pub use timing_point::{TimingPoint, TimingPointBuilder};
+pub use variables::{Variable, Variables};
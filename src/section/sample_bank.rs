@@ -0,0 +1,50 @@
+/// Sample set of a hit sound. Shared by hit samples, slider edges and timing points
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum SampleBank {
+    /// Inherit: hit objects use the timing point's bank, timing points the beatmap's
+    #[default]
+    Auto,
+    Normal,
+    Soft,
+    Drum,
+    /// A bank this crate doesn't know, kept so it is written back as is
+    Unknown(i32),
+}
+
+impl From<i32> for SampleBank {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => SampleBank::Auto,
+            1 => SampleBank::Normal,
+            2 => SampleBank::Soft,
+            3 => SampleBank::Drum,
+            value => SampleBank::Unknown(value),
+        }
+    }
+}
+
+impl From<SampleBank> for i32 {
+    fn from(value: SampleBank) -> Self {
+        match value {
+            SampleBank::Auto => 0,
+            SampleBank::Normal => 1,
+            SampleBank::Soft => 2,
+            SampleBank::Drum => 3,
+            SampleBank::Unknown(value) => value,
+        }
+    }
+}
+
+impl std::str::FromStr for SampleBank {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse::<i32>().map(SampleBank::from)
+    }
+}
+
+impl std::fmt::Display for SampleBank {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
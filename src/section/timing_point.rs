@@ -1,116 +1,45 @@
-#[derive(Debug)]
+use crate::{
+    builder::ensure,
+    error::{Error, Result},
+    utils::scale_time,
+    Beatmap,
+};
+
+use super::SampleBank;
+
+#[derive(Debug, Clone)]
 pub struct TimingPoint {
     time: i32,
     beat_length: Option<f32>,
     meter: Option<i32>,
-    sample_set: Option<i32>,
+    sample_set: Option<SampleBank>,
     sample_index: Option<i32>,
     volume: Option<i32>,
     uninherited: Option<bool>,
     effects: Option<i32>,
 }
 
+fn parse_field<T: std::str::FromStr>(s: &[&str], index: usize) -> Result<Option<T>> {
+    match s.get(index) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::timing_point(index)),
+        None => Ok(None),
+    }
+}
+
 impl TimingPoint {
-    pub fn parse(s: &[&str]) -> std::io::Result<Self> {
+    pub fn parse(s: &[&str]) -> Result<Self> {
         Ok(TimingPoint {
-            time: {
-                if let Ok(n) = s[0].parse() {
-                    n
-                } else {
-                    0
-                }
-            },
-            beat_length: {
-                if s.len() > 1 {
-                    if let Ok(n) = s[1].parse() {
-                        Some(n)
-                    } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            "Invalid TimingPoint token",
-                        ));
-                    }
-                } else {
-                    None
-                }
-            },
-            meter: {
-                if s.len() > 2 {
-                    if let Ok(n) = s[2].parse() {
-                        Some(n)
-                    } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            "Invalid TimingPoint token",
-                        ));
-                    }
-                } else {
-                    None
-                }
-            },
-            sample_set: {
-                if s.len() > 3 {
-                    if let Ok(n) = s[3].parse() {
-                        Some(n)
-                    } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            "Invalid TimingPoint token",
-                        ));
-                    }
-                } else {
-                    None
-                }
-            },
-            sample_index: {
-                if s.len() > 4 {
-                    if let Ok(n) = s[4].parse() {
-                        Some(n)
-                    } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            "Invalid TimingPoint token",
-                        ));
-                    }
-                } else {
-                    None
-                }
-            },
-            volume: {
-                if s.len() > 5 {
-                    if let Ok(n) = s[5].parse() {
-                        Some(n)
-                    } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            "Invalid TimingPoint token",
-                        ));
-                    }
-                } else {
-                    None
-                }
-            },
-            uninherited: {
-                if s.len() > 6 {
-                    Some(s[6] == "1")
-                } else {
-                    None
-                }
-            },
-            effects: {
-                if s.len() > 7 {
-                    if let Ok(n) = s[7].parse() {
-                        Some(n)
-                    } else {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            "Invalid TimingPoint token",
-                        ));
-                    }
-                } else {
-                    None
-                }
-            },
+            time: parse_field::<f64>(s, 0)?.ok_or(Error::timing_point(0))? as i32,
+            beat_length: parse_field(s, 1)?,
+            meter: parse_field(s, 2)?,
+            sample_set: parse_field(s, 3)?,
+            sample_index: parse_field(s, 4)?,
+            volume: parse_field(s, 5)?,
+            uninherited: s.get(6).map(|uninherited| *uninherited == "1"),
+            effects: parse_field(s, 7)?,
         })
     }
 }
@@ -139,6 +68,402 @@ impl std::fmt::Display for TimingPoint {
         if let Some(effects) = self.effects {
             display_string.push_str(&format!(",{}", effects));
         }
-        write!(f, "{}\n", display_string)
+        writeln!(f, "{}", display_string)
+    }
+}
+
+/// Builds a timing point for a [`BeatmapBuilder`](crate::BeatmapBuilder).
+#[derive(Debug)]
+pub struct TimingPointBuilder {
+    time: i32,
+    uninherited: bool,
+    /// Beat length for uninherited points, slider velocity for inherited ones
+    value: f32,
+    meter: i32,
+    sample_set: SampleBank,
+    sample_index: i32,
+    volume: i32,
+    kiai: bool,
+    omit_first_barline: bool,
+}
+
+impl TimingPointBuilder {
+    /// A red line: a new tempo of `beat_length` milliseconds per beat.
+    pub fn uninherited(time: i32, beat_length: f32) -> Self {
+        TimingPointBuilder {
+            time,
+            uninherited: true,
+            value: beat_length,
+            meter: 4,
+            sample_set: SampleBank::Auto,
+            sample_index: 0,
+            volume: 100,
+            kiai: false,
+            omit_first_barline: false,
+        }
+    }
+
+    /// A green line: multiplies the slider velocity by `slider_velocity`.
+    pub fn inherited(time: i32, slider_velocity: f32) -> Self {
+        TimingPointBuilder {
+            uninherited: false,
+            value: slider_velocity,
+            ..Self::uninherited(time, 0.0)
+        }
+    }
+
+    pub fn meter(mut self, meter: i32) -> Self {
+        self.meter = meter;
+        self
+    }
+
+    /// Sample set of the hit objects, [`SampleBank::Auto`] uses the beatmap's default.
+    pub fn sample_set(mut self, sample_set: SampleBank) -> Self {
+        self.sample_set = sample_set;
+        self
+    }
+
+    /// Custom sample index, 0 uses the skin's samples.
+    pub fn sample_index(mut self, sample_index: i32) -> Self {
+        self.sample_index = sample_index;
+        self
+    }
+
+    pub fn volume(mut self, volume: i32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn kiai(mut self, kiai: bool) -> Self {
+        self.kiai = kiai;
+        self
+    }
+
+    pub fn omit_first_barline(mut self, omit_first_barline: bool) -> Self {
+        self.omit_first_barline = omit_first_barline;
+        self
+    }
+
+    pub(crate) fn is_uninherited(&self) -> bool {
+        self.uninherited
+    }
+
+    pub(crate) fn get_time(&self) -> i32 {
+        self.time
+    }
+
+    pub(crate) fn build(self) -> Result<TimingPoint> {
+        let beat_length = if self.uninherited {
+            ensure(
+                self.value.is_finite() && self.value > 0.0,
+                "beat length must be positive",
+            )?;
+            self.value
+        } else {
+            ensure(
+                (0.1..=10.0).contains(&self.value),
+                "slider velocity must be between 0.1 and 10",
+            )?;
+            -100.0 / self.value
+        };
+        ensure(self.meter > 0, "meter must be positive")?;
+        ensure(
+            !matches!(self.sample_set, SampleBank::Unknown(_)),
+            "sample set must be between 0 and 3",
+        )?;
+        ensure(self.sample_index >= 0, "sample index can not be negative")?;
+        ensure(
+            (0..=100).contains(&self.volume),
+            "volume must be between 0 and 100",
+        )?;
+
+        let mut effects = 0;
+        if self.kiai {
+            effects |= 1;
+        }
+        if self.omit_first_barline {
+            effects |= 8;
+        }
+
+        Ok(TimingPoint {
+            time: self.time,
+            beat_length: Some(beat_length),
+            meter: Some(self.meter),
+            sample_set: Some(self.sample_set),
+            sample_index: Some(self.sample_index),
+            volume: Some(self.volume),
+            uninherited: Some(self.uninherited),
+            effects: Some(effects),
+        })
+    }
+}
+
+impl TimingPoint {
+    pub fn get_time(&self) -> i32 {
+        self.time
+    }
+
+    /// Milliseconds per beat for red lines, a negative inverse slider
+    /// velocity percentage for green lines.
+    pub fn get_beat_length(&self) -> Option<f32> {
+        self.beat_length
+    }
+
+    pub fn get_meter(&self) -> Option<i32> {
+        self.meter
+    }
+
+    pub fn get_sample_set(&self) -> Option<SampleBank> {
+        self.sample_set
+    }
+
+    pub fn get_sample_index(&self) -> Option<i32> {
+        self.sample_index
+    }
+
+    pub fn get_volume(&self) -> Option<i32> {
+        self.volume
+    }
+
+    pub fn get_effects(&self) -> Option<i32> {
+        self.effects
+    }
+
+    /// Points written without the uninherited field are red lines.
+    pub fn is_uninherited(&self) -> bool {
+        self.uninherited.unwrap_or(true)
+    }
+
+    pub fn is_kiai(&self) -> bool {
+        self.effects.unwrap_or_default() & 1 != 0
+    }
+
+    pub fn omits_first_barline(&self) -> bool {
+        self.effects.unwrap_or_default() & 8 != 0
+    }
+
+    /// Changes the sample settings, leaving the timing alone.
+    pub(crate) fn change_samples(
+        &mut self,
+        sample_set: SampleBank,
+        sample_index: i32,
+        volume: i32,
+    ) {
+        self.sample_set = Some(sample_set);
+        self.sample_index = Some(sample_index);
+        self.volume = Some(volume);
+    }
+
+    /// A green line at `time` that keeps the slider velocity, kiai and
+    /// samples this point has there.
+    pub(crate) fn inherited_at(&self, time: i32) -> TimingPoint {
+        let beat_length = if self.is_uninherited() {
+            -100.0
+        } else {
+            self.beat_length.unwrap_or(-100.0)
+        };
+
+        TimingPoint {
+            time,
+            beat_length: Some(beat_length),
+            uninherited: Some(false),
+            effects: self.effects.map(|effects| effects & !8),
+            ..self.clone()
+        }
+    }
+
+    /// Scales the time, and the beat length of red lines. Green lines are
+    /// relative to the red line so they keep theirs.
+    pub(crate) fn scale_times(&mut self, rate: f64) {
+        self.time = scale_time(self.time, rate);
+        if self.is_uninherited() {
+            self.beat_length = self
+                .beat_length
+                .map(|beat_length| (beat_length as f64 / rate) as f32);
+        }
+    }
+
+    /// Milliseconds per beat, clamped the way osu! does.
+    pub(crate) fn beat_length(&self) -> f64 {
+        (self.beat_length.unwrap_or_default() as f64).clamp(6.0, 60000.0)
+    }
+
+    /// Slider velocity multiplier set by this point. Red lines reset it to 1.
+    pub(crate) fn slider_velocity(&self) -> f64 {
+        match self.beat_length {
+            Some(beat_length) if !self.is_uninherited() && beat_length < 0.0 => {
+                (100.0 / -beat_length as f64).clamp(0.1, 10.0)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+/// The timing points in time order for binary searches. Points at the same
+/// time keep their file order, so the last one written takes effect.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TimingIndex {
+    points: Vec<usize>,
+    uninherited: Vec<usize>,
+}
+
+impl TimingIndex {
+    pub(crate) fn new(timing_points: &[TimingPoint]) -> Self {
+        let mut points: Vec<usize> = (0..timing_points.len()).collect();
+        points.sort_by_key(|&index| timing_points[index].time);
+        let uninherited = points
+            .iter()
+            .copied()
+            .filter(|&index| timing_points[index].is_uninherited())
+            .collect();
+
+        TimingIndex {
+            points,
+            uninherited,
+        }
+    }
+}
+
+impl Beatmap {
+    /// The last point of `indices` at or before `time`, if any.
+    fn timing_point_at(&self, indices: &[usize], time: f64) -> Option<&TimingPoint> {
+        let end = indices.partition_point(|&index| self.timing_points[index].time as f64 <= time);
+        end.checked_sub(1)
+            .map(|position| &self.timing_points[indices[position]])
+    }
+
+    /// The last point at or before `time`, or the first one for earlier times.
+    pub(crate) fn sample_point_at(&self, time: f64) -> Option<&TimingPoint> {
+        self.timing_point_at(&self.timing_index.points, time)
+            .or_else(|| {
+                self.timing_index
+                    .points
+                    .first()
+                    .map(|&index| &self.timing_points[index])
+            })
+    }
+
+    /// The red line active at `time`. Times before the first red line use
+    /// the first one.
+    pub fn get_uninherited_point_at(&self, time: f64) -> Option<&TimingPoint> {
+        self.timing_point_at(&self.timing_index.uninherited, time)
+            .or_else(|| {
+                self.timing_index
+                    .uninherited
+                    .first()
+                    .map(|&index| &self.timing_points[index])
+            })
+    }
+
+    /// Milliseconds per beat at `time`, 1000 when there are no red lines.
+    pub fn get_beat_length_at(&self, time: f64) -> f64 {
+        self.get_uninherited_point_at(time)
+            .map(|point| point.beat_length())
+            .unwrap_or(1000.0)
+    }
+
+    pub fn get_bpm_at(&self, time: f64) -> f64 {
+        60000.0 / self.get_beat_length_at(time)
+    }
+
+    /// Beats per measure at `time`, 4 when not set.
+    pub fn get_meter_at(&self, time: f64) -> i32 {
+        self.get_uninherited_point_at(time)
+            .and_then(|point| point.meter)
+            .filter(|meter| *meter > 0)
+            .unwrap_or(4)
+    }
+
+    /// Slider velocity multiplier active at `time`, 1 before any timing point.
+    pub fn get_slider_velocity_at(&self, time: f64) -> f64 {
+        self.timing_point_at(&self.timing_index.points, time)
+            .map(|point| point.slider_velocity())
+            .unwrap_or(1.0)
+    }
+
+    /// Sample set at `time`. [`SampleBank::Auto`] means the beatmap's default.
+    pub fn get_sample_set_at(&self, time: f64) -> SampleBank {
+        self.sample_point_at(time)
+            .and_then(|point| point.sample_set)
+            .unwrap_or(SampleBank::Auto)
+    }
+
+    /// Custom sample index at `time`, 0 for the skin's samples.
+    pub fn get_sample_index_at(&self, time: f64) -> i32 {
+        self.sample_point_at(time)
+            .and_then(|point| point.sample_index)
+            .unwrap_or_default()
+    }
+
+    /// Sample volume at `time`, 100 when not set.
+    pub fn get_volume_at(&self, time: f64) -> i32 {
+        self.sample_point_at(time)
+            .and_then(|point| point.volume)
+            .unwrap_or(100)
+    }
+
+    pub fn is_kiai_at(&self, time: f64) -> bool {
+        self.timing_point_at(&self.timing_index.points, time)
+            .is_some_and(|point| point.is_kiai())
+    }
+
+    /// Beats since the red line active at `time`, negative before the first one.
+    pub fn get_beat_at(&self, time: f64) -> f64 {
+        let offset = self
+            .get_uninherited_point_at(time)
+            .map(|point| point.time as f64)
+            .unwrap_or_default();
+        (time - offset) / self.get_beat_length_at(time)
+    }
+
+    /// Measures since the red line active at `time`, negative before the first one.
+    pub fn get_measure_at(&self, time: f64) -> f64 {
+        self.get_beat_at(time) / self.get_meter_at(time) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Beatmap, SampleBank};
+
+    #[test]
+    fn test_queries() {
+        // Out of order, with a green line before the first red line
+        let beatmap = Beatmap::from_bytes(
+            b"osu file format v14
+
+[TimingPoints]
+2000,-50,4,2,1,60,0,1
+1000,500,3,1,0,80,1,0
+-500,-200,4,1,0,70,0,0
+3000,250,4,2,0,50,1,0
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            beatmap.get_uninherited_point_at(0.0).unwrap().get_time(),
+            1000
+        );
+        assert_eq!(beatmap.get_bpm_at(2500.0), 120.0);
+        assert_eq!(beatmap.get_bpm_at(3000.0), 240.0);
+        assert_eq!(beatmap.get_meter_at(500.0), 3);
+
+        assert_eq!(beatmap.get_slider_velocity_at(-1000.0), 1.0);
+        assert_eq!(beatmap.get_slider_velocity_at(0.0), 0.5);
+        assert_eq!(beatmap.get_slider_velocity_at(1500.0), 1.0);
+        assert_eq!(beatmap.get_slider_velocity_at(2999.0), 2.0);
+
+        assert_eq!(beatmap.get_sample_set_at(-1000.0), SampleBank::Normal);
+        assert_eq!(beatmap.get_volume_at(2500.0), 60);
+        assert_eq!(beatmap.get_sample_index_at(2500.0), 1);
+        assert!(!beatmap.is_kiai_at(1999.0));
+        assert!(beatmap.is_kiai_at(2000.0));
+        assert!(!beatmap.is_kiai_at(3000.0));
+
+        assert_eq!(beatmap.get_beat_at(2500.0), 3.0);
+        assert_eq!(beatmap.get_beat_at(0.0), -2.0);
+        assert_eq!(beatmap.get_measure_at(2500.0), 1.0);
+        assert_eq!(beatmap.get_measure_at(3500.0), 0.5);
     }
 }
@@ -0,0 +1,343 @@
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::error::{Error, Result};
+
+/// Longest run of fields considered when extracting new variables.
+const MAX_RUN: usize = 4;
+/// Most times a line is expanded, for variables that refer to each other.
+const MAX_EXPANSION_PASSES: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    name: String,
+    value: String,
+}
+
+impl Variable {
+    pub fn new(name: &str, value: &str) -> Self {
+        Variable {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let (name, value) = s.split_once('=').ok_or(Error::variable(0))?;
+        let name = name.trim();
+        if name.len() < 2 || !name.starts_with('$') {
+            return Err(Error::variable(0));
+        }
+
+        Ok(Variable::new(name, value.trim()))
+    }
+
+    /// The name including its leading `$`
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl std::fmt::Display for Variable {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{}={}", self.name, self.value)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    variables: Vec<Variable>,
+}
+
+impl std::fmt::Display for Variables {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.variables.is_empty() {
+            return write!(f, "");
+        }
+
+        let mut display_string = String::from("[Variables]\n");
+
+        for variable in &self.variables {
+            display_string.push_str(&format!("{}", variable));
+        }
+
+        write!(f, "{}", display_string)
+    }
+}
+
+impl Variables {
+    /// Adds a `$name=value` definition. A later definition of the same name
+    /// takes precedence over an earlier one.
+    pub fn parse_value(&mut self, value: &str) -> Result<()> {
+        self.variables.push(Variable::parse(value)?);
+
+        Ok(())
+    }
+
+    pub fn get_variables(&self) -> &Vec<Variable> {
+        &self.variables
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variables
+            .iter()
+            .rev()
+            .find(|variable| variable.name == name)
+            .map(|variable| variable.value.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    /// Replaces every field of `line` that names a variable with its value,
+    /// like osu! does. Values can use other variables, so this repeats until
+    /// the line stops changing. Fields that aren't a defined name, such as
+    /// `$ab` when only `$a` exists, are left alone.
+    pub fn expand<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if !line.contains('$') {
+            return Cow::Borrowed(line);
+        }
+
+        let fields = line.trim_start_matches([' ', '_']);
+        let indent = &line[..line.len() - fields.len()];
+        let mut expanded = fields.to_string();
+        // Variables that use themselves would never stop changing
+        for _ in 0..MAX_EXPANSION_PASSES {
+            let mut changed = false;
+            let next: Vec<&str> = expanded
+                .split(',')
+                .map(
+                    |field| match self.get(field).filter(|_| field.starts_with('$')) {
+                        Some(value) => {
+                            changed = true;
+                            value
+                        }
+                        None => field,
+                    },
+                )
+                .collect();
+            if !changed {
+                break;
+            }
+            expanded = next.join(",");
+        }
+
+        Cow::Owned(format!("{}{}", indent, expanded))
+    }
+
+    /// Rewrites expanded `lines` to use these variables wherever a run of
+    /// whole fields matches a value, then adds new variables for repeated
+    /// runs of fields as long as that makes the output shorter.
+    pub(crate) fn extract(&self, lines: &[String]) -> (Variables, Vec<String>) {
+        let mut lines: Vec<(&str, Vec<String>)> = lines
+            .iter()
+            .map(|line| {
+                let fields = line.trim_start_matches([' ', '_']);
+                let indent = &line[..line.len() - fields.len()];
+                (indent, fields.split(',').map(|s| s.to_string()).collect())
+            })
+            .collect();
+
+        let mut variables = self.clone();
+        for (index, variable) in self.variables.iter().enumerate().rev() {
+            let shadowed = self.variables[index + 1..]
+                .iter()
+                .any(|later| later.name == variable.name);
+            if shadowed || variable.value.is_empty() || variable.value.contains('$') {
+                continue;
+            }
+
+            let value: Vec<&str> = variable.value.split(',').collect();
+            for (_, fields) in lines.iter_mut() {
+                substitute(fields, &value, &variable.name);
+            }
+        }
+
+        let mut extracted = Vec::new();
+        let Some(prefix) = self.free_prefix(&lines) else {
+            return (variables, lines.into_iter().map(join).collect());
+        };
+
+        // value -> (total occurrences, lines it occurs on)
+        let mut counts: HashMap<String, (usize, Vec<usize>)> = HashMap::new();
+        for (line, (_, fields)) in lines.iter().enumerate() {
+            for (value, count) in runs(fields) {
+                let entry = counts.entry(value).or_default();
+                entry.0 += count;
+                entry.1.push(line);
+            }
+        }
+
+        // Extracting a value only ever lowers the count of the others and
+        // lengthens later names, so savings only go down and stale heap
+        // entries can be refreshed as they are popped.
+        let name_length = |extracted: usize| prefix.len() + (extracted + 1).to_string().len();
+        let mut heap: BinaryHeap<(usize, Reverse<String>)> = counts
+            .iter()
+            .map(|(value, (count, _))| {
+                (
+                    savings(value, *count, name_length(0)),
+                    Reverse(value.clone()),
+                )
+            })
+            .filter(|(saved, _)| *saved > 0)
+            .collect();
+
+        while let Some((saved, Reverse(value))) = heap.pop() {
+            let current = savings(&value, counts[&value].0, name_length(extracted.len()));
+            if current != saved {
+                if current > 0 {
+                    heap.push((current, Reverse(value)));
+                }
+                continue;
+            }
+
+            // Placeholder until the final width of the generated names is known
+            let placeholder = format!("$\u{0}{}", extracted.len());
+            let split: Vec<&str> = value.split(',').collect();
+            for line in std::mem::take(&mut counts.get_mut(&value).unwrap().1) {
+                let fields = &mut lines[line].1;
+                let before = runs(fields);
+                if substitute(fields, &split, &placeholder) {
+                    for (value, count) in before {
+                        counts.get_mut(&value).unwrap().0 -= count;
+                    }
+                    for (value, count) in runs(fields) {
+                        counts.get_mut(&value).unwrap().0 += count;
+                    }
+                }
+            }
+            extracted.push((placeholder, value));
+        }
+
+        // Every generated name has the same width so none is a prefix of another
+        let width = extracted.len().to_string().len();
+        let names: HashMap<String, String> = extracted
+            .into_iter()
+            .enumerate()
+            .map(|(index, (placeholder, value))| {
+                let name = format!("{}{:0width$}", prefix, index + 1, width = width);
+                variables.variables.push(Variable::new(&name, &value));
+                (placeholder, name)
+            })
+            .collect();
+
+        let lines = lines
+            .into_iter()
+            .map(|(indent, fields)| {
+                let fields = fields
+                    .into_iter()
+                    .map(|field| names.get(&field).cloned().unwrap_or(field))
+                    .collect();
+                join((indent, fields))
+            })
+            .collect();
+
+        (variables, lines)
+    }
+
+    /// Picks a `$` prefix for generated names that clashes with neither the
+    /// existing names nor any text in the lines.
+    fn free_prefix(&self, lines: &[(&str, Vec<String>)]) -> Option<String> {
+        ('a'..='z')
+            .map(|letter| format!("${}", letter))
+            .find(|prefix| {
+                self.variables.iter().all(|variable| {
+                    !variable.name.starts_with(prefix.as_str())
+                        && !prefix.starts_with(variable.name.as_str())
+                }) && lines
+                    .iter()
+                    .all(|(_, fields)| fields.iter().all(|field| !field.contains(prefix.as_str())))
+            })
+    }
+}
+
+fn join((indent, fields): (&str, Vec<String>)) -> String {
+    format!("{}{}", indent, fields.join(","))
+}
+
+/// Replaces every non-overlapping run of fields equal to `value` with `name`.
+/// Returns whether anything was replaced.
+fn substitute(fields: &mut Vec<String>, value: &[&str], name: &str) -> bool {
+    let mut replaced = false;
+    let mut index = 0;
+    while index + value.len() <= fields.len() {
+        if fields[index..index + value.len()]
+            .iter()
+            .zip(value)
+            .all(|(field, value)| field == value)
+        {
+            fields.splice(index..index + value.len(), [name.to_string()]);
+            replaced = true;
+        }
+        index += 1;
+    }
+    replaced
+}
+
+/// Counts the non-overlapping runs of up to [`MAX_RUN`] fields on a line that
+/// could be extracted, skipping any that already use a variable.
+fn runs(fields: &[String]) -> HashMap<String, usize> {
+    // value -> (occurrences, field after the last occurrence)
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+
+    for run in 1..=MAX_RUN.min(fields.len()) {
+        for start in 0..=fields.len() - run {
+            let fields = &fields[start..start + run];
+            if fields.iter().any(|field| field.contains('$')) {
+                continue;
+            }
+
+            let entry = counts.entry(fields.join(",")).or_default();
+            if entry.0 > 0 && start < entry.1 {
+                continue;
+            }
+            *entry = (entry.0 + 1, start + run);
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(value, (count, _))| (value, count))
+        .collect()
+}
+
+/// Bytes saved by extracting `value` used `count` times into a variable.
+/// Each use saves the value but costs the name, and the definition costs
+/// `name=value` plus a newline.
+fn savings(value: &str, count: usize, name_length: usize) -> usize {
+    let saved = count * value.len();
+    let cost = count * name_length + name_length + value.len() + 2;
+    saved.saturating_sub(cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(values: &[&str]) -> Variables {
+        let mut variables = Variables::default();
+        for value in values {
+            variables.parse_value(value).unwrap();
+        }
+        variables
+    }
+
+    #[test]
+    fn test_expand() {
+        let variables = variables(&["$a=$bb,1", "$bb=Sprite", "$self=$self,0"]);
+        assert_eq!(variables.expand(" $a,$bb"), " Sprite,1,Sprite");
+        // Only whole fields are names
+        assert_eq!(variables.expand("$ab,x$a,$a "), "$ab,x$a,$a ");
+        assert_eq!(variables.expand("$self").split(',').count(), 17);
+        assert!(matches!(variables.expand("F,0"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,457 @@
+//! Turns a slider's control points into the path the game draws, following
+//! osu!'s own approximations for each curve type.
+
+use std::f32::consts::PI;
+
+use crate::{section::CurveType, utils::Vec2};
+
+/// Largest distance a flattened Bezier may stray from the real curve.
+const BEZIER_TOLERANCE: f32 = 0.25;
+/// Largest distance a flattened arc may stray from the real circle.
+const CIRCULAR_ARC_TOLERANCE: f32 = 0.1;
+/// Points sampled between each pair of Catmull-Rom control points.
+const CATMULL_DETAIL: usize = 50;
+
+/// The sampled path of a slider, in absolute playfield coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliderPath {
+    points: Vec<Vec2>,
+    /// Distance along the path to each point, starting with 0
+    cumulative_length: Vec<f64>,
+}
+
+impl SliderPath {
+    /// Builds the path starting at `start` through `control_points`. When
+    /// `expected_length` is given the path is cut or extended in a straight
+    /// line to exactly that length, like the game does with a slider's
+    /// declared length.
+    pub fn new(
+        start: Vec2,
+        curve_type: CurveType,
+        control_points: &[Vec2],
+        expected_length: Option<f64>,
+    ) -> Self {
+        let vertices: Vec<Vec2> = std::iter::once(start)
+            .chain(control_points.iter().copied())
+            .collect();
+
+        let mut points: Vec<Vec2> = Vec::new();
+        for segment in segments(&vertices, curve_type) {
+            for point in approximate(segment, curve_type) {
+                if points.last() != Some(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        if points.is_empty() {
+            points.push(start);
+        }
+
+        let mut path = SliderPath {
+            points,
+            cumulative_length: Vec::new(),
+        };
+        path.calculate_length(expected_length);
+        path
+    }
+
+    /// The points of the path as a polyline.
+    pub fn get_points(&self) -> &Vec<Vec2> {
+        &self.points
+    }
+
+    /// Length of the path in osu! pixels.
+    pub fn get_length(&self) -> f64 {
+        self.cumulative_length.last().copied().unwrap_or_default()
+    }
+
+    /// Position at `progress` along the path, from 0 at the head to 1 at the
+    /// end. Progress outside that range is clamped.
+    pub fn position_at(&self, progress: f64) -> Vec2 {
+        let distance = progress.clamp(0.0, 1.0) * self.get_length();
+        let index = self
+            .cumulative_length
+            .partition_point(|length| *length < distance);
+
+        if index == 0 {
+            return self.points[0];
+        }
+        if index >= self.points.len() {
+            return self.points[self.points.len() - 1];
+        }
+
+        let (p0, p1) = (self.points[index - 1], self.points[index]);
+        let (d0, d1) = (
+            self.cumulative_length[index - 1],
+            self.cumulative_length[index],
+        );
+        if (d1 - d0).abs() < 1e-7 {
+            return p0;
+        }
+
+        p0 + (p1 - p0) * ((distance - d0) / (d1 - d0)) as f32
+    }
+
+    /// Position at the end of the path, where the first slide ends.
+    pub fn end_position(&self) -> Vec2 {
+        self.position_at(1.0)
+    }
+
+    /// Samples the path every `step` osu! pixels, always including both ends.
+    pub fn sample(&self, step: f64) -> Vec<Vec2> {
+        let length = self.get_length();
+        if step <= 0.0 || length <= 0.0 {
+            return vec![self.position_at(0.0), self.end_position()];
+        }
+
+        let count = (length / step).ceil() as usize;
+        (0..=count)
+            .map(|i| self.position_at((i as f64 * step / length).min(1.0)))
+            .collect()
+    }
+
+    fn calculate_length(&mut self, expected_length: Option<f64>) {
+        let mut length = 0.0;
+        self.cumulative_length = vec![0.0];
+        for pair in self.points.windows(2) {
+            length += (pair[1] - pair[0]).length() as f64;
+            self.cumulative_length.push(length);
+        }
+
+        let Some(expected) = expected_length else {
+            return;
+        };
+        if expected == length || !expected.is_finite() {
+            return;
+        }
+
+        // The last length is always replaced
+        self.cumulative_length.pop();
+        let mut end = self.points.len() - 1;
+
+        if length > expected {
+            while self
+                .cumulative_length
+                .last()
+                .is_some_and(|length| *length >= expected)
+            {
+                self.cumulative_length.pop();
+                self.points.pop();
+                end = end.saturating_sub(1);
+            }
+        }
+
+        if end == 0 || self.cumulative_length.is_empty() {
+            // The expected length is zero or negative
+            self.points.truncate(1);
+            self.cumulative_length = vec![0.0];
+            return;
+        }
+
+        let last = self.cumulative_length[self.cumulative_length.len() - 1];
+        let direction = (self.points[end] - self.points[end - 1]).normalized();
+        self.points[end] = self.points[end - 1] + direction * (expected - last) as f32;
+        self.cumulative_length.push(expected);
+    }
+}
+
+/// Splits the control points into segments wherever a point is repeated,
+/// which is how the file format marks a red anchor. Each segment shares its
+/// end point with the start of the next.
+fn segments(vertices: &[Vec2], curve_type: CurveType) -> Vec<&[Vec2]> {
+    // Catmull-Rom sliders from stable ignore red anchors
+    if curve_type == CurveType::CentripetalCatmullRom {
+        return vec![vertices];
+    }
+
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for end in 1..vertices.len().saturating_sub(1) {
+        if vertices[end] == vertices[end - 1] {
+            segments.push(&vertices[start..end]);
+            start = end;
+        }
+    }
+    segments.push(&vertices[start..]);
+    segments
+}
+
+fn approximate(points: &[Vec2], curve_type: CurveType) -> Vec<Vec2> {
+    match curve_type {
+        CurveType::Linear => points.to_vec(),
+        CurveType::CentripetalCatmullRom => catmull(points),
+        CurveType::Perfect if points.len() == 3 => {
+            if is_linear(points[0], points[1], points[2]) {
+                // osu! stable draws collinear perfect curves as lines
+                points.to_vec()
+            } else {
+                circular_arc(points).unwrap_or_else(|| bezier(points))
+            }
+        }
+        CurveType::Perfect | CurveType::Bezier => bezier(points),
+    }
+}
+
+fn is_linear(a: Vec2, b: Vec2, c: Vec2) -> bool {
+    ((b.y - a.y) * (c.x - a.x) - (b.x - a.x) * (c.y - a.y)).abs() < 1e-3
+}
+
+/// Flattens a Bezier curve by subdividing it until each piece is close
+/// enough to a line.
+fn bezier(points: &[Vec2]) -> Vec<Vec2> {
+    let count = points.len();
+    let mut output = Vec::new();
+    if count == 0 {
+        return output;
+    }
+
+    let mut to_flatten = vec![points.to_vec()];
+    let mut left = vec![Vec2::default(); count * 2 - 1];
+    let mut right = vec![Vec2::default(); count];
+
+    while let Some(parent) = to_flatten.pop() {
+        if is_flat_enough(&parent) {
+            subdivide(&parent, &mut left, &mut right);
+            left[count..].copy_from_slice(&right[1..]);
+
+            output.push(parent[0]);
+            for i in 1..count - 1 {
+                let index = 2 * i;
+                output.push((left[index - 1] + left[index] * 2.0 + left[index + 1]) * 0.25);
+            }
+            continue;
+        }
+
+        subdivide(&parent, &mut left, &mut right);
+        to_flatten.push(right.clone());
+        to_flatten.push(left[..count].to_vec());
+    }
+
+    output.push(points[count - 1]);
+    output
+}
+
+fn is_flat_enough(points: &[Vec2]) -> bool {
+    points.windows(3).all(|p| {
+        (p[0] - p[1] * 2.0 + p[2]).length_squared() <= BEZIER_TOLERANCE * BEZIER_TOLERANCE * 4.0
+    })
+}
+
+/// Splits a Bezier curve in half with de Casteljau's algorithm.
+fn subdivide(points: &[Vec2], left: &mut [Vec2], right: &mut [Vec2]) {
+    let count = points.len();
+    let mut midpoints = points.to_vec();
+
+    for i in 0..count {
+        left[i] = midpoints[0];
+        right[count - i - 1] = midpoints[count - i - 1];
+
+        for j in 0..count - i - 1 {
+            midpoints[j] = (midpoints[j] + midpoints[j + 1]) / 2.0;
+        }
+    }
+}
+
+fn catmull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut output = Vec::new();
+
+    for i in 0..points.len().saturating_sub(1) {
+        let v1 = if i > 0 { points[i - 1] } else { points[i] };
+        let v2 = points[i];
+        let v3 = if i < points.len() - 1 {
+            points[i + 1]
+        } else {
+            v2 * 2.0 - v1
+        };
+        let v4 = if i < points.len() - 2 {
+            points[i + 2]
+        } else {
+            v3 * 2.0 - v2
+        };
+
+        for c in 0..CATMULL_DETAIL {
+            output.push(catmull_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                c as f32 / CATMULL_DETAIL as f32,
+            ));
+            output.push(catmull_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                (c + 1) as f32 / CATMULL_DETAIL as f32,
+            ));
+        }
+    }
+
+    output
+}
+
+fn catmull_point(v1: Vec2, v2: Vec2, v3: Vec2, v4: Vec2, t: f32) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t * t2;
+
+    let axis = |a: f32, b: f32, c: f32, d: f32| {
+        0.5 * (2.0 * b
+            + (-a + c) * t
+            + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2
+            + (-a + 3.0 * b - 3.0 * c + d) * t3)
+    };
+
+    Vec2::new(axis(v1.x, v2.x, v3.x, v4.x), axis(v1.y, v2.y, v3.y, v4.y))
+}
+
+/// Flattens the arc of the circle through three points, or `None` when the
+/// circle is too large to draw.
+fn circular_arc(points: &[Vec2]) -> Option<Vec<Vec2>> {
+    let (a, b, c) = (points[0], points[1], points[2]);
+
+    let d = 2.0 * (a.x * (b - c).y + b.x * (c - a).y + c.x * (a - b).y);
+    if d.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let (a_sq, b_sq, c_sq) = (a.length_squared(), b.length_squared(), c.length_squared());
+    let centre = Vec2::new(
+        a_sq * (b - c).y + b_sq * (c - a).y + c_sq * (a - b).y,
+        a_sq * (c - b).x + b_sq * (a - c).x + c_sq * (b - a).x,
+    ) / d;
+
+    let (d_a, d_c) = (a - centre, c - centre);
+    let radius = d_a.length();
+    let theta_start = d_a.y.atan2(d_a.x);
+    let mut theta_end = d_c.y.atan2(d_c.x);
+    while theta_end < theta_start {
+        theta_end += 2.0 * PI;
+    }
+
+    let mut direction = 1.0;
+    let mut theta_range = theta_end - theta_start;
+
+    // Go the other way around if b is not between a and c
+    let ortho_a_to_c = Vec2::new((c - a).y, -(c - a).x);
+    if ortho_a_to_c.dot(b - a) < 0.0 {
+        direction = -direction;
+        theta_range = 2.0 * PI - theta_range;
+    }
+
+    let amount = if 2.0 * radius <= CIRCULAR_ARC_TOLERANCE {
+        2
+    } else {
+        let step = 2.0 * (1.0 - CIRCULAR_ARC_TOLERANCE / radius).acos();
+        ((theta_range / step).ceil() as usize).max(2)
+    };
+    if !radius.is_finite() || amount >= 1000 {
+        return None;
+    }
+
+    Some(
+        (0..amount)
+            .map(|i| {
+                let theta = theta_start + direction * i as f32 / (amount - 1) as f32 * theta_range;
+                centre + Vec2::new(theta.cos(), theta.sin()) * radius
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 0.01
+    }
+
+    #[test]
+    fn test_linear() {
+        let path = SliderPath::new(
+            Vec2::new(0.0, 0.0),
+            CurveType::Linear,
+            &[Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0)],
+            Some(150.0),
+        );
+        assert_eq!(path.get_length(), 150.0);
+        assert!(close(path.position_at(0.5), Vec2::new(75.0, 0.0)));
+        assert!(close(path.end_position(), Vec2::new(100.0, 50.0)));
+
+        // Extended along the last segment
+        let path = SliderPath::new(
+            Vec2::new(0.0, 0.0),
+            CurveType::Linear,
+            &[Vec2::new(100.0, 0.0)],
+            Some(120.0),
+        );
+        assert!(close(path.end_position(), Vec2::new(120.0, 0.0)));
+        assert_eq!(path.sample(50.0).len(), 4);
+    }
+
+    #[test]
+    fn test_perfect() {
+        let path = SliderPath::new(
+            Vec2::new(0.0, 0.0),
+            CurveType::Perfect,
+            &[Vec2::new(100.0, 100.0), Vec2::new(200.0, 0.0)],
+            None,
+        );
+        // A half circle of radius 100
+        assert!((path.get_length() - 100.0 * std::f64::consts::PI).abs() < 0.5);
+        // The arc is flattened, so the middle sits slightly inside the circle
+        assert!(path.position_at(0.5).distance(Vec2::new(100.0, 100.0)) < 0.2);
+        assert!(close(path.end_position(), Vec2::new(200.0, 0.0)));
+
+        // Collinear points are drawn as lines
+        let path = SliderPath::new(
+            Vec2::new(0.0, 0.0),
+            CurveType::Perfect,
+            &[Vec2::new(50.0, 0.0), Vec2::new(100.0, 0.0)],
+            None,
+        );
+        assert_eq!(path.get_length(), 100.0);
+    }
+
+    #[test]
+    fn test_bezier() {
+        let path = SliderPath::new(
+            Vec2::new(0.0, 0.0),
+            CurveType::Bezier,
+            &[Vec2::new(50.0, 100.0), Vec2::new(100.0, 0.0)],
+            None,
+        );
+        assert!(close(path.position_at(0.0), Vec2::new(0.0, 0.0)));
+        assert!(close(path.end_position(), Vec2::new(100.0, 0.0)));
+        // The curve peaks at half the height of its middle control point
+        let peak = path.get_points().iter().map(|p| p.y).fold(0.0, f32::max);
+        assert!((peak - 50.0).abs() < 0.5);
+
+        // A repeated point is a red anchor, turning it into two lines
+        let path = SliderPath::new(
+            Vec2::new(0.0, 0.0),
+            CurveType::Bezier,
+            &[
+                Vec2::new(100.0, 0.0),
+                Vec2::new(100.0, 0.0),
+                Vec2::new(100.0, 100.0),
+            ],
+            None,
+        );
+        assert_eq!(path.get_length(), 200.0);
+        assert!(close(path.position_at(0.5), Vec2::new(100.0, 0.0)));
+    }
+
+    #[test]
+    fn test_catmull() {
+        let path = SliderPath::new(
+            Vec2::new(0.0, 0.0),
+            CurveType::CentripetalCatmullRom,
+            &[Vec2::new(100.0, 50.0), Vec2::new(200.0, 0.0)],
+            Some(100.0),
+        );
+        assert!((path.get_length() - 100.0).abs() < 1e-9);
+        assert!(close(path.position_at(0.0), Vec2::new(0.0, 0.0)));
+        assert!(path.end_position().y > 0.0);
+    }
+}
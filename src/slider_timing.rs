@@ -0,0 +1,253 @@
+//! Works out when each part of a slider happens from the timing points and
+//! difficulty settings, the way osu! generates slider events.
+
+use crate::{
+    section::{HitObject, SliderHitObject},
+    Beatmap,
+};
+
+/// Slider distance travelled per beat at a slider multiplier and velocity of 1.
+const BASE_SCORING_DISTANCE: f64 = 100.0;
+/// How far before the real end the legacy last tick is judged, in milliseconds.
+pub const LEGACY_LAST_TICK_OFFSET: f64 = 36.0;
+/// Sliders longer than this many osu! pixels get no ticks past it.
+const MAX_TICK_LENGTH: f64 = 100000.0;
+
+/// A tick of a slider, the small scoring points along its body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SliderTick {
+    time: f64,
+    span: usize,
+    progress: f64,
+}
+
+impl SliderTick {
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Which slide the tick is on, starting from 0.
+    pub fn get_span(&self) -> usize {
+        self.span
+    }
+
+    /// Position of the tick along the path, from 0 at the head to 1 at the end.
+    pub fn get_progress(&self) -> f64 {
+        self.progress
+    }
+}
+
+/// The timing of one slider in a beatmap, see [`Beatmap::get_slider_timing`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliderTiming {
+    start_time: f64,
+    slides: usize,
+    length: f64,
+    velocity: f64,
+    tick_distance: f64,
+}
+
+impl SliderTiming {
+    /// Speed of the slider ball in osu! pixels per millisecond.
+    pub fn get_velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Distance between ticks in osu! pixels.
+    pub fn get_tick_distance(&self) -> f64 {
+        self.tick_distance
+    }
+
+    /// Time one slide takes.
+    pub fn get_span_duration(&self) -> f64 {
+        self.length / self.velocity
+    }
+
+    pub fn get_duration(&self) -> f64 {
+        self.get_span_duration() * self.slides as f64
+    }
+
+    pub fn get_end_time(&self) -> f64 {
+        self.start_time + self.get_duration()
+    }
+
+    /// Times the slider turns around, one fewer than it has slides.
+    pub fn get_repeat_times(&self) -> Vec<f64> {
+        (1..self.slides)
+            .map(|span| self.start_time + span as f64 * self.get_span_duration())
+            .collect()
+    }
+
+    /// Every tick on every slide in time order. Ticks too close to the end
+    /// of a slide are skipped, like in the game.
+    pub fn get_ticks(&self) -> Vec<SliderTick> {
+        let length = self.length.min(MAX_TICK_LENGTH);
+        let tick_distance = self.tick_distance.clamp(0.0, length);
+        if tick_distance == 0.0 || !tick_distance.is_finite() {
+            return Vec::new();
+        }
+
+        let min_distance_from_end = self.velocity * 10.0;
+        let span_duration = self.get_span_duration();
+        let mut ticks = Vec::new();
+
+        for span in 0..self.slides {
+            let span_start = self.start_time + span as f64 * span_duration;
+            let reversed = span % 2 == 1;
+
+            let mut span_ticks = Vec::new();
+            let mut distance = tick_distance;
+            while distance <= length && distance < length - min_distance_from_end {
+                let progress = distance / length;
+                let time_progress = if reversed { 1.0 - progress } else { progress };
+                span_ticks.push(SliderTick {
+                    time: span_start + time_progress * span_duration,
+                    span,
+                    progress,
+                });
+                distance += tick_distance;
+            }
+
+            if reversed {
+                span_ticks.reverse();
+            }
+            ticks.extend(span_ticks);
+        }
+
+        ticks
+    }
+
+    /// Time of the legacy last tick, which is what osu! judges instead of the
+    /// real end: [`LEGACY_LAST_TICK_OFFSET`] before the end, but never
+    /// earlier than halfway through the slider.
+    pub fn get_legacy_last_tick_time(&self) -> f64 {
+        (self.start_time + self.get_duration() / 2.0)
+            .max(self.get_end_time() - LEGACY_LAST_TICK_OFFSET)
+    }
+}
+
+impl Beatmap {
+    /// Works out the velocity, duration, repeats and ticks of `slider` from
+    /// the timing points active at its start.
+    pub fn get_slider_timing(&self, slider: &SliderHitObject) -> SliderTiming {
+        let start_time = slider.get_time() as f64;
+        let slider_velocity = self.get_slider_velocity_at(start_time);
+        let slider_multiplier = self.get_effective_slider_multiplier();
+        let tick_rate = self.get_effective_slider_tick_rate();
+
+        let scoring_distance = BASE_SCORING_DISTANCE * slider_multiplier * slider_velocity;
+        // Before v8 ticks ignored the slider velocity
+        let tick_distance = if self.get_version() < 8 {
+            scoring_distance / slider_velocity / tick_rate
+        } else {
+            scoring_distance / tick_rate
+        };
+
+        SliderTiming {
+            start_time,
+            slides: slider.get_span_count(),
+            length: slider.get_path().get_length(),
+            velocity: scoring_distance / self.get_beat_length_at(start_time),
+            tick_distance,
+        }
+    }
+
+    /// When a hit object ends, working out slider durations from the timing points.
+    pub fn get_hit_object_end_time(&self, hit_object: &HitObject) -> f64 {
+        match hit_object {
+            HitObject::Slider(slider) => self.get_slider_timing(slider).get_end_time(),
+            _ => hit_object.get_end_time().unwrap_or_default() as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Beatmap, BeatmapBuilder, CurveType, DifficultyBuilder, GeneralBuilder, HitObject,
+        MetadataBuilder, SliderBuilder, TimingPointBuilder,
+    };
+
+    fn beatmap(slider: SliderBuilder) -> Beatmap {
+        BeatmapBuilder::new()
+            .general(GeneralBuilder::new().audio_file("audio.mp3"))
+            .metadata(
+                MetadataBuilder::new()
+                    .title("Title")
+                    .artist("Artist")
+                    .creator("Creator")
+                    .version("Normal"),
+            )
+            .difficulty(
+                DifficultyBuilder::new()
+                    .slider_multiplier(1.0)
+                    .slider_tick_rate(2.0),
+            )
+            .timing_point(TimingPointBuilder::uninherited(0, 500.0))
+            .timing_point(TimingPointBuilder::inherited(1000, 2.0))
+            .timing_point(TimingPointBuilder::uninherited(3000, 250.0))
+            .hit_object(slider)
+            .build()
+            .unwrap()
+    }
+
+    fn slider(beatmap: &Beatmap) -> &crate::SliderHitObject {
+        match &beatmap.get_hit_objects()[0] {
+            HitObject::Slider(slider) => slider,
+            _ => panic!("expected a slider"),
+        }
+    }
+
+    #[test]
+    fn test_slider_timing() {
+        let beatmap = beatmap(
+            SliderBuilder::new(0, 0, 0, CurveType::Linear, 200.0)
+                .point(300, 0)
+                .slides(2),
+        );
+        let timing = beatmap.get_slider_timing(slider(&beatmap));
+
+        // 100 px per beat of 500ms
+        assert_eq!(timing.get_velocity(), 0.2);
+        assert_eq!(timing.get_span_duration(), 1000.0);
+        assert_eq!(timing.get_end_time(), 2000.0);
+        assert_eq!(timing.get_repeat_times(), vec![1000.0]);
+        assert_eq!(timing.get_legacy_last_tick_time(), 1964.0);
+        assert_eq!(
+            beatmap.get_hit_object_end_time(&beatmap.get_hit_objects()[0]),
+            2000.0
+        );
+
+        let ticks: Vec<(f64, usize)> = timing
+            .get_ticks()
+            .iter()
+            .map(|tick| (tick.get_time(), tick.get_span()))
+            .collect();
+        assert_eq!(
+            ticks,
+            vec![
+                (250.0, 0),
+                (500.0, 0),
+                (750.0, 0),
+                (1250.0, 1),
+                (1500.0, 1),
+                (1750.0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inherited_velocity() {
+        let beatmap =
+            beatmap(SliderBuilder::new(0, 0, 1500, CurveType::Linear, 100.0).point(300, 0));
+        let timing = beatmap.get_slider_timing(slider(&beatmap));
+
+        assert_eq!(timing.get_velocity(), 0.4);
+        assert_eq!(timing.get_span_duration(), 250.0);
+        // Ticks are spread by the slider velocity too, and the one at the
+        // end is dropped
+        assert!(timing.get_ticks().is_empty());
+        // Never earlier than halfway
+        assert_eq!(timing.get_legacy_last_tick_time(), 1714.0);
+    }
+}
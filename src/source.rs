@@ -0,0 +1,382 @@
+//! Keeps the original text of a parsed file around so that writing it back
+//! only touches the lines whose values actually changed.
+
+use std::collections::HashMap;
+
+use crate::sanitize::sanitize;
+
+/// The canonical rendering of one value in a section.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Entry {
+    /// A `Key: Value` pair, matched against the original file by key
+    Keyed(String, String),
+    /// An item of a list section, matched against the original file by content
+    Item(String),
+}
+
+impl Entry {
+    /// Splits the output of a key-value section's `Display` into entries.
+    pub fn keyed(section: &str) -> Vec<Entry> {
+        section
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let (key, _) = line.split_once(':')?;
+                Some(Entry::Keyed(key.trim().to_string(), line.to_string()))
+            })
+            .collect()
+    }
+
+    /// Renders a list item, which may span several lines.
+    pub fn item<T: std::fmt::Display>(item: &T) -> Entry {
+        Entry::Item(item.to_string().trim_end_matches('\n').to_string())
+    }
+
+    fn key(&self) -> Option<&str> {
+        match self {
+            Entry::Keyed(key, _) => Some(key),
+            Entry::Item(_) => None,
+        }
+    }
+
+    fn text(&self) -> &str {
+        match self {
+            Entry::Keyed(_, text) | Entry::Item(text) => text,
+        }
+    }
+}
+
+/// The entries of one section, identified by its header.
+#[derive(Debug, Clone)]
+pub(crate) struct SectionEntries {
+    pub header: &'static str,
+    pub entries: Vec<Entry>,
+}
+
+/// The lines an item of a list section was parsed from, 1-based and inclusive.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Span {
+    pub header: &'static str,
+    pub first: usize,
+    pub last: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Piece {
+    /// Comments, blank lines, headers and anything else that is kept verbatim
+    Text(String),
+    /// A key-value line of a keyed section
+    Keyed { key: String, raw: String },
+    /// The lines of the nth item of a list section
+    Item { index: usize, raw: String },
+}
+
+#[derive(Debug, Clone)]
+struct SourceSection {
+    header: Option<&'static str>,
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Source {
+    sections: Vec<SourceSection>,
+    snapshot: Vec<SectionEntries>,
+    newline: &'static str,
+}
+
+impl Source {
+    /// `snapshot` is the canonical rendering of every section right after
+    /// parsing and `spans` locates each list item of it in `text`.
+    pub fn new(text: &str, spans: &[Span], snapshot: Vec<SectionEntries>) -> Self {
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let raw_lines: Vec<&str> = text.split_inclusive('\n').collect();
+        let codes: HashMap<usize, &str> = sanitize(text)
+            .into_iter()
+            .map(|line| (line.number, line.code))
+            .collect();
+
+        let keyed: Vec<&str> = snapshot
+            .iter()
+            .filter(|section| section.entries.iter().any(|e| e.key().is_some()))
+            .map(|section| section.header)
+            .collect();
+
+        let mut sections = vec![SourceSection {
+            header: None,
+            pieces: Vec::new(),
+        }];
+        let mut spans = spans.iter().peekable();
+        let mut item_index = HashMap::new();
+        let mut number = 1;
+
+        while let Some(raw) = raw_lines.get(number - 1) {
+            let code = codes
+                .get(&number)
+                .map(|code| code.trim_start_matches('\u{feff}'))
+                .unwrap_or_default();
+
+            if let Some(section) = snapshot.iter().find(|section| section.header == code) {
+                sections.push(SourceSection {
+                    header: Some(section.header),
+                    pieces: vec![Piece::Text(raw.to_string())],
+                });
+                number += 1;
+                continue;
+            }
+
+            let current = sections.last_mut().unwrap();
+
+            if let Some(span) = spans.next_if(|span| span.first == number) {
+                let last = span.last.min(raw_lines.len());
+                let index = item_index.entry(span.header).or_insert(0);
+                current.pieces.push(Piece::Item {
+                    index: *index,
+                    raw: raw_lines[number - 1..last].concat(),
+                });
+                *index += 1;
+                number = last + 1;
+                continue;
+            }
+
+            let piece = match (current.header, code.split_once(':')) {
+                (Some(header), Some((key, _))) if keyed.contains(&header) => Piece::Keyed {
+                    key: key.trim().to_string(),
+                    raw: raw.to_string(),
+                },
+                _ => Piece::Text(raw.to_string()),
+            };
+            current.pieces.push(piece);
+            number += 1;
+        }
+
+        Source {
+            sections,
+            snapshot,
+            newline,
+        }
+    }
+
+    /// Writes the original text with every changed entry replaced by its
+    /// canonical rendering from `current`.
+    pub fn render(&self, current: &[SectionEntries]) -> String {
+        let mut output = String::new();
+
+        for (position, section) in self.sections.iter().enumerate() {
+            let Some(header) = section.header else {
+                for piece in &section.pieces {
+                    match piece {
+                        Piece::Text(raw) | Piece::Keyed { raw, .. } | Piece::Item { raw, .. } => {
+                            output += raw
+                        }
+                    }
+                }
+                continue;
+            };
+
+            let before = self.entries(&self.snapshot, header);
+            let after = self.entries(current, header);
+            let is_last = !self.sections[position + 1..]
+                .iter()
+                .any(|s| s.header == Some(header));
+
+            if before
+                .iter()
+                .chain(after.iter())
+                .any(|entry| entry.key().is_some())
+            {
+                self.render_keyed(&mut output, &section.pieces, before, after, is_last);
+            } else {
+                self.render_items(&mut output, &section.pieces, before, after, is_last);
+            }
+        }
+
+        for section in current {
+            let before = self.entries(&self.snapshot, section.header);
+            let in_source = self
+                .sections
+                .iter()
+                .any(|s| s.header == Some(section.header));
+
+            if !in_source && before != section.entries.as_slice() {
+                if !output.is_empty() && !output.ends_with(&format!("{0}{0}", self.newline)) {
+                    output += self.newline;
+                }
+                output += section.header;
+                output += self.newline;
+                for entry in &section.entries {
+                    self.push_entry(&mut output, entry);
+                }
+            }
+        }
+
+        output
+    }
+
+    fn entries<'a>(&self, sections: &'a [SectionEntries], header: &str) -> &'a [Entry] {
+        sections
+            .iter()
+            .find(|section| section.header == header)
+            .map(|section| section.entries.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn push_entry(&self, output: &mut String, entry: &Entry) {
+        if !output.is_empty() && !output.ends_with('\n') {
+            *output += self.newline;
+        }
+        for line in entry.text().lines() {
+            *output += line;
+            *output += self.newline;
+        }
+    }
+
+    /// Position after which new entries are added: the last value of the
+    /// section, or its header when it has none.
+    fn anchor(pieces: &[Piece]) -> usize {
+        pieces
+            .iter()
+            .rposition(|piece| !matches!(piece, Piece::Text(_)))
+            .unwrap_or(0)
+    }
+
+    fn render_keyed(
+        &self,
+        output: &mut String,
+        pieces: &[Piece],
+        before: &[Entry],
+        after: &[Entry],
+        is_last: bool,
+    ) {
+        let find = |entries: &'_ [Entry], key: &str| -> Option<String> {
+            entries
+                .iter()
+                .find(|entry| entry.key() == Some(key))
+                .map(|entry| entry.text().to_string())
+        };
+
+        let anchor = Self::anchor(pieces);
+        let mut written: Vec<&str> = Vec::new();
+
+        for (position, piece) in pieces.iter().enumerate() {
+            match piece {
+                Piece::Text(raw) | Piece::Item { raw, .. } => *output += raw,
+                Piece::Keyed { key, raw } => {
+                    let now = find(after, key);
+                    if find(before, key) == now {
+                        *output += raw;
+                    } else if !written.contains(&key.as_str()) {
+                        if let Some(now) = now {
+                            self.push_entry(output, &Entry::Keyed(key.clone(), now));
+                        }
+                    }
+                    written.push(key);
+                }
+            }
+
+            if position == anchor && is_last {
+                for entry in after {
+                    let key = entry.key().unwrap_or_default();
+                    let in_source = pieces
+                        .iter()
+                        .any(|piece| matches!(piece, Piece::Keyed { key: k, .. } if k == key));
+                    if !in_source && find(before, key).as_deref() != Some(entry.text()) {
+                        self.push_entry(output, entry);
+                    }
+                }
+            }
+        }
+    }
+
+    fn render_items(
+        &self,
+        output: &mut String,
+        pieces: &[Piece],
+        before: &[Entry],
+        after: &[Entry],
+        is_last: bool,
+    ) {
+        let (kept, inserted) = align(before, after);
+        let anchor = Self::anchor(pieces);
+
+        for (position, piece) in pieces.iter().enumerate() {
+            match piece {
+                Piece::Text(raw) | Piece::Keyed { raw, .. } => *output += raw,
+                Piece::Item { index, raw } => {
+                    for entry in inserted.get(*index).into_iter().flatten() {
+                        self.push_entry(output, entry);
+                    }
+                    if kept.get(*index).copied().unwrap_or(false) {
+                        *output += raw;
+                    }
+                }
+            }
+
+            if position == anchor && is_last {
+                for entry in inserted.get(before.len()).into_iter().flatten() {
+                    self.push_entry(output, entry);
+                }
+            }
+        }
+    }
+}
+
+/// Matches the entries of `before` and `after` along their longest common
+/// subsequence. Returns whether each entry of `before` is kept, and for each
+/// position in `before` (plus one past the end) the new entries that go
+/// in front of it.
+fn align<'a>(before: &[Entry], after: &'a [Entry]) -> (Vec<bool>, Vec<Vec<&'a Entry>>) {
+    let mut kept = vec![false; before.len()];
+    let mut inserted: Vec<Vec<&Entry>> = vec![Vec::new(); before.len() + 1];
+
+    let prefix = before.iter().zip(after).take_while(|(a, b)| a == b).count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    kept[..prefix].fill(true);
+    kept[before.len() - suffix..].fill(true);
+
+    let old = &before[prefix..before.len() - suffix];
+    let new = &after[prefix..after.len() - suffix];
+
+    // Anything past this size is treated as a full rewrite of the middle
+    // rather than building a huge table.
+    const MAX_TABLE: usize = 1 << 22;
+
+    if old.is_empty() || new.is_empty() || old.len() * new.len() > MAX_TABLE {
+        inserted[prefix + old.len()].extend(new);
+        return (kept, inserted);
+    }
+
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            kept[prefix + i] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            inserted[prefix + i].push(&new[j]);
+            j += 1;
+        }
+    }
+    inserted[prefix + i].extend(&new[j..]);
+
+    (kept, inserted)
+}
@@ -0,0 +1,248 @@
+//! Works out how osu! offsets objects placed on top of each other so the
+//! ones underneath stay visible.
+
+use crate::{section::HitObject, utils::Vec2, Beatmap};
+
+/// Objects closer than this many osu! pixels count as placed on each other.
+const STACK_DISTANCE: f32 = 3.0;
+/// How far one stack height moves an object, in circle radii over 10.
+const STACK_OFFSET_MULTIPLIER: f64 = -6.4;
+
+/// What stacking needs to know about a hit object.
+struct StackObject {
+    time: f64,
+    end_time: f64,
+    position: Vec2,
+    end_position: Vec2,
+    /// The end of the path, wherever the slider really finishes
+    path_end_position: Vec2,
+    is_circle: bool,
+    is_slider: bool,
+}
+
+/// A hit object's place in its stack and where that draws it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StackedObject {
+    stack_height: i32,
+    position: Vec2,
+    end_position: Vec2,
+}
+
+impl StackedObject {
+    /// Positive heights move the object up and left, negative ones down
+    /// and right.
+    pub fn get_stack_height(&self) -> i32 {
+        self.stack_height
+    }
+
+    pub fn get_position(&self) -> Vec2 {
+        self.position
+    }
+
+    /// Where a slider ends after all of its slides, the position for
+    /// other objects.
+    pub fn get_end_position(&self) -> Vec2 {
+        self.end_position
+    }
+}
+
+impl Beatmap {
+    /// How many places each hit object is stacked, in file order, using
+    /// the stacking of the beatmap's file format version.
+    pub fn get_stack_heights(&self) -> Vec<i32> {
+        let objects: Vec<StackObject> = self
+            .hit_objects
+            .iter()
+            .map(|hit_object| {
+                let position = Vec2::new(hit_object.get_x() as f32, hit_object.get_y() as f32);
+                let (end_position, path_end_position) = match hit_object {
+                    HitObject::Slider(slider) => {
+                        (slider.get_end_position(), slider.get_path().end_position())
+                    }
+                    _ => (position, position),
+                };
+                StackObject {
+                    time: hit_object.get_time() as f64,
+                    end_time: self.get_hit_object_end_time(hit_object),
+                    position,
+                    end_position,
+                    path_end_position,
+                    is_circle: matches!(hit_object, HitObject::Circle(_)),
+                    is_slider: matches!(hit_object, HitObject::Slider(_)),
+                }
+            })
+            .collect();
+
+        let stack_threshold =
+            self.get_preempt() * self.get_general_stack_leniency().unwrap_or(0.7) as f64;
+        if self.get_version() >= 6 {
+            stack(&objects, stack_threshold)
+        } else {
+            stack_old(&objects, stack_threshold)
+        }
+    }
+
+    /// Every hit object's stack height and where it is drawn with it, in
+    /// file order.
+    pub fn get_stacked_objects(&self) -> Vec<StackedObject> {
+        self.hit_objects
+            .iter()
+            .zip(self.get_stack_heights())
+            .map(|(hit_object, stack_height)| {
+                let offset = self.get_stack_offset(stack_height);
+                let position = Vec2::new(hit_object.get_x() as f32, hit_object.get_y() as f32);
+                let end_position = match hit_object {
+                    HitObject::Slider(slider) => slider.get_end_position(),
+                    _ => position,
+                };
+                StackedObject {
+                    stack_height,
+                    position: position + offset,
+                    end_position: end_position + offset,
+                }
+            })
+            .collect()
+    }
+
+    /// How far an object `stack_height` places up its stack moves, which
+    /// grows with the circle radius.
+    pub(crate) fn get_stack_offset(&self, stack_height: i32) -> Vec2 {
+        let scale = self.get_circle_radius() / 64.0;
+        let offset = (stack_height as f64 * scale * STACK_OFFSET_MULTIPLIER) as f32;
+        Vec2::new(offset, offset)
+    }
+}
+
+/// The stacking of file format v6 and later, working backwards from the
+/// last object so stacks grow towards their first object.
+fn stack(objects: &[StackObject], stack_threshold: f64) -> Vec<i32> {
+    let mut heights = vec![0; objects.len()];
+
+    for i in (1..objects.len()).rev() {
+        if heights[i] != 0 || !(objects[i].is_circle || objects[i].is_slider) {
+            continue;
+        }
+
+        let mut top = i;
+        if objects[i].is_circle {
+            for n in (0..i).rev() {
+                let object_n = &objects[n];
+                if !(object_n.is_circle || object_n.is_slider) {
+                    continue;
+                }
+                if objects[top].time - object_n.end_time > stack_threshold {
+                    break;
+                }
+
+                // Circles under the end of a slider stack downwards from it
+                if object_n.is_slider
+                    && object_n.end_position.distance(objects[top].position) < STACK_DISTANCE
+                {
+                    let offset = heights[top] - heights[n] + 1;
+                    for j in n + 1..=i {
+                        if object_n.end_position.distance(objects[j].position) < STACK_DISTANCE {
+                            heights[j] -= offset;
+                        }
+                    }
+                    break;
+                }
+
+                if object_n.position.distance(objects[top].position) < STACK_DISTANCE {
+                    heights[n] = heights[top] + 1;
+                    top = n;
+                }
+            }
+        } else {
+            for n in (0..i).rev() {
+                let object_n = &objects[n];
+                if !(object_n.is_circle || object_n.is_slider) {
+                    continue;
+                }
+                if objects[top].time - object_n.time > stack_threshold {
+                    break;
+                }
+
+                if object_n.end_position.distance(objects[top].position) < STACK_DISTANCE {
+                    heights[n] = heights[top] + 1;
+                    top = n;
+                }
+            }
+        }
+    }
+
+    heights
+}
+
+/// The stacking of file formats before v6, working forwards from each
+/// object. Only the object's own height and those after it change.
+fn stack_old(objects: &[StackObject], stack_threshold: f64) -> Vec<i32> {
+    let mut heights = vec![0; objects.len()];
+
+    for i in 0..objects.len() {
+        let current = &objects[i];
+        if heights[i] != 0 && !current.is_slider {
+            continue;
+        }
+
+        let mut start_time = current.end_time;
+        let mut slider_stack = 0;
+        for j in i + 1..objects.len() {
+            // osu!stable compared with the later object's start time, as
+            // its end time was never set
+            if objects[j].time - stack_threshold > start_time {
+                break;
+            }
+
+            if objects[j].position.distance(current.position) < STACK_DISTANCE {
+                heights[i] += 1;
+                start_time = objects[j].time;
+            } else if objects[j].position.distance(current.path_end_position) < STACK_DISTANCE {
+                // Objects at a slider's end stack down and right instead
+                slider_stack += 1;
+                heights[j] -= slider_stack;
+                start_time = objects[j].time;
+            }
+        }
+    }
+
+    heights
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Beatmap;
+
+    fn beatmap(version: u8, hit_objects: &str) -> Beatmap {
+        let source = format!(
+            "osu file format v{}\n\n[General]\nStackLeniency: 0.7\n\n[Difficulty]\nCircleSize:4\nApproachRate:9\n\n[TimingPoints]\n0,500,4,2,0,100,1,0\n\n[HitObjects]\n{}",
+            version, hit_objects
+        );
+        Beatmap::from_bytes(source.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_stack_heights() {
+        let circles = "100,100,0,1,0\n100,100,100,1,0\n100,100,200,1,0\n";
+        assert_eq!(beatmap(14, circles).get_stack_heights(), vec![2, 1, 0]);
+        assert_eq!(beatmap(5, circles).get_stack_heights(), vec![2, 1, 0]);
+
+        // A circle at the end of the path of a slider that comes back to
+        // its head only stacks in old beatmaps
+        let slider = "100,100,0,2,0,L|200:100,2,100\n200,100,500,1,0\n";
+        assert_eq!(beatmap(14, slider).get_stack_heights(), vec![0, 0]);
+        assert_eq!(beatmap(5, slider).get_stack_heights(), vec![0, -1]);
+    }
+
+    #[test]
+    fn test_stacked_positions() {
+        let beatmap = beatmap(14, "100,100,0,2,0,L|200:100,1,100\n200,100,500,1,0\n");
+        let stacked = beatmap.get_stacked_objects();
+        let offset = beatmap.get_circle_radius() / 10.0;
+
+        assert_eq!(stacked[1].get_stack_height(), -1);
+        assert!((stacked[1].get_position().x as f64 - (200.0 + offset)).abs() < 1e-3);
+        assert!((stacked[1].get_position().y as f64 - (100.0 + offset)).abs() < 1e-3);
+        assert_eq!(stacked[0].get_position().x, 100.0);
+        assert!((stacked[0].get_end_position().x - 200.0).abs() < 1e-3);
+    }
+}
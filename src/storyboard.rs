@@ -1,26 +1,44 @@
-use std::{io::Read, path::Path};
+use std::{
+    ffi::OsString,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use crate::{
-    sanitize::sanitize,
-    section::{Command, OsuStoryboard},
+    error::{Error, Result},
+    sanitize::{command, sanitize},
+    section::{Command, OsuStoryboard, Variable, Variables},
+    source::{Entry, SectionEntries, Source, Span},
+    token::Section,
 };
 
+#[derive(Default)]
 pub struct Storyboard {
-    file_name: String,
+    file_name: Option<String>,
+    directory: Option<OsString>,
     pub storyboards: Vec<OsuStoryboard>,
+    variables: Variables,
+    source: Option<Source>,
+    lossless: bool,
+    extract_variables: bool,
 }
 
 impl std::fmt::Display for Storyboard {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        if self.storyboards.is_empty() {
-            return write!(f, "");
+        if self.extract_variables {
+            return write!(f, "{}", self.to_extracted_string());
         }
 
-        let mut display_string = String::from("[Events]\n");
+        if let (true, Some(source)) = (self.lossless, &self.source) {
+            return write!(f, "{}", source.render(&self.section_entries()));
+        }
 
-        for storyboard in &self.storyboards {
-            display_string += &format!("{}", storyboard);
+        let mut display_string = format!("{}", self.variables);
+        if !self.variables.is_empty() {
+            display_string.push('\n');
         }
+        display_string += &self.to_expanded_string();
 
         write!(f, "{}", display_string)
     }
@@ -29,8 +47,13 @@ impl std::fmt::Display for Storyboard {
 impl Storyboard {
     pub fn new(file_name: &str) -> Self {
         Storyboard {
-            file_name: file_name.to_string(),
+            file_name: Some(file_name.to_string()),
+            directory: None,
             storyboards: Vec::new(),
+            variables: Variables::default(),
+            source: None,
+            lossless: true,
+            extract_variables: false,
         }
     }
 
@@ -38,68 +61,250 @@ impl Storyboard {
         self.storyboards.push(storyboard);
     }
 
-    pub fn parse(osb_data: &str, file_name: &str) -> std::io::Result<Storyboard> {
-        let mut storyboard = Storyboard::new(file_name);
+    /// Parses a storyboard and names it `file_name`. Use [`str::parse`] to
+    /// parse without a name.
+    pub fn parse(osb_data: &str, file_name: &str) -> Result<Storyboard> {
+        let mut storyboard = osb_data.parse::<Storyboard>()?;
+        storyboard.change_file_name(file_name);
 
-        let sanitized = sanitize(osb_data);
+        Ok(storyboard)
+    }
+
+    fn parse_unnamed(osb_data: &str) -> Result<Storyboard> {
+        let mut storyboard = Storyboard::default();
 
-        let lines = sanitized
-            .lines()
-            .map(|line| line.to_string())
-            .collect::<Vec<String>>();
+        let lines = sanitize(osb_data);
+        let mut spans: Vec<Span> = Vec::new();
 
+        let mut section = None;
         let mut index = 0;
 
-        if let Some(line) = lines.get(index) {
-            if !line.contains("[Events]") {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    "Invalid Storyboard file",
-                ));
-            } else {
-                index += 1;
-
-                while let Some(line) = lines.get(index) {
-                    if let Ok(mut osu_storyboard) = OsuStoryboard::parse(line.as_str()) {
-                        index += 1;
-                        'command_loop: while let Some(potential_command) = lines.get(index) {
-                            if let Ok(command) = Command::parse(&potential_command) {
-                                osu_storyboard.add_command(command);
-
-                                index += 1;
-                            } else {
-                                break 'command_loop;
-                            }
+        while let Some(line) = lines.get(index) {
+            index += 1;
+
+            match line.code.trim_start_matches('\u{feff}') {
+                "[Variables]" => section = Some(Section::Variables),
+                "[Events]" => section = Some(Section::Events),
+                _ => match section {
+                    Some(Section::Variables) => {
+                        storyboard
+                            .variables
+                            .parse_value(line.code)
+                            .map_err(|e| e.at(line.number, line.raw))?;
+                        spans.push(Span {
+                            header: Section::Variables.header(),
+                            first: line.number,
+                            last: line.number,
+                        });
+                    }
+                    Some(Section::Events) => {
+                        let mut osu_storyboard =
+                            OsuStoryboard::parse(&storyboard.variables.expand(line.code))
+                                .map_err(|e| e.at(line.number, line.raw))?;
+
+                        let mut span = Span {
+                            header: Section::Events.header(),
+                            first: line.number,
+                            last: line.number,
+                        };
+                        while let Some(potential_command) = lines.get(index) {
+                            let Some((depth, code)) = command(potential_command) else {
+                                break;
+                            };
+
+                            Command::parse(&storyboard.variables.expand(code))
+                                .and_then(|command| {
+                                    osu_storyboard.add_command_at_depth(depth, command)
+                                })
+                                .map_err(|e| {
+                                    e.at(potential_command.number, potential_command.raw)
+                                })?;
+                            span.last = potential_command.number;
+                            index += 1;
                         }
                         storyboard.push_storyboard(osu_storyboard);
+                        spans.push(span);
                     }
-                }
+                    _ => return Err(Error::section(0).at(line.number, line.raw)),
+                },
             }
         }
 
+        storyboard.source = Some(Source::new(osb_data, &spans, storyboard.section_entries()));
+        storyboard.lossless = true;
+
         Ok(storyboard)
     }
 
-    pub fn parse_file(file: &str) -> std::io::Result<Storyboard> {
+    pub fn parse_file(file: &str) -> Result<Storyboard> {
         let path = Path::new(file);
-        let name = path.file_name().unwrap().to_str().unwrap();
-        let mut file = std::fs::File::open(path)?;
+        let mut storyboard = Storyboard::from_reader(std::fs::File::open(path)?)?;
+
+        if let Some(file_name) = path.file_name().and_then(|name| name.to_str()) {
+            storyboard.change_file_name(file_name);
+        }
+        storyboard.change_directory(OsString::from(path.parent().unwrap_or(Path::new(""))));
+
+        Ok(storyboard)
+    }
 
+    /// Parses a storyboard from any reader. The file name and directory are
+    /// left unset.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Storyboard> {
         let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        Storyboard::parse_unnamed(&contents)
+    }
+
+    /// Parses a storyboard from raw bytes, which must be valid UTF-8.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Storyboard> {
+        let osb_data = std::str::from_utf8(bytes)
+            .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+        Storyboard::parse_unnamed(osb_data)
+    }
+
+    pub fn save_with_name(&self, name: &str) -> Result<()> {
+        std::fs::write(name, self.to_string())?;
+
+        Ok(())
+    }
+
+    pub fn save_to_directory_with_name(&self, name: &str) -> Result<()> {
+        let directory = self.directory.clone().unwrap_or_default();
+        std::fs::write(PathBuf::from(directory).join(name), self.to_string())?;
+
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_with_name(self.required_file_name()?)
+    }
+
+    pub fn save_to_directory(&self) -> Result<()> {
+        self.save_to_directory_with_name(self.required_file_name()?)
+    }
+
+    fn required_file_name(&self) -> Result<&str> {
+        self.get_file_name().ok_or_else(|| {
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Storyboard has no file name",
+            ))
+        })
+    }
+
+    pub fn get_file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    pub fn get_directory(&self) -> Option<&OsString> {
+        self.directory.as_ref()
+    }
+
+    pub fn change_file_name(&mut self, file_name: &str) {
+        self.file_name = Some(file_name.to_string());
+    }
+
+    pub fn change_directory<D: Into<OsString>>(&mut self, directory: D) {
+        self.directory = Some(directory.into());
+    }
+
+    /// Whether a parsed storyboard is written back with its original
+    /// formatting and comments, only rewriting the objects that changed.
+    pub fn is_lossless(&self) -> bool {
+        self.lossless && self.source.is_some()
+    }
+
+    /// Lossless output is on by default for parsed storyboards. Turn it off
+    /// to write every object in canonical form.
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
+    /// The `[Variables]` definitions, with their `$` names.
+    pub fn get_variables(&self) -> &Vec<Variable> {
+        self.variables.get_variables()
+    }
+
+    pub fn get_variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name)
+    }
+
+    /// When on, output replaces repeated values with variables to shrink the
+    /// file, reusing the existing variables and adding new ones as needed.
+    /// This rewrites every line, so it takes precedence over lossless output.
+    pub fn set_extract_variables(&mut self, extract_variables: bool) {
+        self.extract_variables = extract_variables;
+    }
+
+    /// The `[Events]` section with every variable replaced by its value.
+    pub fn to_expanded_string(&self) -> String {
+        if self.storyboards.is_empty() {
+            return String::new();
+        }
+
+        let mut display_string = String::from("[Events]\n");
+
+        for storyboard in &self.storyboards {
+            display_string += &format!("{}", storyboard);
+        }
 
-        file.read_to_string(&mut contents)?;
-        Storyboard::parse(&contents, name)
+        display_string
     }
-    pub fn save_with_name(&self, name: &str) {
-        std::fs::write(name, self.to_string()).unwrap();
+
+    fn to_extracted_string(&self) -> String {
+        let lines: Vec<String> = self
+            .storyboards
+            .iter()
+            .flat_map(|storyboard| {
+                storyboard
+                    .to_string()
+                    .lines()
+                    .map(|line| line.to_string())
+                    .collect::<Vec<String>>()
+            })
+            .collect();
+        let (variables, lines) = self.variables.extract(&lines);
+
+        let mut display_string = format!("{}", variables);
+        if !variables.is_empty() {
+            display_string.push('\n');
+        }
+        if !lines.is_empty() {
+            display_string += "[Events]\n";
+            for line in lines {
+                display_string += &line;
+                display_string.push('\n');
+            }
+        }
+
+        display_string
     }
 
-    pub fn save(&self) {
-        self.save_with_name(self.get_file_name());
+    fn section_entries(&self) -> Vec<SectionEntries> {
+        vec![
+            SectionEntries {
+                header: Section::Variables.header(),
+                entries: self
+                    .variables
+                    .get_variables()
+                    .iter()
+                    .map(Entry::item)
+                    .collect(),
+            },
+            SectionEntries {
+                header: Section::Events.header(),
+                entries: self.storyboards.iter().map(Entry::item).collect(),
+            },
+        ]
     }
+}
 
-    pub fn get_file_name(&self) -> &str {
-        &self.file_name
+impl FromStr for Storyboard {
+    type Err = Error;
+
+    fn from_str(osb_data: &str) -> Result<Storyboard> {
+        Storyboard::parse_unnamed(osb_data)
     }
 }
 
@@ -110,6 +315,93 @@ mod tests {
     #[test]
     fn test_storyboard_parse() {
         let storyboard = Storyboard::parse_file("storyboard.osb").unwrap();
-        storyboard.save_with_name("test_storyboard.osb");
+        let path = std::env::temp_dir().join("osu_parser_test_storyboard.osb");
+        storyboard.save_with_name(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            storyboard.to_string()
+        );
+    }
+
+    #[test]
+    fn test_storyboard_from_bytes() {
+        let osb_data = std::fs::read("storyboard.osb").unwrap();
+        let storyboard = Storyboard::from_bytes(&osb_data).unwrap();
+
+        assert_eq!(storyboard.get_file_name(), None);
+        assert!(!storyboard.storyboards.is_empty());
+        assert!(storyboard.save().is_err());
+    }
+
+    #[test]
+    fn test_storyboard_nested_commands() {
+        let osb_data = "[Events]\nSprite,Foreground,Centre,\"sb/star.png\",320,240\n_L,1000,4\n__F,0,0,500,0,1\n__T,HitSoundClap,0,500\n___S,0,0,100,1,1.2\n_M,0,0,1000,0,0,640,480\n";
+        let mut storyboard: Storyboard = osb_data.parse().unwrap();
+
+        let commands = storyboard.storyboards[0].get_commands();
+        assert_eq!(commands.len(), 2);
+        let loop_commands = commands[0].get_commands().unwrap();
+        assert_eq!(loop_commands.len(), 2);
+        assert_eq!(loop_commands[1].get_commands().unwrap().len(), 1);
+        assert!(commands[1].get_commands().is_none());
+
+        storyboard.set_lossless(false);
+        assert_eq!(storyboard.to_string(), osb_data.replace('_', " "));
+
+        let error = "[Events]\nSprite,Foreground,Centre,\"sb/star.png\",320,240\n M,0,0,1000,0,0,640,480\n  F,0,0,500,0,1\n"
+            .parse::<Storyboard>()
+            .err()
+            .unwrap();
+        assert_eq!(error.line(), Some(4));
+    }
+
+    #[test]
+    fn test_storyboard_variables() {
+        let osb_data = "[Variables]\n$bg=Sprite,Background,Centre\n$fade=F,0,0,1000\n\n[Events]\n$bg,\"bg.jpg\",320,240\n $fade,0,1\n";
+        let mut storyboard: Storyboard = osb_data.parse().unwrap();
+
+        assert_eq!(storyboard.get_variable("$fade"), Some("F,0,0,1000"));
+        assert_eq!(storyboard.to_string(), osb_data);
+        assert_eq!(
+            storyboard.to_expanded_string(),
+            "[Events]\nSprite,Background,Centre,\"bg.jpg\",320,240\n F,0,0,1000,0,1\n"
+        );
+
+        storyboard.set_extract_variables(true);
+        assert_eq!(
+            storyboard.to_string(),
+            "[Variables]\n$bg=Sprite,Background,Centre\n$fade=F,0,0,1000\n\n[Events]\n$bg,\"bg.jpg\",320,240\n $fade,0,1\n"
+        );
+    }
+
+    #[test]
+    fn test_storyboard_extract_variables() {
+        let mut osb_data = String::from("[Events]\n");
+        for i in 0..20 {
+            osb_data += &format!("Sprite,Foreground,Centre,\"sb/star.png\",{},240\n", i * 10);
+            osb_data += " F,0,1000,2000,0,1\n";
+        }
+        let mut storyboard: Storyboard = osb_data.parse().unwrap();
+        storyboard.set_extract_variables(true);
+
+        let extracted = storyboard.to_string();
+        assert!(extracted.starts_with("[Variables]\n$a"));
+        assert!(extracted.len() < osb_data.len());
+
+        let reparsed: Storyboard = extracted.parse().unwrap();
+        assert_eq!(reparsed.to_expanded_string(), osb_data);
+    }
+
+    #[test]
+    fn test_storyboard_lossless() {
+        let osb_data = "[Events]\r\n// Background\r\nSprite,Background,Centre,\"bg.jpg\",320,240\r\n F,0,0,1000,0,1\r\n\r\nSprite,Foreground,Centre,\"fg.png\",320,240\r\n F,0,0,1000,0,1\r\n";
+        let mut storyboard: Storyboard = osb_data.parse().unwrap();
+        assert_eq!(storyboard.to_string(), osb_data);
+
+        storyboard.storyboards.remove(0);
+        assert_eq!(
+            storyboard.to_string(),
+            "[Events]\r\n// Background\r\n\r\nSprite,Foreground,Centre,\"fg.png\",320,240\r\n F,0,0,1000,0,1\r\n"
+        );
     }
 }
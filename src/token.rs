@@ -1,3 +1,5 @@
+use crate::error::{Error, Result};
+
 #[derive(Debug, PartialEq)]
 pub enum Section {
     General,
@@ -8,10 +10,11 @@ pub enum Section {
     TimingPoints,
     Colours,
     HitObjects,
+    Variables,
 }
 
 impl Section {
-    pub fn parse(s: &str) -> std::io::Result<Self> {
+    pub fn parse(s: &str) -> Result<Self> {
         match s {
             "[General]" => Ok(Section::General),
             "[Editor]" => Ok(Section::Editor),
@@ -21,10 +24,22 @@ impl Section {
             "[TimingPoints]" => Ok(Section::TimingPoints),
             "[Colours]" => Ok(Section::Colours),
             "[HitObjects]" => Ok(Section::HitObjects),
-            _ => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Invalid section : {}", s),
-            )),
+            "[Variables]" => Ok(Section::Variables),
+            _ => Err(Error::section(0)),
+        }
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Section::General => "[General]",
+            Section::Editor => "[Editor]",
+            Section::Metadata => "[Metadata]",
+            Section::Difficulty => "[Difficulty]",
+            Section::Events => "[Events]",
+            Section::TimingPoints => "[TimingPoints]",
+            Section::Colours => "[Colours]",
+            Section::HitObjects => "[HitObjects]",
+            Section::Variables => "[Variables]",
         }
     }
 }
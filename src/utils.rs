@@ -1,4 +1,10 @@
-#[derive(Debug, Clone)]
+/// Converts a time to what it becomes when the song plays `rate` times as fast.
+pub(crate) fn scale_time(time: i32, rate: f64) -> i32 {
+    (time as f64 / rate).round() as i32
+}
+
+/// An RGB colour, with components from 0 to 255.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color {
     r: i32,
     g: i32,
@@ -9,16 +15,29 @@ impl Color {
     pub fn new(r: i32, g: i32, b: i32) -> Self {
         Color { r, g, b }
     }
+
+    pub fn get_r(&self) -> i32 {
+        self.r
+    }
+
+    pub fn get_g(&self) -> i32 {
+        self.g
+    }
+
+    pub fn get_b(&self) -> i32 {
+        self.b
+    }
 }
 
-impl From<&str> for Color {
-    fn from(s: &str) -> Self {
-        let colors = s.splitn(3, ",").collect::<Vec<&str>>();
-        Color {
-            r: colors[0].parse().unwrap(),
-            g: colors[1].parse().unwrap(),
-            b: colors[2].parse().unwrap(),
-        }
+impl Color {
+    /// Parses a `r,g,b` triple, returning `None` if it is malformed.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut colors = s.splitn(3, ',').map(|c| c.trim().parse().ok());
+        Some(Color {
+            r: colors.next()??,
+            g: colors.next()??,
+            b: colors.next()??,
+        })
     }
 }
 
@@ -27,3 +46,82 @@ impl std::fmt::Display for Color {
         write!(f, "{},{},{}", self.r, self.g, self.b)
     }
 }
+
+/// A position or direction on the playfield, in osu! pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub fn new(x: f32, y: f32) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.x * self.x + self.y * self.y
+    }
+
+    pub fn distance(self, other: Vec2) -> f32 {
+        (self - other).length()
+    }
+
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The vector scaled to a length of 1, or zero if it has no length.
+    pub fn normalized(self) -> Vec2 {
+        let length = self.length();
+        if length == 0.0 {
+            Vec2::default()
+        } else {
+            self / length
+        }
+    }
+}
+
+impl std::ops::Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl std::ops::Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl std::ops::Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, scale: f32) -> Vec2 {
+        Vec2::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl std::ops::Div<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, scale: f32) -> Vec2 {
+        Vec2::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl std::ops::Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
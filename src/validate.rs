@@ -0,0 +1,481 @@
+//! Modding checks over a beatmap, reporting the kind of problems osu!'s
+//! AIMod points out before an upload.
+
+use std::path::PathBuf;
+
+use crate::{
+    section::{GameMode, HitObject},
+    Beatmap,
+};
+
+/// Snap divisors the osu! editor offers.
+const SNAP_DIVISORS: [f64; 8] = [1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0];
+/// Objects further than this many milliseconds off every snap are unsnapped.
+/// Times are written as whole milliseconds while beat lengths aren't, so
+/// snapped objects can be a little over one off.
+const SNAP_LENIENCY: f64 = 1.5;
+/// Spinners shorter than this many milliseconds are hard to spin at all.
+const MIN_SPINNER_LENGTH: f64 = 500.0;
+
+/// How bad a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth a look, but may be intended
+    Warning,
+    /// Breaks the beatmap or keeps it from being ranked
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The check a [`Diagnostic`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    UnsnappedObject,
+    UnorderedObject,
+    OverlappingNote,
+    ShortSpinner,
+    MissingAudio,
+    MissingBackground,
+    BreakOverlap,
+    ZeroLengthSlider,
+    InconsistentMetadata,
+}
+
+impl Rule {
+    /// A stable name for the rule, for filtering and suppressing it.
+    pub fn get_id(&self) -> &'static str {
+        match self {
+            Rule::UnsnappedObject => "unsnapped-object",
+            Rule::UnorderedObject => "unordered-object",
+            Rule::OverlappingNote => "overlapping-note",
+            Rule::ShortSpinner => "short-spinner",
+            Rule::MissingAudio => "missing-audio",
+            Rule::MissingBackground => "missing-background",
+            Rule::BreakOverlap => "break-overlap",
+            Rule::ZeroLengthSlider => "zero-length-slider",
+            Rule::InconsistentMetadata => "inconsistent-metadata",
+        }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.get_id())
+    }
+}
+
+/// A problem found by [`Beatmap::validate`] or [`Beatmap::validate_set`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    severity: Severity,
+    rule: Rule,
+    time: Option<i32>,
+    message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, rule: Rule, time: Option<i32>, message: String) -> Self {
+        Diagnostic {
+            severity,
+            rule,
+            time,
+            message,
+        }
+    }
+
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Where the problem is in milliseconds, `None` for the whole beatmap.
+    pub fn get_time(&self) -> Option<i32> {
+        self.time
+    }
+
+    /// The time as the osu! editor shows it, `mm:ss:mmm`.
+    pub fn get_timestamp(&self) -> Option<String> {
+        self.time.map(|time| {
+            let sign = if time < 0 { "-" } else { "" };
+            let time = time.unsigned_abs();
+            format!(
+                "{}{:02}:{:02}:{:03}",
+                sign,
+                time / 60000,
+                time / 1000 % 60,
+                time % 1000
+            )
+        })
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(timestamp) = self.get_timestamp() {
+            write!(f, "{} ", timestamp)?;
+        }
+        write!(f, "[{}] {}: {}", self.severity, self.rule, self.message)
+    }
+}
+
+impl Beatmap {
+    /// Runs every check on the beatmap, returning what it found in time
+    /// order after the problems with the whole beatmap. Audio and
+    /// background files are only looked for on disk when the beatmap has a
+    /// directory.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        self.check_files(&mut diagnostics);
+        self.check_objects(&mut diagnostics);
+        self.check_overlapping_notes(&mut diagnostics);
+        self.check_breaks(&mut diagnostics);
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.time);
+        diagnostics
+    }
+
+    /// Checks that the difficulties of a set share their metadata and
+    /// audio, comparing each to the first.
+    pub fn validate_set(beatmaps: &[Beatmap]) -> Vec<Diagnostic> {
+        let Some((first, rest)) = beatmaps.split_first() else {
+            return Vec::new();
+        };
+        let expected_fields = first.get_set_fields();
+        let mut diagnostics = Vec::new();
+        for beatmap in rest {
+            let fields = beatmap.get_set_fields();
+            for ((name, expected), (_, found)) in expected_fields.iter().zip(fields) {
+                if *expected != found {
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        Rule::InconsistentMetadata,
+                        None,
+                        format!(
+                            "[{}] has {} \"{}\" but [{}] has \"{}\"",
+                            beatmap.get_metadata_version(),
+                            name,
+                            found,
+                            first.get_metadata_version(),
+                            expected
+                        ),
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    /// The fields every difficulty of a set should share.
+    fn get_set_fields(&self) -> [(&'static str, String); 8] {
+        // Tags may be in any order
+        let mut tags: Vec<&str> = self.get_metadata_tags().split_whitespace().collect();
+        tags.sort_unstable();
+        tags.dedup();
+
+        [
+            ("title", self.get_metadata_title().to_string()),
+            (
+                "unicode title",
+                self.get_metadata_unicode_title().to_string(),
+            ),
+            ("artist", self.get_metadata_artist().to_string()),
+            (
+                "unicode artist",
+                self.get_metadata_unicode_artist().to_string(),
+            ),
+            ("creator", self.get_metadata_creator().to_string()),
+            ("source", self.get_metadata_source().to_string()),
+            ("tags", tags.join(" ")),
+            (
+                "audio file",
+                self.get_general_audio_file().unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn check_files(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let directory = self.get_directory().map(PathBuf::from);
+        let missing = |file: &str| {
+            directory
+                .as_ref()
+                .is_some_and(|directory| !directory.join(file).is_file())
+        };
+
+        match self
+            .get_general_audio_file()
+            .filter(|file| !file.is_empty())
+        {
+            None => diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                Rule::MissingAudio,
+                None,
+                "no AudioFilename is set".to_string(),
+            )),
+            Some(file) if missing(&file) => diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                Rule::MissingAudio,
+                None,
+                format!("audio file \"{}\" doesn't exist", file),
+            )),
+            Some(_) => {}
+        }
+
+        match self.events.get_background() {
+            None => diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                Rule::MissingBackground,
+                None,
+                "no background is set".to_string(),
+            )),
+            Some(file) if missing(file) => diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                Rule::MissingBackground,
+                None,
+                format!("background \"{}\" doesn't exist", file),
+            )),
+            Some(_) => {}
+        }
+    }
+
+    /// Checks the order, snapping and lengths of the hit objects.
+    fn check_objects(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let mut last_time = None;
+        for hit_object in &self.hit_objects {
+            let time = hit_object.get_time();
+            if last_time.is_some_and(|last_time| time < last_time) {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    Rule::UnorderedObject,
+                    Some(time),
+                    "object is before the one written above it".to_string(),
+                ));
+            }
+            last_time = Some(time);
+
+            self.check_snap(diagnostics, time as f64, "object");
+            match hit_object {
+                HitObject::Slider(slider) => {
+                    if slider.get_length() <= 0.0 || slider.get_path().get_length() <= 0.0 {
+                        diagnostics.push(Diagnostic::new(
+                            Severity::Error,
+                            Rule::ZeroLengthSlider,
+                            Some(time),
+                            "slider has no length".to_string(),
+                        ));
+                    } else {
+                        let end_time = self.get_hit_object_end_time(hit_object);
+                        self.check_snap(diagnostics, end_time, "slider end");
+                    }
+                }
+                HitObject::Spinner(spinner) => {
+                    let length = (spinner.get_end_time() - time) as f64;
+                    if length < MIN_SPINNER_LENGTH {
+                        diagnostics.push(Diagnostic::new(
+                            Severity::Warning,
+                            Rule::ShortSpinner,
+                            Some(time),
+                            format!("spinner is only {}ms long", length),
+                        ));
+                    }
+                    self.check_snap(diagnostics, spinner.get_end_time() as f64, "spinner end");
+                }
+                HitObject::Hold(hold) => {
+                    self.check_snap(diagnostics, hold.get_end_time() as f64, "hold end");
+                }
+                HitObject::Circle(_) => {}
+            }
+        }
+    }
+
+    /// Reports `time` when it is off every snap of the red line active there.
+    fn check_snap(&self, diagnostics: &mut Vec<Diagnostic>, time: f64, what: &str) {
+        let Some(point) = self.get_uninherited_point_at(time) else {
+            return;
+        };
+        let beat_length = self.get_beat_length_at(time);
+        let offset = time - point.get_time() as f64;
+
+        let closest = SNAP_DIVISORS
+            .iter()
+            .map(|divisor| {
+                let snap_length = beat_length / divisor;
+                let snaps = offset / snap_length;
+                ((snaps - snaps.round()).abs() * snap_length, divisor)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((distance, divisor)) = closest.filter(|(distance, _)| *distance > SNAP_LENIENCY)
+        {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                Rule::UnsnappedObject,
+                Some(time.round() as i32),
+                format!(
+                    "{} is {:.0}ms off the nearest 1/{} snap",
+                    what, distance, divisor
+                ),
+            ));
+        }
+    }
+
+    /// Notes in the same osu!mania column must not start before the one
+    /// before ends.
+    fn check_overlapping_notes(&self, diagnostics: &mut Vec<Diagnostic>) {
+        if self.get_general_mode().unwrap_or_default() != GameMode::Mania {
+            return;
+        }
+        let Ok(objects) = self.get_mania_objects() else {
+            return;
+        };
+
+        let mut column_ends: Vec<Option<f64>> = Vec::new();
+        for object in objects {
+            let column = object.get_column();
+            if column >= column_ends.len() {
+                column_ends.resize(column + 1, None);
+            }
+            if column_ends[column].is_some_and(|end| object.get_time() <= end) {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    Rule::OverlappingNote,
+                    Some(object.get_time() as i32),
+                    format!("note overlaps the one before it in column {}", column + 1),
+                ));
+            }
+            column_ends[column] = Some(object.get_end_time().unwrap_or(object.get_time()));
+        }
+    }
+
+    fn check_breaks(&self, diagnostics: &mut Vec<Diagnostic>) {
+        for osu_break in self.events.get_breaks() {
+            let (start, end) = (osu_break.get_start_time(), osu_break.get_end_time());
+            let overlaps = self.hit_objects.iter().any(|hit_object| {
+                let time = hit_object.get_time() as f64;
+                time < end as f64 && self.get_hit_object_end_time(hit_object) > start as f64
+            });
+            if overlaps {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    Rule::BreakOverlap,
+                    Some(start),
+                    "break overlaps an object".to_string(),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Beatmap, Rule, Severity};
+
+    #[test]
+    fn test_validate() {
+        let beatmap = Beatmap::from_bytes(
+            b"osu file format v14
+
+[Difficulty]
+SliderMultiplier:1
+
+[Events]
+2,1000,3000
+
+[TimingPoints]
+0,500,4,1,0,100,1,0
+
+[HitObjects]
+256,192,500,1,0,0:0:0:0:
+256,192,260,1,0,0:0:0:0:
+256,192,1500,2,0,L|356:192,1,0,0|0,0:0|0:0,0:0:0:0:
+256,192,4000,12,0,4250,0:0:0:0:
+",
+        )
+        .unwrap();
+        let diagnostics = beatmap.validate();
+
+        let rules: Vec<(Option<i32>, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.get_time(), diagnostic.get_rule().get_id()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                (None, "missing-audio"),
+                (None, "missing-background"),
+                (Some(260), "unordered-object"),
+                (Some(260), "unsnapped-object"),
+                (Some(1000), "break-overlap"),
+                (Some(1500), "zero-length-slider"),
+                (Some(4000), "short-spinner"),
+            ]
+        );
+        assert_eq!(diagnostics[2].get_severity(), Severity::Error);
+        assert_eq!(diagnostics[2].get_timestamp().unwrap(), "00:00:260");
+    }
+
+    #[test]
+    fn test_overlapping_notes_and_metadata() {
+        let mania = Beatmap::from_bytes(
+            b"osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+Mode: 3
+
+[Metadata]
+Title:Song
+Version:Hard
+Tags:b a
+
+[Difficulty]
+CircleSize:4
+
+[Events]
+0,0,\"bg.png\",0,0
+
+[TimingPoints]
+0,500,4,1,0,100,1,0
+
+[HitObjects]
+64,192,0,128,0,1000:0:0:0:0:
+64,192,500,1,0,0:0:0:0:
+192,192,500,1,0,0:0:0:0:
+",
+        )
+        .unwrap();
+        let diagnostics = mania.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].get_rule(), Rule::OverlappingNote);
+        assert_eq!(diagnostics[0].get_time(), Some(500));
+
+        let normal = Beatmap::from_bytes(
+            b"osu file format v14\n\n[General]\nAudioFilename: audio.mp3\n\n[Metadata]\nTitle:Song\nVersion:Normal\nTags:a b\n",
+        )
+        .unwrap();
+        assert!(Beatmap::validate_set(&[mania.clone(), normal]).is_empty());
+
+        let other = Beatmap::from_bytes(
+            b"osu file format v14\n\n[General]\nAudioFilename: other.mp3\n\n[Metadata]\nTitle:Song\nVersion:Insane\nTags:a b\n",
+        )
+        .unwrap();
+        let diagnostics = Beatmap::validate_set(&[mania, other]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].get_rule(), Rule::InconsistentMetadata);
+    }
+}
@@ -0,0 +1,60 @@
+//! Inputs that used to panic. Those in `parse` must now parse and have hit
+//! sounds and a star rating in every mode, and those in `fail` must fail
+//! with an error that points at the offending line.
+
+use osu_parser::{Beatmap, Error, GameMode, Storyboard};
+
+fn parse(path: &std::path::Path) -> Option<Result<(), Error>> {
+    let file = path.to_str().unwrap();
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("osu") => Some(Beatmap::parse_file(file).map(|_| ())),
+        Some("osb") => Some(Storyboard::parse_file(file).map(|_| ())),
+        _ => None,
+    }
+}
+
+fn get_paths(directory: &str) -> Vec<std::path::PathBuf> {
+    let paths: Vec<_> = std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert!(!paths.is_empty(), "{} is empty", directory);
+    paths
+}
+
+#[test]
+fn test_must_parse() {
+    for path in get_paths("tests/regressions/parse") {
+        if let Some(Err(error)) = parse(&path) {
+            panic!("{}: {}", path.display(), error);
+        }
+        if path.extension().is_some_and(|extension| extension == "osu") {
+            let beatmap = Beatmap::parse_file(path.to_str().unwrap()).unwrap();
+            for mode in [
+                GameMode::Osu,
+                GameMode::Taiko,
+                GameMode::Catch,
+                GameMode::Mania,
+            ] {
+                beatmap.get_difficulty(mode, &[]).unwrap();
+            }
+            beatmap.get_hitsound_events();
+        }
+    }
+}
+
+#[test]
+fn test_must_fail() {
+    for path in get_paths("tests/regressions/fail") {
+        match parse(&path) {
+            Some(Ok(())) => panic!("{}: parsed", path.display()),
+            Some(Err(error)) => assert!(
+                error.line().unwrap_or_default() > 0,
+                "{}: {}",
+                path.display(),
+                error
+            ),
+            None => {}
+        }
+    }
+}